@@ -24,6 +24,14 @@ defmodule TripleStore.Backend.RocksDB.NIF do
   - `:pos` - Predicate-Object-Subject index
   - `:osp` - Object-Subject-Predicate index
   - `:derived` - Stores inferred triples from reasoning
+  - `:counters` - 64-bit counters updated with `merge/5`
+  - `:gspo`, `:gpos`, `:gosp` - Quad indices of the named graphs, keyed by
+    graph ID followed by the triple
+  - `:spog` - Quad index keyed by the triple followed by the graph ID
+
+  Further column families can be added at runtime with `create_column_family/3`
+  and removed with `drop_column_family/2`. Every data function accepts the
+  name of any existing column family as an atom or binary.
   """
 
   @skip_compilation System.get_env("RUSTLER_SKIP_COMPILATION") == "1"
@@ -34,7 +42,21 @@ defmodule TripleStore.Backend.RocksDB.NIF do
     skip_compilation?: @skip_compilation
 
   @type db_ref :: reference()
-  @type column_family :: :id2str | :str2id | :spo | :pos | :osp | :derived
+  @type column_family ::
+          :id2str
+          | :str2id
+          | :spo
+          | :pos
+          | :osp
+          | :derived
+          | :counters
+          | :gspo
+          | :gpos
+          | :gosp
+          | :spog
+          | atom()
+          | String.t()
+  @type write_option :: {:sync | :disable_wal | :no_slowdown | :low_pri, boolean()}
 
   @doc """
   Verifies that the NIF is loaded correctly.
@@ -58,9 +80,82 @@ defmodule TripleStore.Backend.RocksDB.NIF do
 
   ## Arguments
   - `path` - Path to the database directory
+  - `opts` - Keyword list or map of RocksDB tuning options (default: `[]`)
+
+  ## Options
+
+  Database-wide options:
+  - `:create_if_missing` - Create the database if it does not exist
+    (default: `true`). Set to `false` to fail on a mistyped path instead of
+    creating an empty store
+  - `:block_cache_size` - Size in bytes of an LRU block cache shared by all
+    column families
+  - `:max_open_files` - Maximum number of open files (`-1` for unlimited)
+  - `:parallelism` - Number of background threads for flushes and compactions
+  - `:max_background_jobs` - Maximum concurrent background jobs
+  - `:bytes_per_sync` - Incrementally sync SST files every N bytes
+  - `:enable_statistics` - Collect the tickers and histograms returned by
+    `statistics/1` (default: `false`)
+  - `:transactional` - Open as an optimistic transaction database so that
+    `transaction_begin/2` can be used (default: `false`)
+  - `:wal_ttl_seconds` - Keep WAL files for this many seconds after they are
+    obsolete so that `updates_since/2` can read them
+  - `:wal_size_limit_mb` - Keep obsolete WAL files up to this total size in MB
+  - `:event_listener` - Pid sent flush, compaction and write stall events,
+    see "Events" below
+
+  Column family options (used as the default for every column family):
+  - `:write_buffer_size` - Memtable size in bytes
+  - `:max_write_buffer_number` - Maximum number of memtables
+  - `:target_file_size_base` - Target SST file size for level 1
+  - `:level_zero_file_num_compaction_trigger` - L0 file count that triggers compaction
+  - `:compaction_style` - `:level`, `:universal` or `:fifo`
+  - `:compression` - `:none`, `:snappy`, `:zlib`, `:bz2`, `:lz4`, `:lz4hc` or `:zstd`
+  - `:block_size` - Uncompressed data block size in bytes
+  - `:disable_auto_compactions` - Disable automatic compactions
+  - `:prefix_length` - Length in bytes of the fixed prefix extractor (`0` for
+    none). Defaults to `8` (one term ID) for the triple and quad indices
+  - `:bloom_filter_bits` - Bloom filter bits per key (`0` for no filter).
+    Defaults to `10` for the index and dictionary column families
+  - `:whole_key_filtering` - Also add whole keys to the bloom filter. Defaults
+    to `true` for column families without a prefix extractor
+
+  Prefix scans with a prefix at least `:prefix_length` bytes long use the prefix
+  bloom filters; shorter prefixes fall back to a total-order scan.
+
+  Per column family overrides:
+  - `:column_families` - Keyword list keyed by column family atom, each value
+    being a keyword list of column family options, e.g.
+    `column_families: [spo: [write_buffer_size: 128 * 1024 * 1024]]`. Column
+    families created with `create_column_family/3` are reopened automatically
+    and can be configured here as well
+
+  ## Events
+
+  With `:event_listener`, a RocksDB event listener sends the pid one message
+  per event, in the order RocksDB reports them:
+  - `{:rocksdb_event, :flush_completed, %{cf: cf, file_path: path, job_id: id,
+    num_entries: n, num_deletions: n, smallest_seqno: seq, largest_seqno: seq,
+    triggered_writes_slowdown: boolean, triggered_writes_stop: boolean}}`
+    when a memtable has been written to an L0 file
+  - `{:rocksdb_event, :compaction_completed, %{cf: cf, status: :ok |
+    {:error, reason}, job_id: id, base_input_level: level, output_level:
+    level, input_files: n, output_files: n, input_bytes: n, output_bytes: n,
+    input_records: n, output_records: n, elapsed_micros: n, manual: boolean,
+    trivial_move: boolean}}` when a compaction has finished, including
+    trivial moves, which move files to `output_level` without rewriting them
+  - `{:rocksdb_event, :stall_conditions_changed, %{cf: cf, previous:
+    condition, current: condition}}` when the write stall condition of a
+    column family, `:normal`, `:delayed` or `:stopped`, has changed
+
+  Files added by `ingest_external_files/4` and `bulk_load_ntriples/3` are
+  neither flushed nor compacted and produce no events. Events stop when the
+  listener exits.
 
   ## Returns
   - `{:ok, db_ref}` on success
+  - `{:error, {:invalid_option, key}}` if an option is unknown or has an invalid value
+  - `{:error, {:invalid_cf, cf}}` if a column family override names a column family that does not exist
   - `{:error, {:open_failed, reason}}` on failure
 
   ## Examples
@@ -69,14 +164,104 @@ defmodule TripleStore.Backend.RocksDB.NIF do
       iex> is_reference(db)
       true
 
+      iex> {:ok, db} = NIF.open("/tmp/test_db", block_cache_size: 512 * 1024 * 1024,
+      ...>   column_families: [spo: [compression: :lz4]])
+      iex> is_reference(db)
+      true
+
+  """
+  @spec open(String.t(), keyword() | map()) :: {:ok, db_ref()} | {:error, term()}
+  def open(_path, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Opens an existing database read-only.
+
+  Any number of read-only handles can be open alongside the read-write one,
+  e.g. for analytics queries next to the primary writer. The handle sees the
+  data as of the time it was opened, and every write function returns
+  `{:error, :read_only}` on it.
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `path` - Path to the database directory
+  - `opts` - Keyword list or map of options, as for `open/2` (default: `[]`).
+    `:transactional` is not allowed
+
+  ## Returns
+  - `{:ok, db_ref}` on success
+  - `{:error, {:invalid_option, key}}` if an option is unknown or has an invalid value
+  - `{:error, {:invalid_cf, cf}}` if a column family override names a column family that does not exist
+  - `{:error, {:open_failed, reason}}` if the database does not exist or cannot be opened
+
+  ## Examples
+
+      iex> {:ok, db} = NIF.open_read_only("/tmp/test_db")
+      iex> NIF.put(db, :id2str, "key", "value")
+      {:error, :read_only}
+
+  """
+  @spec open_read_only(String.t(), keyword() | map()) :: {:ok, db_ref()} | {:error, term()}
+  def open_read_only(_path, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Opens an existing database as a secondary instance of its primary.
+
+  A secondary follows a primary opened read-write by another handle or OS
+  process. It sees the primary's data as of the time it was opened and picks
+  up later writes with `try_catch_up_with_primary/1`. Every write function
+  returns `{:error, :read_only}` on it.
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `primary_path` - Path to the primary's database directory
+  - `secondary_path` - Directory for the secondary's own info logs
+  - `opts` - Keyword list or map of options, as for `open/2` (default: `[]`).
+    `:transactional` is not allowed
+
+  ## Returns
+  - `{:ok, db_ref}` on success
+  - `{:error, {:invalid_option, key}}` if an option is unknown or has an invalid value
+  - `{:error, {:invalid_cf, cf}}` if a column family override names a column family that does not exist
+  - `{:error, {:open_failed, reason}}` if the database does not exist or cannot be opened
+
+  ## Examples
+
+      iex> {:ok, db} = NIF.open_as_secondary("/data/store", "/tmp/store_secondary")
+      iex> NIF.try_catch_up_with_primary(db)
+      :ok
+
+  """
+  @spec open_as_secondary(String.t(), String.t(), keyword() | map()) ::
+          {:ok, db_ref()} | {:error, term()}
+  def open_as_secondary(_primary_path, _secondary_path, _opts \\ []),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Replays the primary's new writes into a secondary instance.
+
+  Column families created by the primary after the secondary was opened stay
+  invisible until the secondary is reopened.
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference, opened with `open_as_secondary/3`
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :not_secondary}` if the database was not opened as a secondary
+  - `{:error, {:catch_up_failed, reason}}` on failure
+
   """
-  @spec open(String.t()) :: {:ok, db_ref()} | {:error, {:open_failed, String.t()}}
-  def open(_path), do: :erlang.nif_error(:nif_not_loaded)
+  @spec try_catch_up_with_primary(db_ref()) :: :ok | {:error, term()}
+  def try_catch_up_with_primary(_db_ref), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   Closes the database and releases all resources.
 
-  After calling close, the database handle is no longer valid.
+  After calling close, the database handle is no longer valid. Waits for
+  running calls on the handle, such as `compact_range/5` or a waiting
+  `flush/3`, to finish first.
   Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
 
   ## Arguments
@@ -109,13 +294,87 @@ defmodule TripleStore.Backend.RocksDB.NIF do
   def get_path(_db_ref), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  Lists all column families in the database.
+  Lists the column families of the database as recorded on disk.
+
+  Includes the built-in column families and those created with
+  `create_column_family/3`. RocksDB's `default` column family is not listed.
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+
+  ## Returns
+  - `{:ok, names}` with the column family names as atoms (binaries for names
+    too long to be an atom)
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:list_cf_failed, reason}}` on failure
+
+  ## Examples
+
+      iex> {:ok, cfs} = NIF.list_column_families(db)
+      iex> :spo in cfs
+      true
+
+  """
+  @spec list_column_families(db_ref()) :: {:ok, [atom() | String.t()]} | {:error, term()}
+  def list_column_families(_db_ref), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates a column family at runtime.
+
+  The column family starts from the column family options passed to `open/2`,
+  overridden by `opts`. Options are not persisted: when the database is
+  reopened the column family gets the `open/2` options, including any
+  `:column_families` entry for its name.
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
+  - `opts` - Column family options as accepted by `open/2` (default: `[]`)
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_cf, cf}}` if the name is empty, `"default"` or a built-in
+    column family such as `:spo`
+  - `{:error, {:cf_exists, cf}}` if the column family already exists
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, {:create_cf_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> NIF.create_column_family(db, "graph_42", prefix_length: 8)
+      :ok
+      iex> NIF.put(db, "graph_42", "key", "value")
+      :ok
+
+  """
+  @spec create_column_family(db_ref(), atom() | String.t(), keyword() | map()) ::
+          :ok | {:error, term()}
+  def create_column_family(_db_ref, _cf, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Drops a column family created at runtime, together with all of its data.
+
+  The built-in column families cannot be dropped. Iterators already open on
+  the column family keep reading the data they saw.
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
 
   ## Returns
-  - List of column family atoms: `[:id2str, :str2id, :spo, :pos, :osp, :derived]`
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_cf, cf}}` if the column family does not exist or is built in
+  - `{:error, {:drop_cf_failed, reason}}` on other errors
   """
-  @spec list_column_families :: [column_family()]
-  def list_column_families, do: :erlang.nif_error(:nif_not_loaded)
+  @spec drop_column_family(db_ref(), atom() | String.t()) :: :ok | {:error, term()}
+  def drop_column_family(_db_ref, _cf), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   Checks if the database is open.
@@ -141,7 +400,7 @@ defmodule TripleStore.Backend.RocksDB.NIF do
 
   ## Arguments
   - `db_ref` - The database reference
-  - `cf` - The column family atom
+  - `cf` - The column family name as an atom or binary
   - `key` - The key as a binary
 
   ## Returns
@@ -173,14 +432,25 @@ defmodule TripleStore.Backend.RocksDB.NIF do
 
   ## Arguments
   - `db_ref` - The database reference
-  - `cf` - The column family atom
+  - `cf` - The column family name as an atom or binary
   - `key` - The key as a binary
   - `value` - The value as a binary
+  - `opts` - Write options (default: `[]`):
+    - `:sync` - fsync the WAL before returning, so the write survives a
+      machine crash
+    - `:disable_wal` - Skip the WAL; the write is lost on a crash until
+      the memtable is flushed
+    - `:no_slowdown` - Fail with `{:error, :write_stall}` instead of
+      waiting when RocksDB would stall the write
+    - `:low_pri` - Yield to other writes while compaction is behind
 
   ## Returns
   - `:ok` on success
   - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
   - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
   - `{:error, {:put_failed, reason}}` on other errors
 
   ## Examples
@@ -188,10 +458,13 @@ defmodule TripleStore.Backend.RocksDB.NIF do
       iex> {:ok, db} = NIF.open("/tmp/test_db")
       iex> NIF.put(db, :id2str, "key1", "value1")
       :ok
+      iex> NIF.put(db, :id2str, "key2", "value2", sync: true)
+      :ok
 
   """
-  @spec put(db_ref(), column_family(), binary(), binary()) :: :ok | {:error, term()}
-  def put(_db_ref, _cf, _key, _value), do: :erlang.nif_error(:nif_not_loaded)
+  @spec put(db_ref(), column_family(), binary(), binary(), [write_option()]) ::
+          :ok | {:error, term()}
+  def put(_db_ref, _cf, _key, _value, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   Deletes a key from a column family.
@@ -200,13 +473,17 @@ defmodule TripleStore.Backend.RocksDB.NIF do
 
   ## Arguments
   - `db_ref` - The database reference
-  - `cf` - The column family atom
+  - `cf` - The column family name as an atom or binary
   - `key` - The key to delete
+  - `opts` - Write options, as for `put/5` (default: `[]`)
 
   ## Returns
   - `:ok` on success (even if key didn't exist)
   - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
   - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
   - `{:error, {:delete_failed, reason}}` on other errors
 
   ## Examples
@@ -220,8 +497,8 @@ defmodule TripleStore.Backend.RocksDB.NIF do
       :not_found
 
   """
-  @spec delete(db_ref(), column_family(), binary()) :: :ok | {:error, term()}
-  def delete(_db_ref, _cf, _key), do: :erlang.nif_error(:nif_not_loaded)
+  @spec delete(db_ref(), column_family(), binary(), [write_option()]) :: :ok | {:error, term()}
+  def delete(_db_ref, _cf, _key, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   Checks if a key exists in a column family.
@@ -231,7 +508,7 @@ defmodule TripleStore.Backend.RocksDB.NIF do
 
   ## Arguments
   - `db_ref` - The database reference
-  - `cf` - The column family atom
+  - `cf` - The column family name as an atom or binary
   - `key` - The key to check
 
   ## Returns
@@ -255,6 +532,37 @@ defmodule TripleStore.Backend.RocksDB.NIF do
   @spec exists(db_ref(), column_family(), binary()) :: {:ok, boolean()} | {:error, term()}
   def exists(_db_ref, _cf, _key), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Gets the values for multiple keys from a column family.
+
+  Performs a single batched RocksDB lookup, so the whole list costs one
+  dirty scheduler hop instead of one per key.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
+  - `keys` - List of keys as binaries
+
+  ## Returns
+  - `{:ok, results}` with one `{:ok, value}` or `:not_found` per key, in input order
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:get_failed, reason}}` if any lookup fails
+
+  ## Examples
+
+      iex> {:ok, db} = NIF.open("/tmp/test_db")
+      iex> NIF.put(db, :id2str, "key1", "value1")
+      :ok
+      iex> NIF.multi_get(db, :id2str, ["key1", "nonexistent"])
+      {:ok, [{:ok, "value1"}, :not_found]}
+
+  """
+  @spec multi_get(db_ref(), column_family(), [binary()]) ::
+          {:ok, [{:ok, binary()} | :not_found]} | {:error, term()}
+  def multi_get(_db_ref, _cf, _keys), do: :erlang.nif_error(:nif_not_loaded)
+
   # ============================================================================
   # Batch Operations
   # ============================================================================
@@ -263,6 +571,7 @@ defmodule TripleStore.Backend.RocksDB.NIF do
   @type delete_operation :: {column_family(), binary()}
   @type mixed_put :: {:put, column_family(), binary(), binary()}
   @type mixed_delete :: {:delete, column_family(), binary()}
+  @type mixed_merge :: {:merge, :counters, binary(), integer()}
 
   @doc """
   Atomically writes multiple key-value pairs to column families.
@@ -273,11 +582,15 @@ defmodule TripleStore.Backend.RocksDB.NIF do
   ## Arguments
   - `db_ref` - The database reference
   - `operations` - List of `{cf, key, value}` tuples
+  - `opts` - Write options, as for `put/5` (default: `[]`)
 
   ## Returns
   - `:ok` on success
   - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
   - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
   - `{:error, {:batch_failed, reason}}` on other errors
 
   ## Examples
@@ -290,10 +603,12 @@ defmodule TripleStore.Backend.RocksDB.NIF do
       ...> ]
       iex> NIF.write_batch(db, operations)
       :ok
+      iex> NIF.write_batch(db, operations, disable_wal: true)
+      :ok
 
   """
-  @spec write_batch(db_ref(), [put_operation()]) :: :ok | {:error, term()}
-  def write_batch(_db_ref, _operations), do: :erlang.nif_error(:nif_not_loaded)
+  @spec write_batch(db_ref(), [put_operation()], [write_option()]) :: :ok | {:error, term()}
+  def write_batch(_db_ref, _operations, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   Atomically deletes multiple keys from column families.
@@ -304,11 +619,15 @@ defmodule TripleStore.Backend.RocksDB.NIF do
   ## Arguments
   - `db_ref` - The database reference
   - `operations` - List of `{cf, key}` tuples
+  - `opts` - Write options, as for `put/5` (default: `[]`)
 
   ## Returns
   - `:ok` on success
   - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
   - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
   - `{:error, {:batch_failed, reason}}` on other errors
 
   ## Examples
@@ -320,11 +639,11 @@ defmodule TripleStore.Backend.RocksDB.NIF do
       :ok
 
   """
-  @spec delete_batch(db_ref(), [delete_operation()]) :: :ok | {:error, term()}
-  def delete_batch(_db_ref, _operations), do: :erlang.nif_error(:nif_not_loaded)
+  @spec delete_batch(db_ref(), [delete_operation()], [write_option()]) :: :ok | {:error, term()}
+  def delete_batch(_db_ref, _operations, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  Atomically performs mixed put and delete operations.
+  Atomically performs mixed put, delete and merge operations.
 
   Uses RocksDB WriteBatch for atomic commit - either all operations succeed
   or none do. This is essential for maintaining consistency when updating
@@ -337,12 +656,19 @@ defmodule TripleStore.Backend.RocksDB.NIF do
   - `operations` - List of operations:
     - `{:put, cf, key, value}` for puts
     - `{:delete, cf, key}` for deletes
+    - `{:merge, :counters, key, delta}` to add a signed delta to a counter,
+      as for `merge/5`
+  - `opts` - Write options, as for `put/5` (default: `[]`)
 
   ## Returns
   - `:ok` on success
   - `{:error, :already_closed}` if database is closed
-  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid, or is not
+    `:counters` in a merge
   - `{:error, {:invalid_operation, op}}` if operation type is invalid
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
   - `{:error, {:batch_failed, reason}}` on other errors
 
   ## Examples
@@ -358,14 +684,211 @@ defmodule TripleStore.Backend.RocksDB.NIF do
       :ok
 
   """
-  @spec mixed_batch(db_ref(), [mixed_put() | mixed_delete()]) :: :ok | {:error, term()}
-  def mixed_batch(_db_ref, _operations), do: :erlang.nif_error(:nif_not_loaded)
+  @spec mixed_batch(db_ref(), [mixed_put() | mixed_delete() | mixed_merge()], [write_option()]) ::
+          :ok | {:error, term()}
+  def mixed_batch(_db_ref, _operations, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Atomically deletes every key in `[start_key, end_key)` of a column family.
+
+  Writes a single range tombstone instead of one tombstone per key, so large
+  ranges cost the same as small ones.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
+  - `start_key` - Inclusive start key
+  - `end_key` - Exclusive end key
+  - `opts` - Write options, as for `put/5` (default: `[]`)
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, :invalid_range}` if `start_key` is greater than `end_key`
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+  - `{:error, {:delete_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> {:ok, db} = NIF.open("/tmp/test_db")
+      iex> NIF.delete_range(db, :spo, <<1::64>>, <<2::64>>)
+      :ok
+
+  """
+  @spec delete_range(db_ref(), column_family(), binary(), binary(), [write_option()]) ::
+          :ok | {:error, term()}
+  def delete_range(_db_ref, _cf, _start_key, _end_key, _opts \\ []),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  # ============================================================================
+  # Triple Operations
+  # ============================================================================
+
+  @type term_id :: non_neg_integer()
+  @type triple :: {term_id(), term_id(), term_id()}
+  @type triples :: [triple()] | binary()
+
+  @doc """
+  Atomically inserts triples into the SPO, POS, and OSP indices.
+
+  The index keys are built natively and written with empty values in a
+  single WriteBatch, so either all triples are inserted or none are.
+  Inserting an existing triple is a no-op.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `triples` - List of `{subject_id, predicate_id, object_id}` tuples, or a
+    binary of packed 24-byte records (three 64-bit big-endian IDs each)
+  - `opts` - Keyword list of options:
+    - `:target` - `:indices` (default) writes the SPO, POS, and OSP keys;
+      `:derived` writes one SPO-ordered key per triple to the `:derived`
+      column family instead
+    - Any write option accepted by `put/5`
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, :invalid_triple}` if a triple is not three 64-bit IDs
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+  - `{:error, {:batch_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> {:ok, db} = NIF.open("/tmp/test_db")
+      iex> NIF.insert_triples(db, [{1, 2, 3}, {4, 5, 6}])
+      :ok
+      iex> NIF.insert_triples(db, <<7::64, 8::64, 9::64>>, target: :derived)
+      :ok
+
+  """
+  @spec insert_triples(db_ref(), triples(), keyword()) :: :ok | {:error, term()}
+  def insert_triples(_db_ref, _triples, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Atomically deletes triples from the SPO, POS, and OSP indices.
+
+  All index keys are removed in a single WriteBatch. Deleting a triple that
+  does not exist is a no-op.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `triples` - List of `{subject_id, predicate_id, object_id}` tuples, or a
+    binary of packed 24-byte records (three 64-bit big-endian IDs each)
+  - `opts` - Keyword list of options:
+    - `:target` - `:indices` (default) or `:derived`, as for `insert_triples/3`
+    - Any write option accepted by `put/5`
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, :invalid_triple}` if a triple is not three 64-bit IDs
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+  - `{:error, {:batch_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> {:ok, db} = NIF.open("/tmp/test_db")
+      iex> NIF.insert_triples(db, [{1, 2, 3}])
+      :ok
+      iex> NIF.delete_triples(db, [{1, 2, 3}])
+      :ok
+
+  """
+  @spec delete_triples(db_ref(), triples(), keyword()) :: :ok | {:error, term()}
+  def delete_triples(_db_ref, _triples, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Atomically deletes every triple with the given subject.
+
+  The subject's block of the SPO index is removed with one range tombstone,
+  and the matching POS and OSP keys are deleted in the same WriteBatch.
+  Triples inserted concurrently with the call may survive in some indices.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `subject` - The subject term ID
+  - `opts` - Write options, as for `put/5` (default: `[]`)
+
+  ## Returns
+  - `{:ok, count}` with the number of triples deleted
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, {:iterator_failed, reason}}` on read errors
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+  - `{:error, {:batch_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> {:ok, db} = NIF.open("/tmp/test_db")
+      iex> NIF.insert_triples(db, [{1, 2, 3}, {1, 4, 5}, {6, 2, 3}])
+      :ok
+      iex> NIF.delete_subject(db, 1)
+      {:ok, 2}
+
+  """
+  @spec delete_subject(db_ref(), term_id(), [write_option()]) ::
+          {:ok, non_neg_integer()} | {:error, term()}
+  def delete_subject(_db_ref, _subject, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Atomically deletes every triple with the given predicate.
+
+  The predicate's block of the POS index is removed with one range tombstone,
+  and the matching SPO and OSP keys are deleted in the same WriteBatch.
+  Triples inserted concurrently with the call may survive in some indices.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `predicate` - The predicate term ID
+  - `opts` - Write options, as for `put/5` (default: `[]`)
+
+  ## Returns
+  - `{:ok, count}` with the number of triples deleted
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, {:iterator_failed, reason}}` on read errors
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+  - `{:error, {:batch_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> {:ok, db} = NIF.open("/tmp/test_db")
+      iex> NIF.insert_triples(db, [{1, 2, 3}, {1, 4, 5}, {6, 2, 3}])
+      :ok
+      iex> NIF.delete_predicate(db, 2)
+      {:ok, 2}
+
+  """
+  @spec delete_predicate(db_ref(), term_id(), [write_option()]) ::
+          {:ok, non_neg_integer()} | {:error, term()}
+  def delete_predicate(_db_ref, _predicate, _opts \\ []),
+    do: :erlang.nif_error(:nif_not_loaded)
 
   # ============================================================================
   # Iterator Operations
   # ============================================================================
 
   @type iterator_ref :: reference()
+  @type direction :: :forward | :reverse
 
   @doc """
   Creates a prefix iterator for a column family.
@@ -378,7 +901,7 @@ defmodule TripleStore.Backend.RocksDB.NIF do
 
   ## Arguments
   - `db_ref` - The database reference
-  - `cf` - The column family atom
+  - `cf` - The column family name as an atom or binary
   - `prefix` - The prefix to iterate over (can be empty for full scan)
 
   ## Returns
@@ -400,6 +923,43 @@ defmodule TripleStore.Backend.RocksDB.NIF do
           {:ok, iterator_ref()} | {:error, term()}
   def prefix_iterator(_db_ref, _cf, _prefix), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Creates an iterator over the keys in `[lower, upper)` of a column family.
+
+  The bounds are applied by RocksDB, so entries outside the range are never
+  read. With `:reverse` the iterator starts at the last key below `upper` and
+  walks backwards, which supports descending scans and numeric range filters
+  over inline-encoded IDs. The returned iterator works with `iterator_next/1`,
+  `iterator_collect/1` and `iterator_close/1`.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
+  - `lower` - Inclusive lower bound, or `nil` for unbounded
+  - `upper` - Exclusive upper bound, or `nil` for unbounded
+  - `direction` - `:forward` or `:reverse`
+
+  ## Returns
+  - `{:ok, iterator_ref}` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:invalid_direction, direction}}` if direction is invalid
+
+  ## Examples
+
+      iex> {:ok, iter} = NIF.range_iterator(db, :spo, "s1", "s3", :reverse)
+      iex> {:ok, key, _value} = NIF.iterator_next(iter)
+      iex> key
+      "s2p1o1"
+
+  """
+  @spec range_iterator(db_ref(), column_family(), binary() | nil, binary() | nil, direction()) ::
+          {:ok, iterator_ref()} | {:error, term()}
+  def range_iterator(_db_ref, _cf, _lower, _upper, _direction),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Gets the next key-value pair from the iterator.
 
@@ -426,10 +986,42 @@ defmodule TripleStore.Backend.RocksDB.NIF do
           {:ok, binary(), binary()} | :iterator_end | {:error, term()}
   def iterator_next(_iter_ref), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Gets up to `n` key-value pairs from the iterator in one call.
+
+  Amortizes the NIF call, iterator lock and scheduler switch over a whole
+  chunk instead of paying them per entry as `iterator_next/1` does.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `iter_ref` - The iterator reference
+  - `n` - Maximum number of pairs to return
+
+  ## Returns
+  - `{:ok, pairs, :more}` if `n` pairs were read and more may follow
+  - `{:ok, pairs, :end}` if the iterator is exhausted or prefix no longer matches
+  - `{:error, :iterator_closed}` if iterator was closed
+  - `{:error, {:iterator_failed, reason}}` on error
+
+  ## Examples
+
+      iex> {:ok, iter} = NIF.prefix_iterator(db, :spo, "s1")
+      iex> NIF.iterator_next_n(iter, 2)
+      {:ok, [{"s1p1o1", ""}, {"s1p1o2", ""}], :more}
+      iex> NIF.iterator_next_n(iter, 2)
+      {:ok, [{"s1p2o1", ""}], :end}
+
+  """
+  @spec iterator_next_n(iterator_ref(), non_neg_integer()) ::
+          {:ok, [{binary(), binary()}], :more | :end} | {:error, term()}
+  def iterator_next_n(_iter_ref, _n), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Seeks the iterator to a specific key.
 
   After seeking, the iterator will return keys >= target that match the prefix.
+  The iterator is repositioned in place, keeping its bounds and prefix; a
+  target below the prefix seeks to the first key with the prefix.
   This is essential for Leapfrog Triejoin in Phase 3.
 
   Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
@@ -457,15 +1049,46 @@ defmodule TripleStore.Backend.RocksDB.NIF do
   def iterator_seek(_iter_ref, _target), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  Closes the iterator and releases resources.
+  Seeks the iterator to the last key <= target and reverses its direction.
+
+  Subsequent `iterator_next/1` calls return keys with the prefix in descending
+  order. A target above the prefix seeks to the last key with the prefix.
+  Used for reverse Leapfrog Triejoin steps.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
 
   ## Arguments
   - `iter_ref` - The iterator reference
+  - `target` - The key to seek to
 
   ## Returns
   - `:ok` on success
-  - `{:error, :iterator_closed}` if already closed
-
+  - `{:error, :iterator_closed}` if iterator was closed
+  - `{:error, :already_closed}` if database was closed
+
+  ## Examples
+
+      iex> {:ok, iter} = NIF.prefix_iterator(db, :spo, "s1")
+      iex> NIF.iterator_seek_for_prev(iter, "s1p2")
+      :ok
+      iex> {:ok, key, _value} = NIF.iterator_next(iter)
+      iex> key <= "s1p2"
+      true
+
+  """
+  @spec iterator_seek_for_prev(iterator_ref(), binary()) :: :ok | {:error, term()}
+  def iterator_seek_for_prev(_iter_ref, _target), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Closes the iterator and releases resources.
+
+  ## Arguments
+  - `iter_ref` - The iterator reference
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :iterator_closed}` if already closed
+
   ## Examples
 
       iex> {:ok, iter} = NIF.prefix_iterator(db, :spo, "s1")
@@ -511,16 +1134,20 @@ defmodule TripleStore.Backend.RocksDB.NIF do
   # Stream Wrapper
   # ============================================================================
 
+  # Entries fetched per NIF call when streaming an iterator
+  @stream_chunk_size 1000
+
   @doc """
   Creates an Elixir Stream from a prefix iterator.
 
   This wraps the iterator in a lazy Stream that automatically handles
-  iteration and cleanup. The iterator is closed when the stream is
-  fully consumed or when the stream is garbage collected.
+  iteration and cleanup. Entries are fetched from the NIF in chunks of
+  #{@stream_chunk_size} with `iterator_next_n/2`. The iterator is closed when
+  the stream is fully consumed or when the stream is garbage collected.
 
   ## Arguments
   - `db_ref` - The database reference
-  - `cf` - The column family atom
+  - `cf` - The column family name as an atom or binary
   - `prefix` - The prefix to iterate over (can be empty for full scan)
 
   ## Returns
@@ -541,9 +1168,52 @@ defmodule TripleStore.Backend.RocksDB.NIF do
       {:ok, iter} ->
         stream =
           Stream.resource(
-            fn -> iter end,
+            fn -> {iter, :more} end,
+            &stream_next/1,
+            fn {iter, _status} -> iterator_close(iter) end
+          )
+
+        {:ok, stream}
+
+      error ->
+        error
+    end
+  end
+
+  @doc """
+  Creates an Elixir Stream from a range iterator.
+
+  Like `prefix_stream/3`, but over the keys in `[lower, upper)` in the
+  given direction. See `range_iterator/5`.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
+  - `lower` - Inclusive lower bound, or `nil` for unbounded
+  - `upper` - Exclusive upper bound, or `nil` for unbounded
+  - `direction` - `:forward` or `:reverse` (default: `:forward`)
+
+  ## Returns
+  - `{:ok, Stream.t()}` on success
+  - `{:error, term()}` on failure
+
+  ## Examples
+
+      iex> {:ok, stream} = NIF.range_stream(db, :spo, "s1", "s3", :reverse)
+      iex> Enum.take(stream, 2)
+      [{"s2p1o1", ""}, {"s1p1o2", ""}]
+
+  """
+  @spec range_stream(db_ref(), column_family(), binary() | nil, binary() | nil, direction()) ::
+          {:ok, Enumerable.t()} | {:error, term()}
+  def range_stream(db_ref, cf, lower, upper, direction \\ :forward) do
+    case range_iterator(db_ref, cf, lower, upper, direction) do
+      {:ok, iter} ->
+        stream =
+          Stream.resource(
+            fn -> {iter, :more} end,
             &stream_next/1,
-            fn iter -> iterator_close(iter) end
+            fn {iter, _status} -> iterator_close(iter) end
           )
 
         {:ok, stream}
@@ -553,14 +1223,82 @@ defmodule TripleStore.Backend.RocksDB.NIF do
     end
   end
 
-  defp stream_next(iter) do
-    case iterator_next(iter) do
-      {:ok, key, value} -> {[{key, value}], iter}
-      :iterator_end -> {:halt, iter}
-      {:error, _} -> {:halt, iter}
+  defp stream_next({iter, :end}), do: {:halt, {iter, :end}}
+
+  defp stream_next({iter, :more}) do
+    case iterator_next_n(iter, @stream_chunk_size) do
+      {:ok, pairs, status} -> {pairs, {iter, status}}
+      {:error, _} -> {:halt, {iter, :end}}
     end
   end
 
+  # ============================================================================
+  # Count Operations
+  # ============================================================================
+
+  @doc """
+  Counts the keys with the given prefix in a column family.
+
+  Iterates keys only inside the NIF: values are never read and no binaries
+  are allocated, so this is much cheaper than counting a `prefix_stream/3`.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
+  - `prefix` - The prefix to count (can be empty to count the whole column family)
+
+  ## Returns
+  - `{:ok, count}` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:iterator_failed, reason}}` on read errors
+
+  ## Examples
+
+      iex> NIF.count_prefix(db, :spo, <<1::64-big>>)
+      {:ok, 42}
+
+  """
+  @spec count_prefix(db_ref(), column_family(), binary()) ::
+          {:ok, non_neg_integer()} | {:error, term()}
+  def count_prefix(_db_ref, _cf, _prefix), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Estimates the number of keys in `[lower, upper)` of a column family.
+
+  Based on RocksDB's `rocksdb.estimate-num-keys` property. With a bound, the
+  estimate is scaled by the share of flushed SST entries in the range,
+  interpolated from each file's smallest and largest key. Nothing is read
+  from disk, so the result is instant but approximate. Keys still in the
+  memtable have no known distribution: with no flushed files the estimate
+  for the whole column family is returned.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
+  - `lower` - Inclusive lower bound, or `nil` for unbounded
+  - `upper` - Exclusive upper bound, or `nil` for unbounded
+
+  ## Returns
+  - `{:ok, estimate}` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:property_failed, reason}}` if RocksDB cannot report the estimate
+
+  ## Examples
+
+      iex> NIF.approximate_count(db, :spo, <<1::64-big>>, <<2::64-big>>)
+      {:ok, 40}
+
+  """
+  @spec approximate_count(db_ref(), column_family(), binary() | nil, binary() | nil) ::
+          {:ok, non_neg_integer()} | {:error, term()}
+  def approximate_count(_db_ref, _cf, _lower, _upper), do: :erlang.nif_error(:nif_not_loaded)
+
   # ============================================================================
   # Snapshot Operations
   # ============================================================================
@@ -610,7 +1348,7 @@ defmodule TripleStore.Backend.RocksDB.NIF do
 
   ## Arguments
   - `snapshot_ref` - The snapshot reference
-  - `cf` - The column family atom
+  - `cf` - The column family name as an atom or binary
   - `key` - The key as a binary
 
   ## Returns
@@ -631,6 +1369,34 @@ defmodule TripleStore.Backend.RocksDB.NIF do
           {:ok, binary()} | :not_found | {:error, term()}
   def snapshot_get(_snapshot_ref, _cf, _key), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Gets the values for multiple keys from a column family using a snapshot.
+
+  Batched version of `snapshot_get/3`.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `snapshot_ref` - The snapshot reference
+  - `cf` - The column family name as an atom or binary
+  - `keys` - List of keys as binaries
+
+  ## Returns
+  - `{:ok, results}` with one `{:ok, value}` or `:not_found` per key, in input order
+  - `{:error, :snapshot_released}` if snapshot was released
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:get_failed, reason}}` if any lookup fails
+
+  ## Examples
+
+      iex> {:ok, snap} = NIF.snapshot(db)
+      iex> NIF.snapshot_multi_get(snap, :spo, ["key1", "key2"])
+      {:ok, [{:ok, "value1"}, :not_found]}
+
+  """
+  @spec snapshot_multi_get(snapshot_ref(), column_family(), [binary()]) ::
+          {:ok, [{:ok, binary()} | :not_found]} | {:error, term()}
+  def snapshot_multi_get(_snapshot_ref, _cf, _keys), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Creates a prefix iterator over a snapshot.
 
@@ -641,7 +1407,7 @@ defmodule TripleStore.Backend.RocksDB.NIF do
 
   ## Arguments
   - `snapshot_ref` - The snapshot reference
-  - `cf` - The column family atom
+  - `cf` - The column family name as an atom or binary
   - `prefix` - The prefix to iterate over (can be empty for full scan)
 
   ## Returns
@@ -661,6 +1427,44 @@ defmodule TripleStore.Backend.RocksDB.NIF do
   def snapshot_prefix_iterator(_snapshot_ref, _cf, _prefix),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Creates an iterator over the keys in `[lower, upper)` of a snapshot.
+
+  Snapshot counterpart of `range_iterator/5`. The returned iterator works
+  with the `snapshot_iterator_*` functions.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `snapshot_ref` - The snapshot reference
+  - `cf` - The column family name as an atom or binary
+  - `lower` - Inclusive lower bound, or `nil` for unbounded
+  - `upper` - Exclusive upper bound, or `nil` for unbounded
+  - `direction` - `:forward` or `:reverse`
+
+  ## Returns
+  - `{:ok, iterator_ref}` on success
+  - `{:error, :snapshot_released}` if snapshot was released
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:invalid_direction, direction}}` if direction is invalid
+
+  ## Examples
+
+      iex> {:ok, snap} = NIF.snapshot(db)
+      iex> {:ok, iter} = NIF.snapshot_range_iterator(snap, :osp, nil, "s2", :forward)
+      iex> {:ok, key, value} = NIF.snapshot_iterator_next(iter)
+
+  """
+  @spec snapshot_range_iterator(
+          snapshot_ref(),
+          column_family(),
+          binary() | nil,
+          binary() | nil,
+          direction()
+        ) :: {:ok, snapshot_iterator_ref()} | {:error, term()}
+  def snapshot_range_iterator(_snapshot_ref, _cf, _lower, _upper, _direction),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Gets the next key-value pair from a snapshot iterator.
 
@@ -680,6 +1484,66 @@ defmodule TripleStore.Backend.RocksDB.NIF do
           {:ok, binary(), binary()} | :iterator_end | {:error, term()}
   def snapshot_iterator_next(_iter_ref), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Gets up to `n` key-value pairs from a snapshot iterator in one call.
+
+  Snapshot counterpart of `iterator_next_n/2`.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `iter_ref` - The snapshot iterator reference
+  - `n` - Maximum number of pairs to return
+
+  ## Returns
+  - `{:ok, pairs, :more}` if `n` pairs were read and more may follow
+  - `{:ok, pairs, :end}` if the iterator is exhausted or prefix no longer matches
+  - `{:error, :iterator_closed}` if iterator was closed
+  - `{:error, {:iterator_failed, reason}}` on error
+
+  """
+  @spec snapshot_iterator_next_n(snapshot_iterator_ref(), non_neg_integer()) ::
+          {:ok, [{binary(), binary()}], :more | :end} | {:error, term()}
+  def snapshot_iterator_next_n(_iter_ref, _n), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Seeks a snapshot iterator to a specific key.
+
+  Snapshot counterpart of `iterator_seek/2`. The iterator keeps reading from
+  the snapshot after the seek.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `iter_ref` - The snapshot iterator reference
+  - `target` - The key to seek to
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :iterator_closed}` if iterator was closed
+
+  """
+  @spec snapshot_iterator_seek(snapshot_iterator_ref(), binary()) :: :ok | {:error, term()}
+  def snapshot_iterator_seek(_iter_ref, _target), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Seeks a snapshot iterator to the last key <= target and reverses its direction.
+
+  Snapshot counterpart of `iterator_seek_for_prev/2`.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `iter_ref` - The snapshot iterator reference
+  - `target` - The key to seek to
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :iterator_closed}` if iterator was closed
+
+  """
+  @spec snapshot_iterator_seek_for_prev(snapshot_iterator_ref(), binary()) ::
+          :ok | {:error, term()}
+  def snapshot_iterator_seek_for_prev(_iter_ref, _target),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Closes a snapshot iterator and releases resources.
 
@@ -740,11 +1604,12 @@ defmodule TripleStore.Backend.RocksDB.NIF do
   Creates an Elixir Stream from a snapshot prefix iterator.
 
   This wraps the snapshot iterator in a lazy Stream that automatically handles
-  iteration and cleanup. The iterator is closed when the stream is fully consumed.
+  iteration and cleanup. Entries are fetched in chunks with
+  `snapshot_iterator_next_n/2`. The iterator is closed when the stream is fully consumed.
 
   ## Arguments
   - `snapshot_ref` - The snapshot reference
-  - `cf` - The column family atom
+  - `cf` - The column family name as an atom or binary
   - `prefix` - The prefix to iterate over (can be empty for full scan)
 
   ## Returns
@@ -766,9 +1631,9 @@ defmodule TripleStore.Backend.RocksDB.NIF do
       {:ok, iter} ->
         stream =
           Stream.resource(
-            fn -> iter end,
+            fn -> {iter, :more} end,
             &snapshot_stream_next/1,
-            fn iter -> snapshot_iterator_close(iter) end
+            fn {iter, _status} -> snapshot_iterator_close(iter) end
           )
 
         {:ok, stream}
@@ -778,11 +1643,1614 @@ defmodule TripleStore.Backend.RocksDB.NIF do
     end
   end
 
-  defp snapshot_stream_next(iter) do
-    case snapshot_iterator_next(iter) do
-      {:ok, key, value} -> {[{key, value}], iter}
-      :iterator_end -> {:halt, iter}
-      {:error, _} -> {:halt, iter}
+  defp snapshot_stream_next({iter, :end}), do: {:halt, {iter, :end}}
+
+  defp snapshot_stream_next({iter, :more}) do
+    case snapshot_iterator_next_n(iter, @stream_chunk_size) do
+      {:ok, pairs, status} -> {pairs, {iter, status}}
+      {:error, _} -> {:halt, {iter, :end}}
     end
   end
+
+  # ============================================================================
+  # Transactions
+  # ============================================================================
+
+  @type txn_ref :: reference()
+
+  @doc """
+  Begins an optimistic transaction.
+
+  Writes made through the transaction are buffered and only become visible to
+  other readers once `txn_commit/1` succeeds. Conflicts are not detected while
+  the transaction runs; the commit fails with `{:error, :conflict}` if another
+  writer changed a key the transaction wrote or read with `for_update: true`
+  after the transaction began.
+
+  The database must have been opened with `transactional: true`. The write
+  options apply to the commit, which writes all of the transaction's changes
+  as one batch.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `opts` - Write options, as for `put/5` (default: `[]`)
+
+  ## Returns
+  - `{:ok, txn_ref}` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :not_transactional}` if the database was not opened as transactional
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+
+  ## Examples
+
+      iex> {:ok, db} = NIF.open("/tmp/test_db", transactional: true)
+      iex> {:ok, txn} = NIF.transaction_begin(db)
+      iex> NIF.txn_put(txn, :id2str, "key1", "value1")
+      :ok
+      iex> NIF.txn_commit(txn)
+      :ok
+
+  """
+  @spec transaction_begin(db_ref(), [write_option()]) :: {:ok, txn_ref()} | {:error, term()}
+  def transaction_begin(_db_ref, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Gets a value from a column family within a transaction.
+
+  Reads see the transaction's own uncommitted writes. With `for_update: true`
+  the key is also tracked for conflict detection, so the commit fails if
+  another writer changes it first. Use this for read-modify-write cycles.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `txn_ref` - The transaction reference
+  - `cf` - The column family name as an atom or binary
+  - `key` - The key as a binary
+  - `opts` - Options (default: `[]`)
+    - `:for_update` - Track the key for conflict detection (default: `false`)
+
+  ## Returns
+  - `{:ok, value}` if found
+  - `:not_found` if key doesn't exist
+  - `{:error, :transaction_closed}` if the transaction has ended
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:invalid_option, key}}` for an unknown or malformed option
+  - `{:error, {:get_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> {:ok, txn} = NIF.transaction_begin(db)
+      iex> NIF.txn_get(txn, :str2id, "counter", for_update: true)
+      {:ok, <<41::64>>}
+
+  """
+  @spec txn_get(txn_ref(), column_family(), binary(), keyword()) ::
+          {:ok, binary()} | :not_found | {:error, term()}
+  def txn_get(_txn_ref, _cf, _key, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Puts a key-value pair into a column family within a transaction.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `txn_ref` - The transaction reference
+  - `cf` - The column family name as an atom or binary
+  - `key` - The key as a binary
+  - `value` - The value as a binary
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :transaction_closed}` if the transaction has ended
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:put_failed, reason}}` on other errors
+
+  """
+  @spec txn_put(txn_ref(), column_family(), binary(), binary()) :: :ok | {:error, term()}
+  def txn_put(_txn_ref, _cf, _key, _value), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Deletes a key from a column family within a transaction.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `txn_ref` - The transaction reference
+  - `cf` - The column family name as an atom or binary
+  - `key` - The key as a binary
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :transaction_closed}` if the transaction has ended
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:delete_failed, reason}}` on other errors
+
+  """
+  @spec txn_delete(txn_ref(), column_family(), binary()) :: :ok | {:error, term()}
+  def txn_delete(_txn_ref, _cf, _key), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates a prefix iterator that reads through a transaction.
+
+  The iterator sees committed data merged with the transaction's own
+  uncommitted writes. It is used with the regular `iterator_*` functions and
+  is closed automatically when the transaction commits or rolls back.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `txn_ref` - The transaction reference
+  - `cf` - The column family name as an atom or binary
+  - `prefix` - The prefix to iterate over
+
+  ## Returns
+  - `{:ok, iterator_ref}` on success
+  - `{:error, :transaction_closed}` if the transaction has ended
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+
+  ## Examples
+
+      iex> {:ok, txn} = NIF.transaction_begin(db)
+      iex> NIF.txn_put(txn, :spo, "s1p2", "")
+      iex> {:ok, iter} = NIF.txn_prefix_iterator(txn, :spo, "s1")
+      iex> NIF.iterator_collect(iter)
+      {:ok, [{"s1p1", ""}, {"s1p2", ""}]}
+
+  """
+  @spec txn_prefix_iterator(txn_ref(), column_family(), binary()) ::
+          {:ok, iterator_ref()} | {:error, term()}
+  def txn_prefix_iterator(_txn_ref, _cf, _prefix), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Commits a transaction.
+
+  The transaction ends whether or not the commit succeeds. On
+  `{:error, :conflict}` none of its writes were applied; begin a new
+  transaction and retry.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `txn_ref` - The transaction reference
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :conflict}` if another writer changed a key the transaction depends on
+  - `{:error, :transaction_closed}` if the transaction has already ended
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :write_stall}` if begun with `no_slowdown: true` and the commit would stall
+  - `{:error, {:commit_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> {:ok, txn} = NIF.transaction_begin(db)
+      iex> {:ok, _} = NIF.txn_get(txn, :str2id, "counter", for_update: true)
+      iex> NIF.put(db, :str2id, "counter", <<42::64>>)  # Concurrent writer
+      iex> NIF.txn_put(txn, :str2id, "counter", <<43::64>>)
+      iex> NIF.txn_commit(txn)
+      {:error, :conflict}
+
+  """
+  @spec txn_commit(txn_ref()) :: :ok | {:error, term()}
+  def txn_commit(_txn_ref), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Rolls back a transaction, discarding all of its writes.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `txn_ref` - The transaction reference
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :transaction_closed}` if the transaction has already ended
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:rollback_failed, reason}}` on other errors
+
+  """
+  @spec txn_rollback(txn_ref()) :: :ok | {:error, term()}
+  def txn_rollback(_txn_ref), do: :erlang.nif_error(:nif_not_loaded)
+
+  # ============================================================================
+  # Leapfrog Triejoin
+  # ============================================================================
+
+  @type join_ref :: reference()
+  @type pattern_element :: {:bound, non_neg_integer()} | {:var, term()}
+  @type pattern_spec :: {pattern_element(), pattern_element(), pattern_element()}
+
+  @doc """
+  Starts a Leapfrog Triejoin over the `:spo`, `:pos` and `:osp` indices.
+
+  Opens one RocksDB iterator per pattern and intersects them one variable at
+  a time, following `variable_order`, entirely inside the NIF. Only matching
+  binding rows cross into the BEAM, read in chunks with `leapfrog_join_next/2`.
+
+  A pattern is read directly from the index whose key order puts its bound
+  terms first and its variables in `variable_order`. When no index has that
+  order (or a variable repeats within a pattern), the pattern's matches are
+  read from the index with its bound terms as prefix and sorted in memory.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `source` - A database or snapshot reference
+  - `patterns` - List of `{s, p, o}` pattern specs whose elements are
+    `{:bound, term_id}` or `{:var, name}`
+  - `variable_order` - List of variable names; each must appear in a pattern
+
+  ## Returns
+  - `{:ok, join_ref}` on success
+  - `{:error, {:invalid_pattern, pattern}}` if a pattern is malformed or uses
+    a variable missing from `variable_order`
+  - `{:error, {:invalid_variable, name}}` if a variable is repeated in
+    `variable_order` or used by no pattern
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :snapshot_released}` if snapshot was released
+  - `{:error, {:iterator_failed, reason}}` on read errors
+
+  ## Examples
+
+      iex> patterns = [
+      ...>   {{:var, :x}, {:bound, knows}, {:var, :y}},
+      ...>   {{:var, :y}, {:bound, name}, {:var, :n}}
+      ...> ]
+      iex> {:ok, join} = NIF.leapfrog_join(db, patterns, [:x, :y, :n])
+      iex> NIF.leapfrog_join_next(join, 100)
+      {:ok, [[1, 2, 10], [1, 3, 11]], :end}
+
+  """
+  @spec leapfrog_join(db_ref() | snapshot_ref(), [pattern_spec()], [term()]) ::
+          {:ok, join_ref()} | {:error, term()}
+  def leapfrog_join(_source, _patterns, _variable_order),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Gets up to `n` binding rows from a leapfrog join.
+
+  Each row is a list of term IDs, one per variable in the join's
+  `variable_order`. Rows are produced in ascending order.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `join_ref` - The join reference
+  - `n` - Maximum number of rows to return
+
+  ## Returns
+  - `{:ok, rows, :more}` if `n` rows were produced and more may follow
+  - `{:ok, rows, :end}` if the join is exhausted
+  - `{:error, :iterator_closed}` if the join was closed
+  - `{:error, {:iterator_failed, reason}}` on read errors
+
+  """
+  @spec leapfrog_join_next(join_ref(), non_neg_integer()) ::
+          {:ok, [[non_neg_integer()]], :more | :end} | {:error, term()}
+  def leapfrog_join_next(_join_ref, _n), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Closes a leapfrog join and releases its iterators.
+
+  ## Arguments
+  - `join_ref` - The join reference
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :iterator_closed}` if already closed
+
+  """
+  @spec leapfrog_join_close(join_ref()) :: :ok | {:error, term()}
+  def leapfrog_join_close(_join_ref), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates an Elixir Stream of binding rows from a leapfrog join.
+
+  Rows are fetched from the NIF in chunks of #{@stream_chunk_size} and the
+  join is closed when the stream is fully consumed or halted.
+
+  ## Arguments
+  - `source` - A database or snapshot reference
+  - `patterns` - List of `{s, p, o}` pattern specs
+  - `variable_order` - List of variable names
+
+  ## Returns
+  - `{:ok, Stream.t()}` on success
+  - `{:error, term()}` on failure
+
+  ## Examples
+
+      iex> {:ok, stream} = NIF.leapfrog_stream(db, patterns, [:x, :y])
+      iex> Enum.take(stream, 2)
+      [[1, 2], [1, 3]]
+
+  """
+  @spec leapfrog_stream(db_ref() | snapshot_ref(), [pattern_spec()], [term()]) ::
+          {:ok, Enumerable.t()} | {:error, term()}
+  def leapfrog_stream(source, patterns, variable_order) do
+    case leapfrog_join(source, patterns, variable_order) do
+      {:ok, join} ->
+        stream =
+          Stream.resource(
+            fn -> {join, :more} end,
+            &leapfrog_stream_next/1,
+            fn {join, _status} -> leapfrog_join_close(join) end
+          )
+
+        {:ok, stream}
+
+      error ->
+        error
+    end
+  end
+
+  defp leapfrog_stream_next({join, :end}), do: {:halt, {join, :end}}
+
+  defp leapfrog_stream_next({join, :more}) do
+    case leapfrog_join_next(join, @stream_chunk_size) do
+      {:ok, rows, status} -> {rows, {join, status}}
+      {:error, _} -> {:halt, {join, :end}}
+    end
+  end
+
+  # ============================================================================
+  # Pattern Lookup
+  # ============================================================================
+
+  @type lookup_ref :: reference()
+  @type lookup_pattern :: {term_id() | nil, term_id() | nil, term_id() | nil}
+
+  @doc """
+  Starts a lookup of the triples matching a pattern.
+
+  Selects the index whose key order puts the bound terms of the pattern
+  first (SPO, POS, or OSP), scans that key prefix, and decodes each key back
+  to `{s, p, o}` inside the NIF. Bound terms not covered by the prefix, such
+  as the predicate of `{s, nil, o}`, are filtered natively. Matching triples
+  are read in chunks with `lookup_pattern_next/2`.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `pattern` - `{s, p, o}` tuple of term IDs, with `nil` for unbound positions
+  - `opts` - Keyword list of options:
+    - `:target` - `:indices` (default) or `:derived` to look up the
+      `:derived` column family
+
+  ## Returns
+  - `{:ok, lookup_ref}` on success
+  - `{:error, {:invalid_pattern, pattern}}` if the pattern is malformed
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :already_closed}` if database is closed
+
+  ## Examples
+
+      iex> NIF.insert_triples(db, [{1, 2, 3}, {1, 5, 3}, {1, 2, 4}])
+      iex> {:ok, lookup} = NIF.lookup_pattern(db, {1, nil, 3})
+      iex> NIF.lookup_pattern_next(lookup, 100)
+      {:ok, [{1, 2, 3}, {1, 5, 3}], :end}
+
+  """
+  @spec lookup_pattern(db_ref(), lookup_pattern(), keyword()) ::
+          {:ok, lookup_ref()} | {:error, term()}
+  def lookup_pattern(_db_ref, _pattern, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Starts a lookup of the triples matching a pattern using a snapshot.
+
+  Snapshot counterpart of `lookup_pattern/3`.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `snapshot_ref` - The snapshot reference
+  - `pattern` - `{s, p, o}` tuple of term IDs, with `nil` for unbound positions
+  - `opts` - Keyword list of options, as for `lookup_pattern/3`
+
+  ## Returns
+  - `{:ok, lookup_ref}` on success
+  - `{:error, {:invalid_pattern, pattern}}` if the pattern is malformed
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :snapshot_released}` if snapshot was released
+
+  """
+  @spec snapshot_lookup_pattern(snapshot_ref(), lookup_pattern(), keyword()) ::
+          {:ok, lookup_ref()} | {:error, term()}
+  def snapshot_lookup_pattern(_snapshot_ref, _pattern, _opts \\ []),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Gets up to `n` matching triples from a pattern lookup.
+
+  Triples are `{s, p, o}` tuples of term IDs, in the key order of the
+  selected index. Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `lookup_ref` - The lookup reference
+  - `n` - Maximum number of triples to return
+
+  ## Returns
+  - `{:ok, triples, :more}` if `n` triples were read and more may follow
+  - `{:ok, triples, :end}` if the lookup is exhausted
+  - `{:error, :iterator_closed}` if the lookup was closed
+  - `{:error, {:iterator_failed, reason}}` on read errors
+
+  """
+  @spec lookup_pattern_next(lookup_ref(), non_neg_integer()) ::
+          {:ok, [triple()], :more | :end} | {:error, term()}
+  def lookup_pattern_next(_lookup_ref, _n), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Closes a pattern lookup and releases its iterator.
+
+  ## Arguments
+  - `lookup_ref` - The lookup reference
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :iterator_closed}` if already closed
+
+  """
+  @spec lookup_pattern_close(lookup_ref()) :: :ok | {:error, term()}
+  def lookup_pattern_close(_lookup_ref), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates an Elixir Stream of the triples matching a pattern.
+
+  Triples are fetched from the NIF in chunks of #{@stream_chunk_size} and the
+  lookup is closed when the stream is fully consumed or halted.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `pattern` - `{s, p, o}` tuple of term IDs, with `nil` for unbound positions
+  - `opts` - Keyword list of options, as for `lookup_pattern/3`
+
+  ## Returns
+  - `{:ok, Stream.t()}` on success
+  - `{:error, term()}` on failure
+
+  ## Examples
+
+      iex> {:ok, stream} = NIF.lookup_pattern_stream(db, {nil, 2, nil})
+      iex> Enum.take(stream, 2)
+      [{1, 2, 3}, {1, 2, 4}]
+
+  """
+  @spec lookup_pattern_stream(db_ref(), lookup_pattern(), keyword()) ::
+          {:ok, Enumerable.t()} | {:error, term()}
+  def lookup_pattern_stream(db_ref, pattern, opts \\ []) do
+    db_ref |> lookup_pattern(pattern, opts) |> lookup_stream()
+  end
+
+  @doc """
+  Creates an Elixir Stream of the triples matching a pattern in a snapshot.
+
+  Snapshot counterpart of `lookup_pattern_stream/3`.
+
+  ## Arguments
+  - `snapshot_ref` - The snapshot reference
+  - `pattern` - `{s, p, o}` tuple of term IDs, with `nil` for unbound positions
+  - `opts` - Keyword list of options, as for `lookup_pattern/3`
+
+  ## Returns
+  - `{:ok, Stream.t()}` on success
+  - `{:error, term()}` on failure
+
+  """
+  @spec snapshot_lookup_pattern_stream(snapshot_ref(), lookup_pattern(), keyword()) ::
+          {:ok, Enumerable.t()} | {:error, term()}
+  def snapshot_lookup_pattern_stream(snapshot_ref, pattern, opts \\ []) do
+    snapshot_ref |> snapshot_lookup_pattern(pattern, opts) |> lookup_stream()
+  end
+
+  defp lookup_stream({:ok, lookup}) do
+    stream =
+      Stream.resource(
+        fn -> {lookup, :more} end,
+        &lookup_stream_next/1,
+        fn {lookup, _status} -> lookup_pattern_close(lookup) end
+      )
+
+    {:ok, stream}
+  end
+
+  defp lookup_stream(error), do: error
+
+  defp lookup_stream_next({lookup, :end}), do: {:halt, {lookup, :end}}
+
+  defp lookup_stream_next({lookup, :more}) do
+    case lookup_pattern_next(lookup, @stream_chunk_size) do
+      {:ok, triples, status} -> {triples, {lookup, status}}
+      {:error, _} -> {:halt, {lookup, :end}}
+    end
+  end
+
+  # ============================================================================
+  # Backup Operations
+  # ============================================================================
+
+  @type backup_id :: non_neg_integer()
+  @type backup_info :: %{
+          id: backup_id(),
+          timestamp: integer(),
+          size: non_neg_integer(),
+          num_files: non_neg_integer()
+        }
+
+  @doc """
+  Creates a checkpoint: an openable, consistent copy of the database.
+
+  SST files are hard-linked when `dir` is on the same filesystem as the
+  database, so checkpoints are cheap to take while the store is in use. The
+  checkpoint can be opened directly with `open/2`.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `dir` - Directory to create; it must not already exist
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:checkpoint_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> NIF.create_checkpoint(db, "/tmp/test_db_checkpoint")
+      :ok
+      iex> {:ok, copy} = NIF.open("/tmp/test_db_checkpoint")
+
+  """
+  @spec create_checkpoint(db_ref(), String.t()) :: :ok | {:error, term()}
+  def create_checkpoint(_db_ref, _dir), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates a new backup of the database with RocksDB's BackupEngine.
+
+  Backups are incremental: SST files already in `backup_dir` are shared by
+  later backups instead of being copied again.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `backup_dir` - The backup directory, created if it does not exist
+  - `opts` - Options (default: `[]`):
+    - `:flush` - Flush the memtables before the backup instead of copying the
+      WAL (default: `false`)
+    - `:max_background_operations` - Number of files copied in parallel
+
+  ## Returns
+  - `{:ok, backup_id}` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, {:backup_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> NIF.backup_create(db, "/tmp/test_db_backups")
+      {:ok, 1}
+      iex> NIF.backup_create(db, "/tmp/test_db_backups", flush: true)
+      {:ok, 2}
+
+  """
+  @spec backup_create(db_ref(), String.t(), keyword()) :: {:ok, backup_id()} | {:error, term()}
+  def backup_create(_db_ref, _backup_dir, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Lists the backups in a backup directory, oldest first.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `backup_dir` - The backup directory
+
+  ## Returns
+  - `{:ok, [backup_info]}` with `:id`, `:timestamp` (Unix seconds), `:size`
+    (bytes) and `:num_files` for each backup
+  - `{:error, {:backup_failed, reason}}` on error
+
+  ## Examples
+
+      iex> NIF.backup_list("/tmp/test_db_backups")
+      {:ok, [%{id: 1, timestamp: 1_700_000_000, size: 4096, num_files: 5}]}
+
+  """
+  @spec backup_list(String.t()) :: {:ok, [backup_info()]} | {:error, term()}
+  def backup_list(_backup_dir), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Deletes all but the newest `keep` backups in a backup directory.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `backup_dir` - The backup directory
+  - `keep` - Number of most recent backups to keep
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, {:backup_failed, reason}}` on error
+
+  """
+  @spec backup_purge(String.t(), non_neg_integer()) :: :ok | {:error, term()}
+  def backup_purge(_backup_dir, _keep), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Verifies that every file of a backup exists with its recorded size.
+
+  File checksums are not checked.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `backup_dir` - The backup directory
+  - `backup_id` - The backup to verify
+
+  ## Returns
+  - `:ok` if the backup is intact
+  - `{:error, {:backup_failed, reason}}` if it is missing or damaged
+
+  """
+  @spec backup_verify(String.t(), backup_id()) :: :ok | {:error, term()}
+  def backup_verify(_backup_dir, _backup_id), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Restores a backup into a database directory.
+
+  The target database must not be open. Its existing files are replaced.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `backup_dir` - The backup directory
+  - `target_path` - Database directory to restore into
+  - `backup_id` - The backup to restore
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, {:restore_failed, reason}}` on error
+
+  ## Examples
+
+      iex> NIF.backup_restore("/tmp/test_db_backups", "/tmp/test_db_restored", 1)
+      :ok
+      iex> {:ok, db} = NIF.open("/tmp/test_db_restored")
+
+  """
+  @spec backup_restore(String.t(), String.t(), backup_id()) :: :ok | {:error, term()}
+  def backup_restore(_backup_dir, _target_path, _backup_id),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  # ============================================================================
+  # SST Bulk Loading
+  # ============================================================================
+
+  @type sst_writer_ref :: reference()
+  @type sst_file_info :: %{
+          path: String.t(),
+          entries: non_neg_integer(),
+          file_size: non_neg_integer()
+        }
+
+  @doc """
+  Opens an SST file writer for a bulk load.
+
+  Keys written to the file bypass the memtable and WAL entirely; the finished
+  file is handed to `ingest_external_files/4`. Create the writer with the
+  options of the column family the file will be ingested into, so that its
+  bloom filter and prefix extractor match.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `path` - Path of the SST file to create
+  - `cf_options` - Column family options (default: `[]`):
+    - `:cf` - Start from the defaults of this column family, e.g. `:spo`.
+      Column families without built-in defaults, such as those created with
+      `create_column_family/3`, start from the top-level defaults
+    - Any column family option accepted by `open/2`
+
+  ## Returns
+  - `{:ok, writer_ref}` on success
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, {:sst_write_failed, reason}}` if the file cannot be created
+
+  ## Examples
+
+      iex> {:ok, writer} = NIF.sst_writer_open("/tmp/spo_0001.sst", cf: :spo)
+      iex> NIF.sst_writer_put_sorted(writer, [{<<1::64, 2::64, 3::64>>, ""}])
+      :ok
+      iex> {:ok, %{path: path}} = NIF.sst_writer_finish(writer)
+      iex> NIF.ingest_external_files(db, :spo, [path])
+      :ok
+
+  """
+  @spec sst_writer_open(String.t(), keyword()) :: {:ok, sst_writer_ref()} | {:error, term()}
+  def sst_writer_open(_path, _cf_options \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Appends key-value pairs to an SST file.
+
+  Keys must be in strictly ascending byte order, both within a call and across
+  calls. A rejected call leaves the entries before the offending key written.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `writer_ref` - The writer reference
+  - `entries` - List of `{key, value}` binaries in ascending key order
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :writer_closed}` if the writer was finished
+  - `{:error, {:unsorted_key, key}}` if a key is not greater than the previous one
+  - `{:error, {:sst_write_failed, reason}}` on other errors
+
+  """
+  @spec sst_writer_put_sorted(sst_writer_ref(), [{binary(), binary()}]) ::
+          :ok | {:error, term()}
+  def sst_writer_put_sorted(_writer_ref, _entries), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Finishes an SST file so it can be ingested, and closes the writer.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `writer_ref` - The writer reference
+
+  ## Returns
+  - `{:ok, %{path: path, entries: count, file_size: bytes}}` on success
+  - `{:error, :writer_closed}` if already finished
+  - `{:error, {:sst_write_failed, reason}}` on error, e.g. when no entries were written
+
+  """
+  @spec sst_writer_finish(sst_writer_ref()) :: {:ok, sst_file_info()} | {:error, term()}
+  def sst_writer_finish(_writer_ref), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Ingests finished SST files into a column family.
+
+  Files whose key range does not overlap existing data are placed directly in
+  the bottom level, skipping the memtable, WAL and compaction. Files being
+  ingested together must not overlap each other.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
+  - `paths` - Paths of files written with `sst_writer_finish/1`
+  - `opts` - Boolean options (default: `[]`):
+    - `:move_files` - Move (hard-link) the files instead of copying them
+    - `:snapshot_consistency` - Keep existing snapshots from seeing the
+      ingested keys (default: `true`)
+    - `:allow_global_seqno` - Allow ingesting files that overlap existing
+      data (default: `true`)
+    - `:allow_blocking_flush` - Allow a memtable flush when the files overlap
+      it (default: `true`)
+    - `:ingest_behind` - Ingest below all existing data; requires the
+      database to reserve the bottom level
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, {:ingest_failed, reason}}` on other errors
+
+  """
+  @spec ingest_external_files(db_ref(), column_family(), [String.t()], keyword()) ::
+          :ok | {:error, term()}
+  def ingest_external_files(_db_ref, _cf, _paths, _opts \\ []),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  # ============================================================================
+  # N-Triples Bulk Load
+  # ============================================================================
+
+  @type bulk_load_stats :: %{
+          triples: non_neg_integer(),
+          unique_triples: non_neg_integer(),
+          terms_created: non_neg_integer()
+        }
+
+  @doc """
+  Loads an N-Triples file directly into the dictionary and triple indices.
+
+  The file is parsed natively and every term gets an ID with the same layout
+  as `TripleStore.Dictionary.encode_id/2`: `xsd:integer`, `xsd:decimal` and
+  timezoned `xsd:dateTime` literals in the inline range are inlined, and all
+  other terms are looked up in `str2id` or allocated from the sequence
+  counters shared with `dictionary_get_or_create/4`. New dictionary entries
+  are written together with the advanced counters in one batch per sorted run.
+  The counters are locked only while a run's new terms are written, so
+  `dictionary_get_or_create/4` calls made during a load wait for at most one
+  run rather than the whole file.
+
+  Triples are sorted on disk in runs of `:chunk_size`, merged into one SST
+  file per index and ingested with `ingest_external_files/4`. Triples that
+  already exist are left as they are.
+
+  The file is read twice: a first pass validates every statement before
+  anything is written, so `{:error, {reason, line}}` leaves the database
+  unchanged. The load is not atomic beyond that. A later failure, such as a
+  full disk, can leave the dictionary entries of the terms seen so far
+  without their triples; those entries are valid and reused by the next
+  load. The `:spo`, `:pos` and `:osp` ingests are separate steps, so a
+  failure between them leaves the indices out of sync until the file is
+  loaded again.
+
+  IRIs and literal values are NFC-normalized as in `StringToId.encode_term/1`,
+  so a term gets the same ID whether it is loaded here or added through
+  `Dictionary.Manager`. Do not run it while a `Dictionary.Manager` is allocating IDs for the
+  same database; a manager started afterwards resumes after the loader's
+  counters.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `file_path` - Path of the N-Triples file
+  - `opts` - Options (default: `[]`):
+    - `:progress` - Pid sent `{:bulk_load_progress, :parsing, triples}` after
+      each sorted run and `{:bulk_load_progress, index, keys}` after each of
+      `:spo`, `:pos` and `:osp` is ingested
+    - `:tmp_dir` - Directory for the sorted runs (default: next to the
+      database directory)
+    - `:chunk_size` - Triples per sorted run (default: `1_000_000`)
+    - `:cache_size` - Dictionary entries cached in memory (default: `1_000_000`)
+
+  ## Returns
+  - `{:ok, %{triples: n, unique_triples: n, terms_created: n}}` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, {reason, line}}` for a statement that cannot be loaded, where
+    `reason` is `:parse_error`, `:term_too_large`, `:null_byte_in_uri` or
+    `:invalid_utf8`
+  - `{:error, :sequence_overflow}` if a sequence counter is exhausted
+  - `{:error, {:bulk_load_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> NIF.bulk_load_ntriples(db, "dump.nt", progress: self())
+      {:ok, %{triples: 1_000_000, unique_triples: 999_812, terms_created: 240_117}}
+
+  """
+  @spec bulk_load_ntriples(db_ref(), String.t(), keyword()) ::
+          {:ok, bulk_load_stats()} | {:error, term()}
+  def bulk_load_ntriples(_db_ref, _file_path, _opts \\ []),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  # ============================================================================
+  # Counter Operations
+  # ============================================================================
+
+  @doc """
+  Adds a signed delta to a counter without reading it.
+
+  Counters live in the `:counters` column family as 8-byte big-endian
+  unsigned values and start at 0. RocksDB combines the deltas with a merge
+  operator, so concurrent updates are never lost. To update a counter
+  atomically with the data it describes, put `{:merge, :counters, key, delta}`
+  in the same `mixed_batch/3`.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family; must be `:counters`
+  - `key` - The counter key
+  - `delta` - Signed 64-bit amount to add; results wrap around at 2^64
+  - `opts` - Write options, as for `put/5` (default: `[]`)
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_cf, cf}}` if column family is not `:counters`
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+  - `{:error, {:merge_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> NIF.merge(db, :counters, "triples", 10)
+      :ok
+      iex> NIF.merge(db, :counters, "triples", -3)
+      :ok
+      iex> NIF.get(db, :counters, "triples")
+      {:ok, <<7::64-big>>}
+
+  """
+  @spec merge(db_ref(), :counters, binary(), integer(), [write_option()]) ::
+          :ok | {:error, term()}
+  def merge(_db_ref, _cf, _key, _delta, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Adds a signed delta to a counter and returns the new value.
+
+  Calls on the same database are serialized, so concurrent callers each see
+  their own increment and never receive the same value. Plain `merge/5` calls
+  made at the same time may be included in the returned value.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family; must be `:counters`
+  - `key` - The counter key
+  - `delta` - Signed 64-bit amount to add; results wrap around at 2^64
+  - `opts` - Write options, as for `put/5` (default: `[]`)
+
+  ## Returns
+  - `{:ok, value}` with the counter value after the increment
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_cf, cf}}` if column family is not `:counters`
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+  - `{:error, {:merge_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> NIF.increment_and_get(db, :counters, "seq:uri", 1)
+      {:ok, 1}
+      iex> NIF.increment_and_get(db, :counters, "seq:uri", 100)
+      {:ok, 101}
+
+  """
+  @spec increment_and_get(db_ref(), :counters, binary(), integer(), [write_option()]) ::
+          {:ok, non_neg_integer()} | {:error, term()}
+  def increment_and_get(_db_ref, _cf, _key, _delta, _opts \\ []),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  # ============================================================================
+  # Dictionary Operations
+  # ============================================================================
+
+  @doc """
+  Looks up or creates the IDs of encoded dictionary terms.
+
+  Existing terms are read from `str2id`. Missing terms get the next ID of
+  their type from sequence counters held natively by the database handle,
+  loaded on first use from the persisted `__seq_counter__*` keys plus the
+  recovery safety margin. Both mapping directions and the advanced counters
+  are written in one WriteBatch, and calls are serialized, so concurrent
+  callers never assign a term two IDs. A term repeated in the input gets the
+  same ID each time.
+
+  The native counters replace `Dictionary.SequenceCounter`; do not use both to
+  allocate IDs for the same database.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `terms` - List of `str2id` keys as built by `StringToId.encode_term/1`
+  - `types` - List of `:uri`, `:bnode` or `:literal`, one per term
+  - `opts` - Write options, as for `put/5` (default: `[]`)
+
+  ## Returns
+  - `{:ok, ids}` with one ID per term, in input order
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, :length_mismatch}` if `terms` and `types` differ in length
+  - `{:error, {:invalid_type, type}}` if a type is not a dictionary type
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :sequence_overflow}` if a sequence counter is exhausted
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+  - `{:error, {:get_failed, reason}}` on read errors or a malformed sequence counter
+  - `{:error, {:batch_failed, reason}}` on write errors
+
+  ## Examples
+
+      iex> {:ok, key} = StringToId.encode_term(RDF.iri("http://example.org/s"))
+      iex> {:ok, [id, id]} = NIF.dictionary_get_or_create(db, [key, key], [:uri, :uri])
+      iex> Dictionary.term_type(id)
+      :uri
+
+  """
+  @spec dictionary_get_or_create(db_ref(), [binary()], [:uri | :bnode | :literal], [write_option()]) ::
+          {:ok, [non_neg_integer()]} | {:error, term()}
+  def dictionary_get_or_create(_db_ref, _terms, _types, _opts \\ []),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  # ============================================================================
+  # Quad Operations
+  # ============================================================================
+
+  @type quad :: {term_id(), term_id(), term_id(), term_id()}
+  @type quads :: [quad()] | binary()
+
+  @doc """
+  Atomically inserts quads into the named graph indices.
+
+  Each `{g, s, p, o}` quad is written to `:gspo`, `:gpos`, `:gosp` and
+  `:spog` in a single WriteBatch. The default graph stays in the triple
+  indices written by `insert_triples/3`.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `quads` - List of `{g, s, p, o}` term ID tuples, or a binary of packed
+    32-byte records (four big-endian 64-bit IDs each)
+  - `opts` - Write options, as for `put/5` (default: `[]`)
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, :invalid_quad}` if a quad is not four non-negative 64-bit IDs
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+  - `{:error, {:batch_failed, reason}}` on other errors
+
+  ## Examples
+
+      iex> NIF.quad_insert(db, [{10, 1, 2, 3}, {11, 1, 2, 3}])
+      :ok
+
+  """
+  @spec quad_insert(db_ref(), quads(), [write_option()]) :: :ok | {:error, term()}
+  def quad_insert(_db_ref, _quads, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Atomically deletes quads from the named graph indices.
+
+  Deleting a quad that does not exist is a no-op.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `quads` - List of `{g, s, p, o}` term ID tuples, or a binary of packed
+    32-byte records
+  - `opts` - Write options, as for `put/5` (default: `[]`)
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, :invalid_quad}` if a quad is not four non-negative 64-bit IDs
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+  - `{:error, {:batch_failed, reason}}` on other errors
+  """
+  @spec quad_delete(db_ref(), quads(), [write_option()]) :: :ok | {:error, term()}
+  def quad_delete(_db_ref, _quads, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Atomically deletes every quad of a named graph.
+
+  The graph's blocks of `:gspo`, `:gpos` and `:gosp` are removed with range
+  tombstones; the `:spog` keys are found by scanning the graph's `:gspo`
+  block and deleted in the same WriteBatch. Quads inserted concurrently with
+  the call may survive in some indices.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `graph` - The graph term ID
+  - `opts` - Write options, as for `put/5` (default: `[]`)
+
+  ## Returns
+  - `{:ok, count}` with the number of quads deleted
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+  - `{:error, {:iterator_failed, reason}}` on read errors
+  - `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+  - `{:error, {:batch_failed, reason}}` on other errors
+  """
+  @spec delete_graph(db_ref(), term_id(), [write_option()]) ::
+          {:ok, non_neg_integer()} | {:error, term()}
+  def delete_graph(_db_ref, _graph, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Starts a lookup of the triples matching a pattern in one named graph.
+
+  Like `lookup_pattern/3`, but over `:gspo`, `:gpos` and `:gosp` with the
+  graph ID leading every key, so only the named graph is scanned. Matching
+  triples are read with `lookup_pattern_next/2` and the lookup is closed with
+  `lookup_pattern_close/1`.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `graph` - The graph term ID
+  - `pattern` - `{s, p, o}` tuple of term IDs, with `nil` for unbound positions
+
+  ## Returns
+  - `{:ok, lookup_ref}` on success
+  - `{:error, {:invalid_pattern, pattern}}` if the pattern is malformed
+  - `{:error, :already_closed}` if database is closed
+
+  ## Examples
+
+      iex> NIF.quad_insert(db, [{10, 1, 2, 3}, {11, 1, 2, 4}])
+      iex> {:ok, lookup} = NIF.lookup_graph_pattern(db, 10, {1, nil, nil})
+      iex> NIF.lookup_pattern_next(lookup, 100)
+      {:ok, [{1, 2, 3}], :end}
+
+  """
+  @spec lookup_graph_pattern(db_ref(), term_id(), lookup_pattern()) ::
+          {:ok, lookup_ref()} | {:error, term()}
+  def lookup_graph_pattern(_db_ref, _graph, _pattern), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates an Elixir Stream of the triples matching a pattern in one named graph.
+
+  Stream counterpart of `lookup_graph_pattern/3`; see `lookup_pattern_stream/3`.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `graph` - The graph term ID
+  - `pattern` - `{s, p, o}` tuple of term IDs, with `nil` for unbound positions
+
+  ## Returns
+  - `{:ok, Stream.t()}` on success
+  - `{:error, term()}` on failure
+
+  ## Examples
+
+      iex> {:ok, stream} = NIF.lookup_graph_pattern_stream(db, 10, {nil, nil, nil})
+      iex> Enum.to_list(stream)
+      [{1, 2, 3}]
+
+  """
+  @spec lookup_graph_pattern_stream(db_ref(), term_id(), lookup_pattern()) ::
+          {:ok, Enumerable.t()} | {:error, term()}
+  def lookup_graph_pattern_stream(db_ref, graph, pattern) do
+    db_ref |> lookup_graph_pattern(graph, pattern) |> lookup_stream()
+  end
+
+  @doc """
+  Lists the named graphs that contain a triple.
+
+  Reads the triple's block of `:spog`.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `triple` - `{s, p, o}` tuple of term IDs
+
+  ## Returns
+  - `{:ok, graphs}` with the graph term IDs in ascending order
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :invalid_triple}` if the triple is not three non-negative 64-bit IDs
+  - `{:error, {:iterator_failed, reason}}` on read errors
+
+  ## Examples
+
+      iex> NIF.triple_graphs(db, {1, 2, 3})
+      {:ok, [10, 11]}
+
+  """
+  @spec triple_graphs(db_ref(), triple()) :: {:ok, [term_id()]} | {:error, term()}
+  def triple_graphs(_db_ref, _triple), do: :erlang.nif_error(:nif_not_loaded)
+
+  # ============================================================================
+  # Change Data Capture
+  # ============================================================================
+
+  @type sequence_number :: non_neg_integer()
+  @type wal_iterator_ref :: reference()
+  @type wal_op ::
+          {:put, column_family() | non_neg_integer(), binary(), binary()}
+          | {:delete, column_family() | non_neg_integer(), binary(), nil}
+          | {:merge, column_family() | non_neg_integer(), binary(), binary()}
+          | {:delete_range, column_family() | non_neg_integer(), binary(), binary()}
+  @type wal_batch :: {sequence_number(), [wal_op()]}
+
+  @doc """
+  Returns the sequence number of the last committed write.
+
+  ## Arguments
+  - `db_ref` - The database reference
+
+  ## Returns
+  - `{:ok, seq}` on success
+  - `{:error, :already_closed}` if database is closed
+
+  ## Examples
+
+      iex> NIF.latest_sequence_number(db)
+      {:ok, 42}
+
+  """
+  @spec latest_sequence_number(db_ref()) :: {:ok, sequence_number()} | {:error, :already_closed}
+  def latest_sequence_number(_db_ref), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Opens an iterator over the write batches committed after `seq`.
+
+  The iterator tails the write-ahead log, so a subscriber can store the `seq`
+  of the last batch it processed and resume from it after a restart. Batches
+  are only available while their WAL files exist; open the database with
+  `:wal_ttl_seconds` or `:wal_size_limit_mb` to keep obsolete WAL files
+  around. Writes made with `disable_wal: true` do not appear in the feed.
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `seq` - Sequence number of the last batch already processed
+
+  ## Returns
+  - `{:ok, wal_iterator_ref}` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:wal_iterator_failed, reason}}` if `seq` is not yet written or
+    no longer in the WAL
+
+  ## Examples
+
+      iex> {:ok, seq} = NIF.latest_sequence_number(db)
+      iex> :ok = NIF.put(db, :id2str, "key", "value")
+      iex> {:ok, wal} = NIF.updates_since(db, seq)
+      iex> NIF.updates_next(wal, 10)
+      {:ok, [{43, [{:put, :id2str, "key", "value"}]}], :end}
+
+  """
+  @spec updates_since(db_ref(), sequence_number()) ::
+          {:ok, wal_iterator_ref()} | {:error, term()}
+  def updates_since(_db_ref, _seq), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Gets up to `n` write batches from a WAL iterator.
+
+  Each batch is `{seq, ops}`, where `seq` is the sequence number of the
+  batch's first write and `ops` lists its changes in write order:
+  - `{:put, cf, key, value}`
+  - `{:delete, cf, key, nil}`
+  - `{:merge, cf, key, operand}`
+  - `{:delete_range, cf, start_key, end_key}`
+
+  Column families created after the iterator was opened are reported by their
+  integer RocksDB ID. An iterator that returned `:end` does not see later
+  writes; open a new one with the last `seq` to continue.
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `wal_ref` - The WAL iterator reference
+  - `n` - Maximum number of batches to return
+
+  ## Returns
+  - `{:ok, batches, :more}` if `n` batches were read and more may follow
+  - `{:ok, batches, :end}` if the iterator reached the end of the WAL
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :iterator_closed}` if the iterator was closed
+  - `{:error, {:wal_iterator_failed, reason}}` on read errors
+
+  """
+  @spec updates_next(wal_iterator_ref(), pos_integer()) ::
+          {:ok, [wal_batch()], :more | :end} | {:error, term()}
+  def updates_next(_wal_ref, _n), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Closes a WAL iterator.
+
+  ## Arguments
+  - `wal_ref` - The WAL iterator reference
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :iterator_closed}` if already closed
+
+  """
+  @spec updates_close(wal_iterator_ref()) :: :ok | {:error, :iterator_closed}
+  def updates_close(_wal_ref), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates an Elixir Stream of the write batches committed after `seq`.
+
+  Reads batches #{@stream_chunk_size} at a time with `updates_next/2` until the
+  end of the WAL. The iterator is closed when the stream is done.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `seq` - Sequence number of the last batch already processed
+
+  ## Returns
+  - `{:ok, Stream.t()}` on success
+  - `{:error, term()}` on failure
+
+  ## Examples
+
+      iex> {:ok, stream} = NIF.updates_stream(db, seq)
+      iex> Enum.to_list(stream)
+      [{43, [{:put, :id2str, "key", "value"}]}]
+
+  """
+  @spec updates_stream(db_ref(), sequence_number()) :: {:ok, Enumerable.t()} | {:error, term()}
+  def updates_stream(db_ref, seq) do
+    case updates_since(db_ref, seq) do
+      {:ok, wal} ->
+        stream =
+          Stream.resource(
+            fn -> {wal, :more} end,
+            &updates_stream_next/1,
+            fn {wal, _status} -> updates_close(wal) end
+          )
+
+        {:ok, stream}
+
+      error ->
+        error
+    end
+  end
+
+  defp updates_stream_next({wal, :end}), do: {:halt, {wal, :end}}
+
+  defp updates_stream_next({wal, :more}) do
+    case updates_next(wal, @stream_chunk_size) do
+      {:ok, batches, status} -> {batches, {wal, status}}
+      {:error, _} -> {:halt, {wal, :end}}
+    end
+  end
+
+  # ============================================================================
+  # Properties and Statistics
+  # ============================================================================
+
+  @type histogram_stats :: %{
+          count: non_neg_integer(),
+          sum: non_neg_integer(),
+          min: float(),
+          max: float(),
+          average: float(),
+          median: float(),
+          p95: float(),
+          p99: float(),
+          std_dev: float()
+        }
+  @type statistics :: %{
+          tickers: %{String.t() => non_neg_integer()},
+          histograms: %{String.t() => histogram_stats()}
+        }
+
+  @doc """
+  Gets a RocksDB property as a string.
+
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary, or `nil` for
+    database-wide properties
+  - `name` - The property name, e.g. `"rocksdb.stats"`
+
+  ## Returns
+  - `{:ok, value}` on success
+  - `:not_found` if the property is unknown
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:property_failed, reason}}` if the name is malformed
+
+  ## Examples
+
+      iex> {:ok, stats} = NIF.get_property(db, :spo, "rocksdb.stats")
+      iex> is_binary(stats)
+      true
+
+  """
+  @spec get_property(db_ref(), column_family() | nil, String.t()) ::
+          {:ok, String.t()} | :not_found | {:error, term()}
+  def get_property(_db_ref, _cf, _name), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Gets a numeric RocksDB property.
+
+  Useful properties include `"rocksdb.estimate-num-keys"`,
+  `"rocksdb.cur-size-all-mem-tables"` and
+  `"rocksdb.estimate-pending-compaction-bytes"` per column family, and
+  `"rocksdb.num-running-compactions"` or `"rocksdb.block-cache-usage"` for
+  the whole database.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary, or `nil` for
+    database-wide properties
+  - `name` - The property name
+
+  ## Returns
+  - `{:ok, value}` on success
+  - `:not_found` if the property is unknown
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:property_failed, reason}}` if the name is malformed or the
+    value is not numeric
+
+  ## Examples
+
+      iex> NIF.get_int_property(db, :spo, "rocksdb.estimate-num-keys")
+      {:ok, 1000}
+
+  """
+  @spec get_int_property(db_ref(), column_family() | nil, String.t()) ::
+          {:ok, non_neg_integer()} | :not_found | {:error, term()}
+  def get_int_property(_db_ref, _cf, _name), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Returns the statistics collected since the database was opened.
+
+  Requires the database to be opened with `enable_statistics: true`. Tickers
+  are cumulative counters and histograms summarize the recorded values, both
+  keyed by their RocksDB name, so the result can be passed as measurements to
+  `:telemetry.execute/3`.
+  Uses dirty CPU scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+
+  ## Returns
+  - `{:ok, %{tickers: tickers, histograms: histograms}}` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :statistics_disabled}` if the database was opened without statistics
+
+  ## Examples
+
+      iex> {:ok, db} = NIF.open("/tmp/test_db", enable_statistics: true)
+      iex> {:ok, %{tickers: tickers}} = NIF.statistics(db)
+      iex> tickers["rocksdb.block.cache.hit"]
+      0
+
+  """
+  @spec statistics(db_ref()) :: {:ok, statistics()} | {:error, term()}
+  def statistics(_db_ref), do: :erlang.nif_error(:nif_not_loaded)
+
+  # ============================================================================
+  # Maintenance Operations
+  # ============================================================================
+
+  @type compact_option ::
+          {:exclusive | :change_level, boolean()}
+          | {:target_level, integer()}
+          | {:bottommost_level_compaction,
+             :skip | :if_have_compaction_filter | :force | :force_optimized}
+
+  @doc """
+  Compacts the keys in `[start_key, end_key]` of a column family.
+
+  Rewrites the overlapping SST files down to the bottommost level, dropping
+  deleted and overwritten entries along the way, e.g. to clear the tombstones
+  left by a large `delete_batch/3`. Memtables overlapping the range are
+  flushed first. Blocks until the compaction has finished; a concurrent
+  `close/1` waits for it.
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
+  - `start_key` - Inclusive start key, or `nil` for unbounded
+  - `end_key` - Inclusive end key, or `nil` for unbounded
+  - `opts` - Keyword list or map of options (default: `[]`):
+    - `:exclusive` - Do not run automatic compactions at the same time
+    - `:change_level` - Move the compacted files to `:target_level`
+    - `:target_level` - Level for `:change_level`
+    - `:bottommost_level_compaction` - `:skip`, `:if_have_compaction_filter`
+      (RocksDB's default), `:force` or `:force_optimized`
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+
+  ## Examples
+
+      iex> NIF.compact_range(db, :spo, nil, nil)
+      :ok
+
+      iex> NIF.compact_range(db, :derived, <<1::64>>, <<2::64>>, bottommost_level_compaction: :force)
+      :ok
+
+  """
+  @spec compact_range(
+          db_ref(),
+          column_family(),
+          binary() | nil,
+          binary() | nil,
+          [compact_option()]
+        ) :: :ok | {:error, term()}
+  def compact_range(_db_ref, _cf, _start_key, _end_key, _opts \\ []),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Flushes the memtables of a column family to SST files.
+
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
+  - `wait` - Whether to block until the flush has finished
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:flush_failed, reason}}` on failure
+
+  ## Examples
+
+      iex> NIF.flush(db, :spo, true)
+      :ok
+
+  """
+  @spec flush(db_ref(), column_family(), boolean()) :: :ok | {:error, term()}
+  def flush(_db_ref, _cf, _wait), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Writes the buffered WAL entries to the WAL file.
+
+  With `sync` the WAL file is also fsynced, making every earlier write that
+  was not made with `sync: true` durable at once.
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `sync` - Whether to also fsync the WAL file
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:flush_failed, reason}}` on failure
+
+  ## Examples
+
+      iex> NIF.flush_wal(db, true)
+      :ok
+
+  """
+  @spec flush_wal(db_ref(), boolean()) :: :ok | {:error, term()}
+  def flush_wal(_db_ref, _sync), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Changes mutable options of a column family on the running database.
+
+  Keys are RocksDB option names such as `disable_auto_compactions`,
+  `write_buffer_size` or `level0_file_num_compaction_trigger`, as atoms or
+  strings. Values may be booleans, numbers, atoms or strings and are passed
+  to RocksDB in string form. The change lasts until the database is closed;
+  a reopened database applies the `open/2` options.
+  Uses dirty IO scheduler to prevent blocking BEAM schedulers.
+
+  ## Arguments
+  - `db_ref` - The database reference
+  - `cf` - The column family name as an atom or binary
+  - `opts` - List or map of `{key, value}` pairs
+
+  ## Returns
+  - `:ok` on success
+  - `{:error, :already_closed}` if database is closed
+  - `{:error, :read_only}` if the database was opened read-only or as a secondary
+  - `{:error, {:invalid_cf, cf}}` if column family is invalid
+  - `{:error, {:invalid_option, term}}` if a key or value cannot be converted
+  - `{:error, {:set_options_failed, reason}}` if RocksDB rejects an option
+
+  ## Examples
+
+      iex> NIF.set_options(db, :spo, disable_auto_compactions: true)
+      :ok
+
+      iex> NIF.set_options(db, :spo, [{"write_buffer_size", 128 * 1024 * 1024}])
+      :ok
+
+  """
+  @spec set_options(db_ref(), column_family(), [{atom() | String.t(), term()}] | map()) ::
+          :ok | {:error, term()}
+  def set_options(_db_ref, _cf, _opts), do: :erlang.nif_error(:nif_not_loaded)
 end
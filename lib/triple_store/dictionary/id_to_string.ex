@@ -91,7 +91,8 @@ defmodule TripleStore.Dictionary.IdToString do
   Looks up multiple terms by their IDs.
 
   This is a batch version of `lookup_term/2` for efficient result
-  serialization. For inline-encoded IDs, values are computed directly.
+  serialization. Dictionary-allocated IDs are fetched with a single
+  `multi_get` call; for inline-encoded IDs, values are computed directly.
 
   ## Arguments
 
@@ -111,7 +112,26 @@ defmodule TripleStore.Dictionary.IdToString do
   @spec lookup_terms(db_ref(), [Dictionary.term_id()]) ::
           {:ok, [{:ok, rdf_term()} | :not_found]} | {:error, term()}
   def lookup_terms(db, ids) do
-    Batch.map_with_early_error(ids, &lookup_term(db, &1))
+    dictionary_keys =
+      for id <- ids, not Dictionary.inline_encoded?(id), do: <<id::64-big>>
+
+    case NIF.multi_get(db, :id2str, dictionary_keys) do
+      {:ok, values} ->
+        {lookups, []} =
+          Enum.map_reduce(ids, values, fn id, values ->
+            if Dictionary.inline_encoded?(id) do
+              {{:inline, id}, values}
+            else
+              [value | rest] = values
+              {{:stored, value}, rest}
+            end
+          end)
+
+        Batch.map_with_early_error(lookups, &resolve_lookup/1)
+
+      {:error, _} = error ->
+        error
+    end
   end
 
   @doc """
@@ -200,6 +220,12 @@ defmodule TripleStore.Dictionary.IdToString do
     end
   end
 
+  @spec resolve_lookup({:inline, Dictionary.term_id()} | {:stored, {:ok, binary()} | :not_found}) ::
+          {:ok, rdf_term()} | :not_found | {:error, term()}
+  defp resolve_lookup({:inline, id}), do: decode_inline_term(id)
+  defp resolve_lookup({:stored, {:ok, term_binary}}), do: decode_term(term_binary)
+  defp resolve_lookup({:stored, :not_found}), do: :not_found
+
   @spec decode_inline_term(Dictionary.term_id()) :: {:ok, rdf_term()} | {:error, term()}
   defp decode_inline_term(id) do
     case Dictionary.term_type(id) do
@@ -202,7 +202,8 @@ defmodule TripleStore.Dictionary.StringToId do
   @doc """
   Looks up IDs for multiple terms.
 
-  Batch version of `lookup_id/2` for efficient bulk operations.
+  Batch version of `lookup_id/2` for efficient bulk operations. All terms
+  are looked up with a single `multi_get` call.
 
   ## Arguments
 
@@ -217,13 +218,20 @@ defmodule TripleStore.Dictionary.StringToId do
   @spec lookup_ids(db_ref(), [rdf_term()]) ::
           {:ok, [{:ok, Dictionary.term_id()} | :not_found]} | {:error, term()}
   def lookup_ids(db, terms) do
-    Batch.map_with_early_error(terms, &lookup_id(db, &1))
+    with {:ok, keys} <- Batch.map_collect_success(terms, &encode_term/1),
+         {:ok, values} <- NIF.multi_get(db, :str2id, keys) do
+      {:ok, Enum.map(values, &decode_id_value/1)}
+    end
   end
 
   # ===========================================================================
   # Private Functions
   # ===========================================================================
 
+  @spec decode_id_value({:ok, binary()} | :not_found) :: {:ok, Dictionary.term_id()} | :not_found
+  defp decode_id_value({:ok, <<id::64-big>>}), do: {:ok, id}
+  defp decode_id_value(:not_found), do: :not_found
+
   @spec strip_angle_brackets(String.t()) :: String.t()
   defp strip_angle_brackets(<<"<", rest::binary>>) do
     # Remove leading < and trailing >
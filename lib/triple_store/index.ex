@@ -59,9 +59,6 @@ defmodule TripleStore.Index do
   # Maximum valid term ID (64-bit unsigned integer)
   @max_term_id (1 <<< 64) - 1
 
-  # Empty binary value for index entries (key contains all information)
-  @empty_value <<>>
-
   # ===========================================================================
   # Guards
   # ===========================================================================
@@ -511,13 +508,9 @@ defmodule TripleStore.Index do
 
   """
   @spec insert_triple(NIF.db_ref(), triple()) :: :ok | {:error, term()}
-  def insert_triple(db, {subject, predicate, object}) when valid_triple?(subject, predicate, object) do
-    operations =
-      for {cf, key} <- encode_triple_keys(subject, predicate, object) do
-        {cf, key, @empty_value}
-      end
-
-    NIF.write_batch(db, operations)
+  def insert_triple(db, {subject, predicate, object} = triple)
+      when valid_triple?(subject, predicate, object) do
+    NIF.insert_triples(db, [triple])
   end
 
   @doc """
@@ -549,13 +542,7 @@ defmodule TripleStore.Index do
   def insert_triples(_db, []), do: :ok
 
   def insert_triples(db, triples) when is_list(triples) do
-    operations =
-      for {subject, predicate, object} <- triples,
-          {cf, key} <- encode_triple_keys(subject, predicate, object) do
-        {cf, key, @empty_value}
-      end
-
-    NIF.write_batch(db, operations)
+    NIF.insert_triples(db, triples)
   end
 
   @doc """
@@ -624,13 +611,9 @@ defmodule TripleStore.Index do
 
   """
   @spec delete_triple(NIF.db_ref(), triple()) :: :ok | {:error, term()}
-  def delete_triple(db, {subject, predicate, object}) when valid_triple?(subject, predicate, object) do
-    operations =
-      for {cf, key} <- encode_triple_keys(subject, predicate, object) do
-        {cf, key}
-      end
-
-    NIF.delete_batch(db, operations)
+  def delete_triple(db, {subject, predicate, object} = triple)
+      when valid_triple?(subject, predicate, object) do
+    NIF.delete_triples(db, [triple])
   end
 
   @doc """
@@ -664,13 +647,7 @@ defmodule TripleStore.Index do
   def delete_triples(_db, []), do: :ok
 
   def delete_triples(db, triples) when is_list(triples) do
-    operations =
-      for {subject, predicate, object} <- triples,
-          {cf, key} <- encode_triple_keys(subject, predicate, object) do
-        {cf, key}
-      end
-
-    NIF.delete_batch(db, operations)
+    NIF.delete_triples(db, triples)
   end
 
   # ===========================================================================
@@ -880,7 +857,9 @@ defmodule TripleStore.Index do
 
   Uses the optimal index based on which pattern positions are bound,
   constructs the appropriate prefix, and iterates over matching entries.
-  For the S?O pattern, results are post-filtered by predicate.
+  For the S?O pattern, results are post-filtered by predicate. Index
+  selection, key decoding, and filtering all run natively in
+  `NIF.lookup_pattern/3`, so only matching triples reach the BEAM.
 
   ## Arguments
 
@@ -902,23 +881,13 @@ defmodule TripleStore.Index do
 
   """
   @spec lookup(NIF.db_ref(), pattern()) :: {:ok, Enumerable.t()} | {:error, term()}
-  def lookup(db, pattern) do
-    %{index: index, prefix: prefix, needs_filter: needs_filter} = select_index(pattern)
-
-    with {:ok, stream} <- NIF.prefix_stream(db, index, prefix) do
-      decoded_stream = Stream.map(stream, fn {key, _value} -> key_to_triple(index, key) end)
-
-      final_stream =
-        if needs_filter do
-          Stream.filter(decoded_stream, &triple_matches_pattern?(&1, pattern))
-        else
-          decoded_stream
-        end
-
-      {:ok, final_stream}
-    end
+  def lookup(db, {s, p, o}) do
+    NIF.lookup_pattern_stream(db, {lookup_term(s), lookup_term(p), lookup_term(o)})
   end
 
+  defp lookup_term({:bound, id}) when valid_term_id?(id), do: id
+  defp lookup_term(:var), do: nil
+
   @doc """
   Returns a list of all triples matching the given pattern.
 
@@ -953,6 +922,10 @@ defmodule TripleStore.Index do
   @doc """
   Counts the number of triples matching the given pattern.
 
+  Patterns answered by an index prefix are counted key-only inside the NIF
+  with `NIF.count_prefix/3`, without materializing any triples. The S?O
+  pattern, which needs filtering by predicate, counts the lookup stream.
+
   ## Arguments
 
   - `db` - RocksDB database reference
@@ -972,9 +945,14 @@ defmodule TripleStore.Index do
   """
   @spec count(NIF.db_ref(), pattern()) :: {:ok, non_neg_integer()} | {:error, term()}
   def count(db, pattern) do
-    case lookup(db, pattern) do
-      {:ok, stream} -> {:ok, Enum.count(stream)}
-      {:error, _} = error -> error
+    case select_index(pattern) do
+      %{needs_filter: false, index: index, prefix: prefix} ->
+        NIF.count_prefix(db, index, prefix)
+
+      %{needs_filter: true} ->
+        with {:ok, stream} <- lookup(db, pattern) do
+          {:ok, Enum.count(stream)}
+        end
     end
   end
 end
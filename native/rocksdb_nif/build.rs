@@ -0,0 +1,29 @@
+//! Compiles the C++ event listener shim in `src/event_listener.cc` against
+//! the RocksDB headers of the librocksdb-sys build the NIF links to.
+
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/event_listener.cc");
+    println!("cargo:rerun-if-env-changed=ROCKSDB_INCLUDE_DIR");
+
+    // A system RocksDB (`ROCKSDB_LIB_DIR`) comes with its own headers;
+    // otherwise use the sources bundled with librocksdb-sys.
+    let include = match env::var_os("ROCKSDB_INCLUDE_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let dir = env::var_os("DEP_ROCKSDB_CARGO_MANIFEST_DIR")
+                .expect("librocksdb-sys did not report its manifest directory");
+            PathBuf::from(dir).join("rocksdb").join("include")
+        }
+    };
+
+    cc::Build::new()
+        .cpp(true)
+        .std("c++17")
+        .define("NDEBUG", Some("1"))
+        .include(include)
+        .file("src/event_listener.cc")
+        .compile("triple_store_event_listener");
+}
@@ -0,0 +1,183 @@
+// RocksDB event listener shim for the TripleStore NIF.
+//
+// Neither the rocksdb crate nor RocksDB's C API exposes `EventListener`, so
+// this file implements one that copies the fields the NIF reports into plain
+// C structs and hands them to callbacks defined in lib.rs. The callbacks run
+// on whichever thread RocksDB notifies from and must not block.
+
+#include <algorithm>
+#include <cstddef>
+#include <cstdint>
+#include <memory>
+#include <string>
+#include <vector>
+
+#include "rocksdb/listener.h"
+#include "rocksdb/options.h"
+
+using ROCKSDB_NAMESPACE::CompactionJobInfo;
+using ROCKSDB_NAMESPACE::DB;
+using ROCKSDB_NAMESPACE::EventListener;
+using ROCKSDB_NAMESPACE::FlushJobInfo;
+using ROCKSDB_NAMESPACE::WriteStallCondition;
+using ROCKSDB_NAMESPACE::WriteStallInfo;
+
+// Same definition as in RocksDB's db/c.cc, which no header exports.
+struct rocksdb_options_t {
+  ROCKSDB_NAMESPACE::Options rep;
+};
+
+extern "C" {
+
+// Mirrored by `RawFlushInfo` in lib.rs.
+struct triple_store_flush_info {
+  const char* cf_name;
+  size_t cf_name_len;
+  const char* file_path;
+  size_t file_path_len;
+  int job_id;
+  uint64_t num_entries;
+  uint64_t num_deletions;
+  uint64_t smallest_seqno;
+  uint64_t largest_seqno;
+  bool triggered_writes_slowdown;
+  bool triggered_writes_stop;
+};
+
+// Mirrored by `RawCompactionInfo` in lib.rs. `status` is empty when the
+// compaction succeeded.
+struct triple_store_compaction_info {
+  const char* cf_name;
+  size_t cf_name_len;
+  const char* status;
+  size_t status_len;
+  int job_id;
+  int base_input_level;
+  int output_level;
+  size_t input_files;
+  size_t output_files;
+  uint64_t input_bytes;
+  uint64_t output_bytes;
+  uint64_t input_records;
+  uint64_t output_records;
+  uint64_t elapsed_micros;
+  bool manual;
+  bool trivial_move;
+};
+
+// Mirrored by `RawStallInfo` in lib.rs. Conditions are 0 for normal,
+// 1 for delayed and 2 for stopped.
+struct triple_store_stall_info {
+  const char* cf_name;
+  size_t cf_name_len;
+  int previous;
+  int current;
+};
+
+// Mirrored by `EventCallbacks` in lib.rs. `destroy` releases `state` once
+// the database and every copy of its options are gone.
+struct triple_store_event_callbacks {
+  void (*flush_completed)(void* state, const triple_store_flush_info* info);
+  void (*compaction_completed)(void* state,
+                               const triple_store_compaction_info* info);
+  void (*stall_conditions_changed)(void* state,
+                                   const triple_store_stall_info* info);
+  void (*destroy)(void* state);
+};
+
+}  // extern "C"
+
+namespace {
+
+int StallCondition(WriteStallCondition condition) {
+  switch (condition) {
+    case WriteStallCondition::kDelayed:
+      return 1;
+    case WriteStallCondition::kStopped:
+      return 2;
+    default:
+      return 0;
+  }
+}
+
+class ForwardingEventListener : public EventListener {
+ public:
+  ForwardingEventListener(void* state,
+                          const triple_store_event_callbacks& callbacks)
+      : state_(state), callbacks_(callbacks) {}
+
+  ~ForwardingEventListener() override { callbacks_.destroy(state_); }
+
+  ForwardingEventListener(const ForwardingEventListener&) = delete;
+  ForwardingEventListener& operator=(const ForwardingEventListener&) = delete;
+
+  const char* Name() const override { return "TripleStoreEventListener"; }
+
+  void OnFlushCompleted(DB* /*db*/, const FlushJobInfo& info) override {
+    triple_store_flush_info flush{};
+    flush.cf_name = info.cf_name.data();
+    flush.cf_name_len = info.cf_name.size();
+    flush.file_path = info.file_path.data();
+    flush.file_path_len = info.file_path.size();
+    flush.job_id = info.job_id;
+    flush.num_entries = info.table_properties.num_entries;
+    flush.num_deletions = info.table_properties.num_deletions;
+    flush.smallest_seqno = info.smallest_seqno;
+    flush.largest_seqno = info.largest_seqno;
+    flush.triggered_writes_slowdown = info.triggered_writes_slowdown;
+    flush.triggered_writes_stop = info.triggered_writes_stop;
+    callbacks_.flush_completed(state_, &flush);
+  }
+
+  void OnCompactionCompleted(DB* /*db*/,
+                             const CompactionJobInfo& info) override {
+    // A trivial move keeps its files and only changes their level
+    std::vector<std::string> inputs = info.input_files;
+    std::vector<std::string> outputs = info.output_files;
+    std::sort(inputs.begin(), inputs.end());
+    std::sort(outputs.begin(), outputs.end());
+
+    std::string status = info.status.ok() ? "" : info.status.ToString();
+
+    triple_store_compaction_info compaction{};
+    compaction.cf_name = info.cf_name.data();
+    compaction.cf_name_len = info.cf_name.size();
+    compaction.status = status.data();
+    compaction.status_len = status.size();
+    compaction.job_id = info.job_id;
+    compaction.base_input_level = info.base_input_level;
+    compaction.output_level = info.output_level;
+    compaction.input_files = info.input_files.size();
+    compaction.output_files = info.output_files.size();
+    compaction.input_bytes = info.stats.total_input_bytes;
+    compaction.output_bytes = info.stats.total_output_bytes;
+    compaction.input_records = info.stats.num_input_records;
+    compaction.output_records = info.stats.num_output_records;
+    compaction.elapsed_micros = info.stats.elapsed_micros;
+    compaction.manual = info.stats.is_manual_compaction;
+    compaction.trivial_move = !inputs.empty() && inputs == outputs;
+    callbacks_.compaction_completed(state_, &compaction);
+  }
+
+  void OnStallConditionsChanged(const WriteStallInfo& info) override {
+    triple_store_stall_info stall{};
+    stall.cf_name = info.cf_name.data();
+    stall.cf_name_len = info.cf_name.size();
+    stall.previous = StallCondition(info.condition.prev);
+    stall.current = StallCondition(info.condition.cur);
+    callbacks_.stall_conditions_changed(state_, &stall);
+  }
+
+ private:
+  void* state_;
+  triple_store_event_callbacks callbacks_;
+};
+
+}  // namespace
+
+extern "C" void triple_store_add_event_listener(
+    rocksdb_options_t* options, void* state,
+    const triple_store_event_callbacks* callbacks) {
+  options->rep.listeners.push_back(
+      std::make_shared<ForwardingEventListener>(state, *callbacks));
+}
@@ -4,18 +4,139 @@
 //! Elixir application. All I/O operations use dirty CPU schedulers to prevent
 //! blocking the BEAM schedulers.
 
-use rocksdb::{ColumnFamilyDescriptor, DBIteratorWithThreadMode, IteratorMode, Options, ReadOptions, SnapshotWithThreadMode, WriteBatch, DB};
+use rocksdb::backup::{BackupEngine, BackupEngineOptions, RestoreOptions};
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::merge_operator::MergeOperands;
+use rocksdb::statistics::{Histogram, Ticker};
+use rocksdb::{AsColumnFamilyRef, BlockBasedOptions, BottommostLevelCompaction, Cache, ColumnFamily, ColumnFamilyDescriptor, CompactOptions, DBCompactionStyle, DBCompressionType, DBIteratorWithThreadMode, DBPinnableSlice, DBRawIteratorWithThreadMode, DBWALIterator, Error, ErrorKind, FlushOptions, IngestExternalFileOptions, IteratorMode, LiveFile, OptimisticTransactionDB, OptimisticTransactionOptions, Options, ReadOptions, SliceTransform, SnapshotWithThreadMode, SstFileWriter, Transaction, WriteBatch, WriteBatchWithTransaction, WriteOptions, DB, DEFAULT_COLUMN_FAMILY_NAME};
 use rustler::{Binary, Encoder, Env, ListIterator, NewBinary, NifResult, Resource, ResourceArc, Term};
-use std::sync::{Arc, Mutex, RwLock};
+use unicode_normalization::UnicodeNormalization;
+use std::collections::HashMap;
+use std::ffi::{c_int, c_void};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, Weak};
 
 /// Column family names used by TripleStore
-const CF_NAMES: [&str; 6] = ["id2str", "str2id", "spo", "pos", "osp", "derived"];
+const CF_NAMES: [&str; 11] = [
+    "id2str", "str2id", "spo", "pos", "osp", "derived", "counters", "gspo", "gpos", "gosp", "spog",
+];
+
+/// Column family whose values are u64 counters combined by `u64_add_merge`.
+const COUNTERS_CF: &str = "counters";
+
+/// Prefix length for the fixed-prefix extractor on the index column families.
+/// One 64-bit term ID: 8-byte scans match it exactly and 16-byte scans stay
+/// within a single extractor prefix.
+const INDEX_PREFIX_LENGTH: usize = 8;
+
+/// Default bloom filter bits per key for the index and dictionary column families.
+const DEFAULT_BLOOM_FILTER_BITS: f64 = 10.0;
+
+/// Number of `0xff` bytes appended to a prefix to form a reverse seek target
+/// after every key with that prefix. Longer than any index key suffix.
+const SEEK_FOR_PREV_CLAMP_PADDING: usize = 64;
+
+/// Size in bytes of one term ID in an index key.
+const TERM_ID_SIZE: usize = 8;
+
+/// Triple indices with their key layout as positions in `(s, p, o)`.
+const TRIPLE_INDICES: [(&str, [usize; 3]); 3] = [
+    ("spo", [0, 1, 2]),
+    ("pos", [1, 2, 0]),
+    ("osp", [2, 0, 1]),
+];
+
+/// An open RocksDB database.
+///
+/// `Transactional` databases are opened as an `OptimisticTransactionDB` so
+/// they can also begin transactions; every other operation behaves the same
+/// on both variants.
+enum Database {
+    Plain(DB),
+    Transactional(OptimisticTransactionDB),
+}
+
+/// Evaluates `$body` with `$db` bound to the database inside either variant.
+macro_rules! with_database {
+    ($database:expr, $db:ident => $body:expr) => {
+        match $database {
+            Database::Plain($db) => $body,
+            Database::Transactional($db) => $body,
+        }
+    };
+}
+
+/// Key-value iterator over a database or over a transaction's view of it.
+enum DbIterator<'a> {
+    Plain(DBIteratorWithThreadMode<'a, DB>),
+    Transactional(DBIteratorWithThreadMode<'a, OptimisticTransactionDB>),
+    Transaction(DBIteratorWithThreadMode<'a, Transaction<'a, OptimisticTransactionDB>>),
+}
+
+/// Evaluates `$body` with `$iter` bound to the iterator inside any variant.
+macro_rules! with_iterator {
+    ($iterator:expr, $iter:ident => $body:expr) => {
+        match $iterator {
+            DbIterator::Plain($iter) => $body,
+            DbIterator::Transactional($iter) => $body,
+            DbIterator::Transaction($iter) => $body,
+        }
+    };
+}
+
+/// Raw (unchecked, seekable) iterator over a database.
+enum DbRawIterator<'a> {
+    Plain(DBRawIteratorWithThreadMode<'a, DB>),
+    Transactional(DBRawIteratorWithThreadMode<'a, OptimisticTransactionDB>),
+}
+
+/// Evaluates `$body` with `$iter` bound to the raw iterator inside either variant.
+macro_rules! with_raw_iterator {
+    ($iterator:expr, $iter:ident => $body:expr) => {
+        match $iterator {
+            DbRawIterator::Plain($iter) => $body,
+            DbRawIterator::Transactional($iter) => $body,
+        }
+    };
+}
+
+/// Point-in-time snapshot of a database.
+enum DbSnapshot<'a> {
+    Plain(SnapshotWithThreadMode<'a, DB>),
+    Transactional(SnapshotWithThreadMode<'a, OptimisticTransactionDB>),
+}
+
+/// How a database was opened.
+#[derive(PartialEq)]
+enum Access {
+    ReadWrite,
+    ReadOnly,
+    /// A secondary instance of the primary at `path`, keeping its own files
+    /// in the given directory
+    Secondary(String),
+}
 
 /// Database reference wrapper for safe cross-NIF-boundary passing.
 /// Uses RwLock to allow concurrent reads with exclusive writes.
 pub struct DbRef {
-    db: RwLock<Option<DB>>,
+    db: RwLock<Option<Database>>,
     path: String,
+    /// Whether writes are allowed, and how to follow a primary
+    access: Access,
+    /// Prefix extractor length for each column family that has one
+    prefix_lengths: RwLock<HashMap<String, usize>>,
+    /// Column family settings from `open/2`, the defaults for created column families
+    cf_defaults: CfSettings,
+    /// Settings each column family was opened with, overrides included
+    cf_settings: HashMap<String, CfSettings>,
+    /// Block cache shared by all column families, including created ones
+    block_cache: Option<Cache>,
+    /// Copy of the open options, kept to read the statistics they collect
+    /// when opened with `enable_statistics: true`
+    statistics: Option<Options>,
+    /// Serializes `increment_and_get` so each caller reads its own increment
+    counter_lock: Mutex<()>,
+    /// Dictionary sequence counters, loaded from `str2id` on first use
+    sequences: Mutex<Option<[u64; 3]>>,
 }
 
 #[rustler::resource_impl]
@@ -28,13 +149,16 @@ impl Resource for DbRef {}
 pub struct IteratorRef {
     /// The RocksDB iterator. Uses 'static lifetime with raw pointer internally.
     /// Safety: The DbRef Arc keeps the database alive for the iterator's lifetime.
-    iterator: Mutex<Option<DBIteratorWithThreadMode<'static, DB>>>,
+    /// Shared so that a transaction can close its iterators when it ends.
+    iterator: Arc<Mutex<Option<DbIterator<'static>>>>,
     /// Reference to the database to keep it alive
     _db_ref: Arc<ResourceArc<DbRef>>,
+    /// Reference to the transaction a `txn_prefix_iterator` reads through
+    txn_ref: Option<Arc<ResourceArc<TransactionRef>>>,
     /// The prefix used for this iterator (for bounds checking)
     prefix: Vec<u8>,
     /// Column family name for this iterator
-    cf_name: String,
+    _cf_name: String,
 }
 
 #[rustler::resource_impl]
@@ -45,7 +169,7 @@ impl Resource for IteratorRef {}
 pub struct SnapshotRef {
     /// The RocksDB snapshot. Uses 'static lifetime with raw pointer internally.
     /// Safety: The DbRef Arc keeps the database alive for the snapshot's lifetime.
-    snapshot: Mutex<Option<SnapshotWithThreadMode<'static, DB>>>,
+    snapshot: Mutex<Option<DbSnapshot<'static>>>,
     /// Reference to the database to keep it alive
     db_ref: Arc<ResourceArc<DbRef>>,
 }
@@ -56,24 +180,413 @@ impl Resource for SnapshotRef {}
 /// Snapshot iterator reference for iterating over a snapshot.
 pub struct SnapshotIteratorRef {
     /// The RocksDB iterator over snapshot.
-    iterator: Mutex<Option<DBIteratorWithThreadMode<'static, DB>>>,
+    iterator: Mutex<Option<DbIterator<'static>>>,
     /// Reference to snapshot to keep it alive
     _snapshot_ref: Arc<ResourceArc<SnapshotRef>>,
     /// The prefix used for this iterator (for bounds checking)
     prefix: Vec<u8>,
     /// Column family name for this iterator
-    cf_name: String,
+    _cf_name: String,
 }
 
 #[rustler::resource_impl]
 impl Resource for SnapshotIteratorRef {}
 
+/// Database or snapshot that a native read operation holds open.
+enum ReadSource {
+    Db { _db_ref: Arc<ResourceArc<DbRef>> },
+    Snapshot { _snapshot_ref: Arc<ResourceArc<SnapshotRef>> },
+}
+
+/// Leapfrog Triejoin reference for streaming join results across NIF calls.
+pub struct LeapfrogRef {
+    /// The join state, holding one RocksDB iterator per pattern.
+    /// Safety: `_source` keeps the database or snapshot alive for the iterators' lifetime.
+    join: Mutex<Option<LeapfrogJoin>>,
+    /// Reference to the database or snapshot to keep it alive
+    _source: ReadSource,
+}
+
+#[rustler::resource_impl]
+impl Resource for LeapfrogRef {}
+
+/// Pattern lookup reference for streaming matching triples across NIF calls.
+pub struct PatternLookupRef {
+    /// The lookup state, holding one RocksDB iterator over the selected index.
+    /// Safety: `_source` keeps the database or snapshot alive for the iterator's lifetime.
+    lookup: Mutex<Option<PatternLookup>>,
+    /// Reference to the database or snapshot to keep it alive
+    _source: ReadSource,
+}
+
+#[rustler::resource_impl]
+impl Resource for PatternLookupRef {}
+
+/// Transaction reference for optimistic transactions on a transactional database.
+pub struct TransactionRef {
+    /// The RocksDB transaction, `None` once committed or rolled back.
+    /// Safety: The DbRef Arc keeps the database alive for the transaction's lifetime.
+    txn: Mutex<Option<Transaction<'static, OptimisticTransactionDB>>>,
+    /// Iterators reading through the transaction, closed when it ends
+    iterators: Mutex<Vec<Weak<Mutex<Option<DbIterator<'static>>>>>>,
+    /// Reference to the database to keep it alive
+    db_ref: Arc<ResourceArc<DbRef>>,
+}
+
+#[rustler::resource_impl]
+impl Resource for TransactionRef {}
+
+/// SST file writer reference for bulk loading.
+pub struct SstWriterRef {
+    /// The writer, `None` once finished
+    writer: Mutex<Option<SstWriter>>,
+}
+
+#[rustler::resource_impl]
+impl Resource for SstWriterRef {}
+
+/// WAL iterator that may be moved between dirty scheduler threads.
+struct WalIterator(DBWALIterator);
+
+// Safety: the iterator owns its RocksDB handle and is only used behind the
+// `WalIteratorRef` mutex, so it is never accessed from two threads at once.
+unsafe impl Send for WalIterator {}
+
+/// WAL iterator reference for streaming committed write batches across NIF calls.
+pub struct WalIteratorRef {
+    /// The WAL iterator, `None` once closed.
+    /// Safety: The DbRef Arc keeps the database alive for the iterator's lifetime.
+    iterator: Mutex<Option<WalIterator>>,
+    /// Column family names by RocksDB column family ID
+    cf_names: HashMap<u32, String>,
+    /// Reference to the database to keep it alive
+    db_ref: Arc<ResourceArc<DbRef>>,
+}
+
+#[rustler::resource_impl]
+impl Resource for WalIteratorRef {}
+
+impl IteratorRef {
+    /// Locks the transaction this iterator reads through, if any.
+    ///
+    /// RocksDB transactions are not thread-safe, so a transaction iterator
+    /// must not advance while another call writes to the transaction.
+    fn lock_transaction(&self) -> NifResult<Option<MutexGuard<'_, Option<Transaction<'static, OptimisticTransactionDB>>>>> {
+        self.txn_ref
+            .as_ref()
+            .map(|txn_ref| txn_ref.txn.lock().map_err(|_| rustler::Error::Term(Box::new("lock poisoned"))))
+            .transpose()
+    }
+}
+
+impl Database {
+    fn cf_handle(&self, name: &str) -> Option<&ColumnFamily> {
+        with_database!(self, db => db.cf_handle(name))
+    }
+
+    fn get_cf(&self, cf: &impl AsColumnFamilyRef, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        with_database!(self, db => db.get_cf(cf, key))
+    }
+
+    fn get_cf_opt(&self, cf: &impl AsColumnFamilyRef, key: &[u8], read_opts: &ReadOptions) -> Result<Option<Vec<u8>>, Error> {
+        with_database!(self, db => db.get_cf_opt(cf, key, read_opts))
+    }
+
+    fn batched_multi_get_cf_opt<'k>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        keys: impl IntoIterator<Item = &'k [u8]>,
+        sorted_input: bool,
+        read_opts: &ReadOptions,
+    ) -> Vec<Result<Option<DBPinnableSlice<'_>>, Error>> {
+        with_database!(self, db => db.batched_multi_get_cf_opt(cf, keys, sorted_input, read_opts))
+    }
+
+    fn put_cf_opt(&self, cf: &impl AsColumnFamilyRef, key: &[u8], value: &[u8], write_opts: &WriteOptions) -> Result<(), Error> {
+        with_database!(self, db => db.put_cf_opt(cf, key, value, write_opts))
+    }
+
+    fn delete_cf_opt(&self, cf: &impl AsColumnFamilyRef, key: &[u8], write_opts: &WriteOptions) -> Result<(), Error> {
+        with_database!(self, db => db.delete_cf_opt(cf, key, write_opts))
+    }
+
+    /// Atomically applies a write batch.
+    fn write_opt(&self, batch: WriteBatch, write_opts: &WriteOptions) -> Result<(), Error> {
+        match self {
+            Database::Plain(db) => db.write_opt(batch, write_opts),
+            Database::Transactional(db) => db.write_opt(WriteBatchWithTransaction::from_data(batch.data()), write_opts),
+        }
+    }
+
+    fn iterator_cf_opt<'a>(&'a self, cf: &impl AsColumnFamilyRef, read_opts: ReadOptions, mode: IteratorMode) -> DbIterator<'a> {
+        match self {
+            Database::Plain(db) => DbIterator::Plain(db.iterator_cf_opt(cf, read_opts, mode)),
+            Database::Transactional(db) => DbIterator::Transactional(db.iterator_cf_opt(cf, read_opts, mode)),
+        }
+    }
+
+    fn raw_iterator_cf_opt<'a>(&'a self, cf: &impl AsColumnFamilyRef, read_opts: ReadOptions) -> DbRawIterator<'a> {
+        match self {
+            Database::Plain(db) => DbRawIterator::Plain(db.raw_iterator_cf_opt(cf, read_opts)),
+            Database::Transactional(db) => DbRawIterator::Transactional(db.raw_iterator_cf_opt(cf, read_opts)),
+        }
+    }
+
+    fn snapshot(&self) -> DbSnapshot<'_> {
+        match self {
+            Database::Plain(db) => DbSnapshot::Plain(db.snapshot()),
+            Database::Transactional(db) => DbSnapshot::Transactional(db.snapshot()),
+        }
+    }
+
+    fn property_value_cf(&self, cf: &impl AsColumnFamilyRef, name: &str) -> Result<Option<String>, Error> {
+        with_database!(self, db => db.property_value_cf(cf, name))
+    }
+
+    fn property_int_value_cf(&self, cf: &impl AsColumnFamilyRef, name: &str) -> Result<Option<u64>, Error> {
+        with_database!(self, db => db.property_int_value_cf(cf, name))
+    }
+
+    fn ingest_external_file_cf_opts(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        ingest_opts: &IngestExternalFileOptions,
+        paths: Vec<String>,
+    ) -> Result<(), Error> {
+        with_database!(self, db => db.ingest_external_file_cf_opts(cf, ingest_opts, paths))
+    }
+
+    fn live_files(&self) -> Result<Vec<LiveFile>, Error> {
+        with_database!(self, db => db.live_files())
+    }
+
+    fn create_cf(&mut self, name: &str, opts: &Options) -> Result<(), Error> {
+        with_database!(self, db => db.create_cf(name, opts))
+    }
+
+    fn drop_cf(&mut self, name: &str) -> Result<(), Error> {
+        with_database!(self, db => db.drop_cf(name))
+    }
+
+    fn compact_range_cf_opt(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        opts: &CompactOptions,
+    ) {
+        with_database!(self, db => db.compact_range_cf_opt(cf, start, end, opts))
+    }
+
+    fn flush_cf_opt(&self, cf: &impl AsColumnFamilyRef, opts: &FlushOptions) -> Result<(), Error> {
+        with_database!(self, db => db.flush_cf_opt(cf, opts))
+    }
+
+    fn flush_wal(&self, sync: bool) -> Result<(), Error> {
+        with_database!(self, db => db.flush_wal(sync))
+    }
+
+    fn set_options_cf(&self, cf: &impl AsColumnFamilyRef, opts: &[(&str, &str)]) -> Result<(), Error> {
+        with_database!(self, db => db.set_options_cf(cf, opts))
+    }
+
+    fn latest_sequence_number(&self) -> u64 {
+        with_database!(self, db => db.latest_sequence_number())
+    }
+
+    fn get_updates_since(&self, seq: u64) -> Result<DBWALIterator, Error> {
+        with_database!(self, db => db.get_updates_since(seq))
+    }
+}
+
+impl Iterator for DbIterator<'_> {
+    type Item = Result<(Box<[u8]>, Box<[u8]>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        with_iterator!(self, iter => iter.next())
+    }
+}
+
+impl DbIterator<'_> {
+    fn set_mode(&mut self, mode: IteratorMode) {
+        with_iterator!(self, iter => iter.set_mode(mode))
+    }
+}
+
+impl DbRawIterator<'_> {
+    fn seek(&mut self, key: &[u8]) {
+        with_raw_iterator!(self, iter => iter.seek(key))
+    }
+
+    fn next(&mut self) {
+        with_raw_iterator!(self, iter => iter.next())
+    }
+
+    fn valid(&self) -> bool {
+        with_raw_iterator!(self, iter => iter.valid())
+    }
+
+    fn key(&self) -> Option<&[u8]> {
+        with_raw_iterator!(self, iter => iter.key())
+    }
+
+    fn status(&self) -> Result<(), Error> {
+        with_raw_iterator!(self, iter => iter.status())
+    }
+}
+
+impl DbSnapshot<'_> {
+    /// Makes reads with `read_opts` see this snapshot.
+    fn apply(&self, read_opts: &mut ReadOptions) {
+        match self {
+            DbSnapshot::Plain(snapshot) => read_opts.set_snapshot(snapshot),
+            DbSnapshot::Transactional(snapshot) => read_opts.set_snapshot(snapshot),
+        }
+    }
+}
+
 impl DbRef {
-    fn new(db: DB, path: String) -> Self {
+    fn new(
+        db: Database,
+        path: String,
+        access: Access,
+        cf_defaults: CfSettings,
+        cf_settings: HashMap<String, CfSettings>,
+        block_cache: Option<Cache>,
+        statistics: Option<Options>,
+    ) -> Self {
+        let prefix_lengths = cf_settings
+            .iter()
+            .map(|(name, settings)| (name.clone(), settings.prefix_length(name)))
+            .filter(|(_, len)| *len > 0)
+            .collect();
+
         DbRef {
             db: RwLock::new(Some(db)),
             path,
+            access,
+            prefix_lengths: RwLock::new(prefix_lengths),
+            cf_defaults,
+            cf_settings,
+            block_cache,
+            statistics,
+            counter_lock: Mutex::new(()),
+            sequences: Mutex::new(None),
+        }
+    }
+
+    /// Returns true if the database was opened read-only or as a secondary,
+    /// so every write must be rejected with `:read_only`.
+    fn is_read_only(&self) -> bool {
+        self.access != Access::ReadWrite
+    }
+
+    /// Returns the settings `name` was opened with, or the `open/2` defaults
+    /// for a column family that was not open at the time.
+    fn cf_settings(&self, name: &str) -> &CfSettings {
+        self.cf_settings.get(name).unwrap_or(&self.cf_defaults)
+    }
+
+    /// Locks the prefix extractor lengths for reading.
+    fn prefix_lengths(&self) -> NifResult<RwLockReadGuard<'_, HashMap<String, usize>>> {
+        self.prefix_lengths
+            .read()
+            .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))
+    }
+
+    /// Returns read options for iterating the keys of `cf_name` that start
+    /// with `prefix`, using this database's prefix extractor lengths.
+    fn prefix_read_options(&self, cf_name: &str, prefix: &[u8]) -> NifResult<ReadOptions> {
+        Ok(prefix_read_options(&*self.prefix_lengths()?, cf_name, prefix))
+    }
+}
+
+/// Returns read options for iterating the keys of `cf_name` that start with `prefix`.
+///
+/// A prefix at least as long as the column family's prefix extractor lets
+/// RocksDB consult the prefix bloom filters. Shorter prefixes, including the
+/// empty prefix of a full scan, cross extractor prefixes and need a
+/// total-order seek to see every key. The prefix range is also set as the
+/// iterate bounds, so seeks and reverse steps never leave it.
+fn prefix_read_options(prefix_lengths: &HashMap<String, usize>, cf_name: &str, prefix: &[u8]) -> ReadOptions {
+    let mut read_opts = ReadOptions::default();
+    match prefix_lengths.get(cf_name) {
+        Some(&len) if prefix.len() >= len => read_opts.set_prefix_same_as_start(true),
+        Some(_) => read_opts.set_total_order_seek(true),
+        None => {}
+    }
+    if !prefix.is_empty() {
+        read_opts.set_iterate_lower_bound(prefix);
+    }
+    if let Some(upper) = prefix_successor(prefix) {
+        read_opts.set_iterate_upper_bound(upper);
+    }
+    read_opts
+}
+
+/// Returns the smallest key greater than every key starting with `prefix`,
+/// or `None` if there is no such key (empty or all-`0xff` prefix).
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let end = prefix.iter().rposition(|&byte| byte != 0xff)?;
+    let mut successor = prefix[..=end].to_vec();
+    successor[end] += 1;
+    Some(successor)
+}
+
+/// Repositions an iterator in place, keeping its read options and snapshot.
+///
+/// `Direction::Forward` seeks to the first key >= `target` and iterates
+/// forwards; `Direction::Reverse` seeks to the last key <= `target` and
+/// iterates backwards. Targets outside `prefix` are clamped to the prefix
+/// range: a forward seek below it starts at the prefix, and a reverse seek
+/// above it starts at the last key with the prefix.
+fn seek_iterator(
+    iterator: &mut DbIterator<'static>,
+    prefix: &[u8],
+    target: &[u8],
+    direction: rocksdb::Direction,
+) {
+    let clamped: Vec<u8>;
+    let target = match direction {
+        rocksdb::Direction::Forward if target < prefix => prefix,
+        rocksdb::Direction::Reverse
+            if !prefix.is_empty() && target > prefix && !target.starts_with(prefix) =>
+        {
+            // Stay inside the prefix so a prefix-mode iterator keeps using the
+            // same extractor prefix as its keys
+            clamped = [prefix, &[0xff; SEEK_FOR_PREV_CLAMP_PADDING]].concat();
+            &clamped
         }
+        _ => target,
+    };
+
+    iterator.set_mode(IteratorMode::From(target, direction));
+}
+
+/// Returns read options restricting iteration to `[lower, upper)`.
+///
+/// Range scans routinely cross prefix extractor boundaries, so they always
+/// use a total-order seek.
+fn range_read_options(lower: Option<Binary>, upper: Option<Binary>) -> ReadOptions {
+    let mut read_opts = ReadOptions::default();
+    read_opts.set_total_order_seek(true);
+    if let Some(lower) = lower {
+        read_opts.set_iterate_lower_bound(lower.as_slice());
+    }
+    if let Some(upper) = upper {
+        read_opts.set_iterate_upper_bound(upper.as_slice());
+    }
+    read_opts
+}
+
+/// Converts a direction atom to the iterator start mode for a range scan.
+fn range_iterator_mode(direction: rustler::Atom) -> Option<IteratorMode<'static>> {
+    if direction == atoms::forward() {
+        Some(IteratorMode::Start)
+    } else if direction == atoms::reverse() {
+        Some(IteratorMode::End)
+    } else {
+        None
     }
 }
 
@@ -91,143 +604,1026 @@ mod atoms {
         pos,
         osp,
         derived,
+        counters,
+        // Column family management atoms
+        cf_exists,
+        create_cf_failed,
+        drop_cf_failed,
+        list_cf_failed,
         // Error types
         open_failed,
         close_failed,
+        read_only,
+        not_secondary,
+        catch_up_failed,
         invalid_cf,
         get_failed,
         put_failed,
         delete_failed,
         batch_failed,
         invalid_operation,
+        invalid_triple,
+        invalid_quad,
+        invalid_range,
         // Operation types for batch - these map to Elixir atoms :put and :delete
         put,
         delete,
+        merge,
+        delete_range,
+        // Triple write targets
+        target,
+        indices,
         // Iterator atoms
         iterator_end,
+        invalid_pattern,
+        invalid_variable,
+        bound,
+        var,
+        more,
+        end,
+        invalid_direction,
+        forward,
+        reverse,
         iterator_failed,
         iterator_closed,
+        // Count atoms
+        property_failed,
+        // Statistics atoms
+        statistics_disabled,
+        // Maintenance atoms
+        exclusive,
+        bottommost_level_compaction,
+        skip,
+        if_have_compaction_filter,
+        force,
+        force_optimized,
+        change_level,
+        target_level,
+        flush_failed,
+        set_options_failed,
+        // Event atoms
+        rocksdb_event,
+        flush_completed,
+        compaction_completed,
+        stall_conditions_changed,
+        file_path,
+        job_id,
+        num_entries,
+        num_deletions,
+        smallest_seqno,
+        largest_seqno,
+        triggered_writes_slowdown,
+        triggered_writes_stop,
+        status,
+        base_input_level,
+        output_level,
+        input_files,
+        output_files,
+        input_bytes,
+        output_bytes,
+        input_records,
+        output_records,
+        elapsed_micros,
+        manual,
+        trivial_move,
+        previous,
+        current,
+        normal,
+        delayed,
+        stopped,
+        // Write option atoms
+        sync,
+        disable_wal,
+        no_slowdown,
+        low_pri,
+        write_stall,
+        // Backup atoms
+        checkpoint_failed,
+        backup_failed,
+        restore_failed,
+        flush,
+        max_background_operations,
+        // SST bulk load atoms
+        cf,
+        writer_closed,
+        unsorted_key,
+        sst_write_failed,
+        ingest_failed,
+        move_files,
+        snapshot_consistency,
+        allow_global_seqno,
+        allow_blocking_flush,
+        ingest_behind,
+        // N-Triples bulk load atoms
+        progress,
+        tmp_dir,
+        chunk_size,
+        cache_size,
+        bulk_load_progress,
+        parsing,
+        parse_error,
+        term_too_large,
+        null_byte_in_uri,
+        invalid_utf8,
+        sequence_overflow,
+        bulk_load_failed,
+        // Counter atoms
+        merge_failed,
+        // Dictionary atoms
+        uri,
+        bnode,
+        literal,
+        invalid_type,
+        length_mismatch,
+        // Transaction atoms
+        not_transactional,
+        transaction_closed,
+        conflict,
+        for_update,
+        commit_failed,
+        rollback_failed,
         // Snapshot atoms
         snapshot_released,
+        // Change feed atoms
+        wal_iterator_failed,
+        // Open option atoms
+        invalid_option,
+        column_families,
+        write_buffer_size,
+        max_write_buffer_number,
+        target_file_size_base,
+        level_zero_file_num_compaction_trigger,
+        compaction_style,
+        compression,
+        block_size,
+        disable_auto_compactions,
+        max_open_files,
+        parallelism,
+        max_background_jobs,
+        bytes_per_sync,
+        block_cache_size,
+        transactional,
+        create_if_missing,
+        enable_statistics,
+        event_listener,
+        wal_ttl_seconds,
+        wal_size_limit_mb,
+        // Compaction styles
+        level,
+        universal,
+        fifo,
+        prefix_length,
+        bloom_filter_bits,
+        whole_key_filtering,
+        // Compression types
+        none,
+        snappy,
+        zlib,
+        bz2,
+        lz4,
+        lz4hc,
+        zstd,
     }
 }
 
-/// Converts a column family atom to its string name.
-/// Returns None if the atom is not a valid column family.
-fn cf_atom_to_name(cf_atom: rustler::Atom) -> Option<&'static str> {
-    if cf_atom == atoms::id2str() {
-        Some("id2str")
-    } else if cf_atom == atoms::str2id() {
-        Some("str2id")
-    } else if cf_atom == atoms::spo() {
-        Some("spo")
-    } else if cf_atom == atoms::pos() {
-        Some("pos")
-    } else if cf_atom == atoms::osp() {
-        Some("osp")
-    } else if cf_atom == atoms::derived() {
-        Some("derived")
+/// Converts a column family atom or binary to its string name.
+/// Returns None if the term is neither or names RocksDB's `default` column
+/// family, which holds no data; whether the column family exists is checked
+/// against the open database.
+fn cf_term_to_name(cf: Term) -> Option<String> {
+    let name = if cf.is_atom() {
+        cf.atom_to_string().ok()?
     } else {
-        None
+        let binary: Binary = cf.decode().ok()?;
+        String::from_utf8(binary.as_slice().to_vec()).ok()?
+    };
+    Some(name).filter(|name| name != DEFAULT_COLUMN_FAMILY_NAME)
+}
+
+/// Encodes a column family name as an atom, or as a binary if too long for an atom.
+fn encode_cf_name<'a>(env: Env<'a>, name: &str) -> Term<'a> {
+    match rustler::Atom::from_str(env, name) {
+        Ok(atom) => atom.encode(env),
+        Err(_) => name.encode(env),
     }
 }
 
-/// Placeholder function to verify NIF loads correctly.
-/// Returns the string "rocksdb_nif" to confirm the NIF is operational.
-#[rustler::nif]
-fn nif_loaded() -> &'static str {
-    "rocksdb_nif"
+/// Returns the name of `cf` if it is one of the built-in `CF_NAMES`.
+fn builtin_cf_name(cf: Term) -> Option<&'static str> {
+    let name = cf_term_to_name(cf)?;
+    CF_NAMES.iter().copied().find(|builtin| *builtin == name)
 }
 
-/// Opens a RocksDB database at the given path with column families.
-///
-/// Creates the database and all required column families if they don't exist.
-/// Returns a ResourceArc containing the database handle.
-///
-/// # Arguments
-/// * `path` - Path to the database directory
+/// Returns the name of `cf` if it may be created or dropped at runtime:
+/// not empty, not `default` and not one of the built-in `CF_NAMES`.
+fn runtime_cf_name(cf: Term) -> Option<String> {
+    cf_term_to_name(cf).filter(|name| !name.is_empty() && !CF_NAMES.contains(&name.as_str()))
+}
+
+// ============================================================================
+// Open Options
+// ============================================================================
+
+/// Column family tunables decoded from the `open/2` options.
 ///
-/// # Returns
-/// * `{:ok, db_ref}` on success
-/// * `{:error, reason}` on failure
-#[rustler::nif(schedule = "DirtyCpu")]
-fn open(env: Env, path: String) -> NifResult<Term> {
-    let mut opts = Options::default();
-    opts.create_if_missing(true);
-    opts.create_missing_column_families(true);
+/// Top-level keys set the defaults for every column family; entries under
+/// `:column_families` override them for a single column family.
+#[derive(Clone, Default)]
+struct CfSettings {
+    write_buffer_size: Option<usize>,
+    max_write_buffer_number: Option<i32>,
+    target_file_size_base: Option<u64>,
+    level_zero_file_num_compaction_trigger: Option<i32>,
+    compaction_style: Option<DBCompactionStyle>,
+    compression: Option<DBCompressionType>,
+    block_size: Option<usize>,
+    disable_auto_compactions: Option<bool>,
+    prefix_length: Option<usize>,
+    bloom_filter_bits: Option<f64>,
+    whole_key_filtering: Option<bool>,
+}
 
-    // Create column family descriptors
-    let cf_descriptors: Vec<ColumnFamilyDescriptor> = CF_NAMES
-        .iter()
-        .map(|name| {
-            let cf_opts = Options::default();
-            ColumnFamilyDescriptor::new(*name, cf_opts)
-        })
-        .collect();
+/// Database-wide settings decoded from the `open/2` options.
+#[derive(Default)]
+struct DbSettings {
+    max_open_files: Option<i32>,
+    parallelism: Option<i32>,
+    max_background_jobs: Option<i32>,
+    bytes_per_sync: Option<u64>,
+    block_cache_size: Option<usize>,
+    transactional: bool,
+    create_if_missing: Option<bool>,
+    enable_statistics: bool,
+    event_listener: Option<rustler::LocalPid>,
+    wal_ttl_seconds: Option<u64>,
+    wal_size_limit_mb: Option<u64>,
+}
 
-    match DB::open_cf_descriptors(&opts, &path, cf_descriptors) {
-        Ok(db) => {
-            let db_ref = ResourceArc::new(DbRef::new(db, path));
-            Ok((atoms::ok(), db_ref).encode(env))
+/// Fully decoded `open/2` options.
+struct OpenConfig {
+    db: DbSettings,
+    cf_defaults: CfSettings,
+    cf_overrides: Vec<(String, CfSettings)>,
+}
+
+impl CfSettings {
+    /// Applies a single column family option.
+    /// Returns `Ok(false)` if the key is not a column family option.
+    fn set(&mut self, key: rustler::Atom, value: Term) -> NifResult<bool> {
+        if key == atoms::write_buffer_size() {
+            self.write_buffer_size = Some(value.decode()?);
+        } else if key == atoms::max_write_buffer_number() {
+            self.max_write_buffer_number = Some(value.decode()?);
+        } else if key == atoms::target_file_size_base() {
+            self.target_file_size_base = Some(value.decode()?);
+        } else if key == atoms::level_zero_file_num_compaction_trigger() {
+            self.level_zero_file_num_compaction_trigger = Some(value.decode()?);
+        } else if key == atoms::compaction_style() {
+            self.compaction_style = Some(decode_compaction_style(value)?);
+        } else if key == atoms::compression() {
+            self.compression = Some(decode_compression(value)?);
+        } else if key == atoms::block_size() {
+            self.block_size = Some(value.decode()?);
+        } else if key == atoms::disable_auto_compactions() {
+            self.disable_auto_compactions = Some(value.decode()?);
+        } else if key == atoms::prefix_length() {
+            self.prefix_length = Some(value.decode()?);
+        } else if key == atoms::bloom_filter_bits() {
+            self.bloom_filter_bits = Some(decode_number(value)?);
+        } else if key == atoms::whole_key_filtering() {
+            self.whole_key_filtering = Some(value.decode()?);
+        } else {
+            return Ok(false);
         }
-        Err(e) => Ok((atoms::error(), (atoms::open_failed(), e.to_string())).encode(env)),
+        Ok(true)
     }
-}
 
-/// Closes the database and releases all resources.
-///
-/// After calling close, the database handle is no longer valid.
-/// Subsequent operations will return `{:error, :already_closed}`.
-///
-/// # Arguments
-/// * `db_ref` - The database reference to close
-///
-/// # Returns
-/// * `:ok` on success
-/// * `{:error, :already_closed}` if already closed
-#[rustler::nif(schedule = "DirtyCpu")]
-fn close(env: Env, db_ref: ResourceArc<DbRef>) -> NifResult<Term> {
-    let mut db_guard = db_ref
-        .db
-        .write()
-        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+    /// Returns the prefix extractor length for `cf_name` (0 for none).
+    ///
+    /// The index column families default to `INDEX_PREFIX_LENGTH` since they
+    /// are almost always scanned by one or two leading term IDs.
+    fn prefix_length(&self, cf_name: &str) -> usize {
+        self.prefix_length.unwrap_or(match cf_name {
+            "spo" | "pos" | "osp" | "gspo" | "gpos" | "gosp" | "spog" => INDEX_PREFIX_LENGTH,
+            _ => 0,
+        })
+    }
 
-    if db_guard.is_none() {
-        return Ok((atoms::error(), atoms::already_closed()).encode(env));
+    /// Returns the bloom filter bits per key for `cf_name` (0 for no filter).
+    fn bloom_filter_bits(&self, cf_name: &str) -> f64 {
+        self.bloom_filter_bits.unwrap_or(match cf_name {
+            "spo" | "pos" | "osp" | "gspo" | "gpos" | "gosp" | "spog" | "str2id" | "id2str" => DEFAULT_BLOOM_FILTER_BITS,
+            _ => 0.0,
+        })
     }
 
-    // Drop the database to close it
-    *db_guard = None;
-    Ok(atoms::ok().encode(env))
-}
+    /// Writes these settings into the `Options` of column family `cf_name`.
+    fn apply(&self, cf_name: &str, opts: &mut Options, block_cache: Option<&Cache>) {
+        if let Some(size) = self.write_buffer_size {
+            opts.set_write_buffer_size(size);
+        }
+        if let Some(count) = self.max_write_buffer_number {
+            opts.set_max_write_buffer_number(count);
+        }
+        if let Some(size) = self.target_file_size_base {
+            opts.set_target_file_size_base(size);
+        }
+        if let Some(trigger) = self.level_zero_file_num_compaction_trigger {
+            opts.set_level_zero_file_num_compaction_trigger(trigger);
+        }
+        if let Some(style) = self.compaction_style {
+            opts.set_compaction_style(style);
+        }
+        if let Some(compression) = self.compression {
+            opts.set_compression_type(compression);
+        }
+        if let Some(disable) = self.disable_auto_compactions {
+            opts.set_disable_auto_compactions(disable);
+        }
 
-/// Returns the path of the database.
-///
-/// # Arguments
-/// * `db_ref` - The database reference
-///
-/// # Returns
-/// * `{:ok, path}` with the database path
-#[rustler::nif]
-fn get_path(env: Env, db_ref: ResourceArc<DbRef>) -> NifResult<Term> {
-    Ok((atoms::ok(), db_ref.path.clone()).encode(env))
-}
+        let prefix_length = self.prefix_length(cf_name);
+        if prefix_length > 0 {
+            opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(prefix_length));
+        }
 
-/// Lists all column families in the database.
-///
-/// # Returns
-/// * List of column family name atoms
-#[rustler::nif]
-fn list_column_families(env: Env) -> NifResult<Term> {
-    let cf_atoms: Vec<Term> = vec![
-        atoms::id2str().encode(env),
-        atoms::str2id().encode(env),
-        atoms::spo().encode(env),
-        atoms::pos().encode(env),
-        atoms::osp().encode(env),
-        atoms::derived().encode(env),
-    ];
-    Ok(cf_atoms.encode(env))
+        let mut table_opts = BlockBasedOptions::default();
+        if let Some(size) = self.block_size {
+            table_opts.set_block_size(size);
+        }
+        if let Some(cache) = block_cache {
+            table_opts.set_block_cache(cache);
+        }
+
+        // With a prefix extractor the filter holds key prefixes, which also
+        // serve point lookups; whole keys are only added when asked for.
+        let bloom_bits = self.bloom_filter_bits(cf_name);
+        if bloom_bits > 0.0 {
+            table_opts.set_bloom_filter(bloom_bits, false);
+        }
+        table_opts.set_whole_key_filtering(self.whole_key_filtering.unwrap_or(prefix_length == 0));
+
+        opts.set_block_based_table_factory(&table_opts);
+
+        if cf_name == COUNTERS_CF {
+            opts.set_merge_operator_associative("u64_add", u64_add_merge);
+        }
+    }
+}
+
+impl DbSettings {
+    /// Applies a single database-wide option.
+    /// Returns `Ok(false)` if the key is not a database-wide option.
+    fn set(&mut self, key: rustler::Atom, value: Term) -> NifResult<bool> {
+        if key == atoms::max_open_files() {
+            self.max_open_files = Some(value.decode()?);
+        } else if key == atoms::parallelism() {
+            self.parallelism = Some(value.decode()?);
+        } else if key == atoms::max_background_jobs() {
+            self.max_background_jobs = Some(value.decode()?);
+        } else if key == atoms::bytes_per_sync() {
+            self.bytes_per_sync = Some(value.decode()?);
+        } else if key == atoms::block_cache_size() {
+            self.block_cache_size = Some(value.decode()?);
+        } else if key == atoms::transactional() {
+            self.transactional = value.decode()?;
+        } else if key == atoms::create_if_missing() {
+            self.create_if_missing = Some(value.decode()?);
+        } else if key == atoms::enable_statistics() {
+            self.enable_statistics = value.decode()?;
+        } else if key == atoms::event_listener() {
+            self.event_listener = Some(value.decode()?);
+        } else if key == atoms::wal_ttl_seconds() {
+            self.wal_ttl_seconds = Some(value.decode()?);
+        } else if key == atoms::wal_size_limit_mb() {
+            self.wal_size_limit_mb = Some(value.decode()?);
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Writes these settings into the database `Options`.
+    fn apply(&self, opts: &mut Options) {
+        if let Some(count) = self.max_open_files {
+            opts.set_max_open_files(count);
+        }
+        if let Some(threads) = self.parallelism {
+            opts.increase_parallelism(threads);
+        }
+        if let Some(jobs) = self.max_background_jobs {
+            opts.set_max_background_jobs(jobs);
+        }
+        if let Some(bytes) = self.bytes_per_sync {
+            opts.set_bytes_per_sync(bytes);
+        }
+        if self.enable_statistics {
+            opts.enable_statistics();
+        }
+        if let Some(secs) = self.wal_ttl_seconds {
+            opts.set_wal_ttl_seconds(secs);
+        }
+        if let Some(size) = self.wal_size_limit_mb {
+            opts.set_wal_size_limit_mb(size);
+        }
+    }
+}
+
+impl OpenConfig {
+    /// Decodes the `open/2` options term.
+    ///
+    /// On failure returns the error reason term `{:invalid_option, key}` for
+    /// an unknown key or a value of the wrong type. Whether the column
+    /// families named under `:column_families` exist is checked by `open`.
+    fn decode<'a>(env: Env<'a>, options: Term<'a>) -> Result<OpenConfig, Term<'a>> {
+        let invalid = |key: Term<'a>| (atoms::invalid_option(), key).encode(env);
+
+        let mut db = DbSettings::default();
+        let mut cf_defaults = CfSettings::default();
+        let mut cf_terms: Vec<(String, Term<'a>)> = Vec::new();
+
+        for (key, value) in option_pairs(options).map_err(invalid)? {
+            if key == atoms::column_families() {
+                for (cf_atom, cf_opts) in option_pairs(value).map_err(invalid)? {
+                    match cf_term_to_name(cf_atom.to_term(env)) {
+                        Some(name) => cf_terms.push((name, cf_opts)),
+                        None => return Err((atoms::invalid_cf(), cf_atom).encode(env)),
+                    }
+                }
+                continue;
+            }
+
+            let handled = db
+                .set(key, value)
+                .and_then(|handled| if handled { Ok(true) } else { cf_defaults.set(key, value) });
+
+            match handled {
+                Ok(true) => {}
+                _ => return Err(invalid(key.encode(env))),
+            }
+        }
+
+        // Per-CF overrides are applied on top of the completed defaults so that
+        // the order of keys in the options list does not matter.
+        let mut cf_overrides = Vec::with_capacity(cf_terms.len());
+        for (name, cf_opts) in cf_terms {
+            let mut settings = cf_defaults.clone();
+            for (key, value) in option_pairs(cf_opts).map_err(invalid)? {
+                match settings.set(key, value) {
+                    Ok(true) => {}
+                    _ => return Err(invalid(key.encode(env))),
+                }
+            }
+            cf_overrides.push((name, settings));
+        }
+
+        Ok(OpenConfig {
+            db,
+            cf_defaults,
+            cf_overrides,
+        })
+    }
+
+    /// Returns the settings for a column family, including any override.
+    fn cf_settings(&self, name: &str) -> &CfSettings {
+        self.cf_overrides
+            .iter()
+            .rev()
+            .find(|(cf_name, _)| cf_name == name)
+            .map(|(_, settings)| settings)
+            .unwrap_or(&self.cf_defaults)
+    }
+}
+
+/// Decodes a keyword list or map of options into `(key, value)` pairs.
+/// On failure returns the offending term.
+fn option_pairs(options: Term) -> Result<Vec<(rustler::Atom, Term)>, Term> {
+    let mut pairs = Vec::new();
+
+    if options.is_map() {
+        let iter: rustler::types::map::MapIterator = options.decode().map_err(|_| options)?;
+        for (key, value) in iter {
+            let key_atom: rustler::Atom = key.decode().map_err(|_| key)?;
+            pairs.push((key_atom, value));
+        }
+        return Ok(pairs);
+    }
+
+    let iter: ListIterator = options.decode().map_err(|_| options)?;
+    for item in iter {
+        let (key, value): (Term, Term) = item.decode().map_err(|_| item)?;
+        let key_atom: rustler::Atom = key.decode().map_err(|_| key)?;
+        pairs.push((key_atom, value));
+    }
+    Ok(pairs)
+}
+
+/// Decodes an integer or float option value as a float.
+fn decode_number(value: Term) -> NifResult<f64> {
+    match value.decode::<i64>() {
+        Ok(number) => Ok(number as f64),
+        Err(_) => value.decode::<f64>(),
+    }
+}
+
+fn decode_compaction_style(value: Term) -> NifResult<DBCompactionStyle> {
+    let style: rustler::Atom = value.decode()?;
+    if style == atoms::level() {
+        Ok(DBCompactionStyle::Level)
+    } else if style == atoms::universal() {
+        Ok(DBCompactionStyle::Universal)
+    } else if style == atoms::fifo() {
+        Ok(DBCompactionStyle::Fifo)
+    } else {
+        Err(rustler::Error::BadArg)
+    }
+}
+
+fn decode_compression(value: Term) -> NifResult<DBCompressionType> {
+    let compression: rustler::Atom = value.decode()?;
+    if compression == atoms::none() {
+        Ok(DBCompressionType::None)
+    } else if compression == atoms::snappy() {
+        Ok(DBCompressionType::Snappy)
+    } else if compression == atoms::zlib() {
+        Ok(DBCompressionType::Zlib)
+    } else if compression == atoms::bz2() {
+        Ok(DBCompressionType::Bz2)
+    } else if compression == atoms::lz4() {
+        Ok(DBCompressionType::Lz4)
+    } else if compression == atoms::lz4hc() {
+        Ok(DBCompressionType::Lz4hc)
+    } else if compression == atoms::zstd() {
+        Ok(DBCompressionType::Zstd)
+    } else {
+        Err(rustler::Error::BadArg)
+    }
+}
+
+/// Applies a single write option.
+/// Returns `Ok(false)` if the key is not a write option.
+fn set_write_option(write_opts: &mut WriteOptions, key: rustler::Atom, value: Term) -> NifResult<bool> {
+    if key == atoms::sync() {
+        write_opts.set_sync(value.decode()?);
+    } else if key == atoms::disable_wal() {
+        write_opts.disable_wal(value.decode()?);
+    } else if key == atoms::no_slowdown() {
+        write_opts.set_no_slowdown(value.decode()?);
+    } else if key == atoms::low_pri() {
+        write_opts.set_low_pri(value.decode()?);
+    } else {
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Decodes the options of a write NIF into `WriteOptions`.
+///
+/// On failure returns `{:invalid_option, key}` for an unknown key or a value
+/// of the wrong type.
+fn decode_write_options<'a>(env: Env<'a>, options: Term<'a>) -> Result<WriteOptions, Term<'a>> {
+    let pairs = option_pairs(options).map_err(|term| (atoms::invalid_option(), term).encode(env))?;
+    let mut write_opts = WriteOptions::default();
+
+    for (key, value) in pairs {
+        if !matches!(set_write_option(&mut write_opts, key, value), Ok(true)) {
+            return Err((atoms::invalid_option(), key).encode(env));
+        }
+    }
+
+    Ok(write_opts)
+}
+
+/// Maps a failed write to its error term.
+///
+/// A write rejected by `no_slowdown` because RocksDB would have stalled it
+/// comes back as `Incomplete` and is reported as `:write_stall`.
+fn write_error<'a>(env: Env<'a>, failure: rustler::Atom, error: Error) -> Term<'a> {
+    match error.kind() {
+        ErrorKind::Incomplete => (atoms::error(), atoms::write_stall()).encode(env),
+        _ => (atoms::error(), (failure, error.to_string())).encode(env),
+    }
+}
+
+/// Placeholder function to verify NIF loads correctly.
+/// Returns the string "rocksdb_nif" to confirm the NIF is operational.
+#[rustler::nif]
+fn nif_loaded() -> &'static str {
+    "rocksdb_nif"
+}
+
+/// Opens the database at `path` with the given access and `open/2` options.
+///
+/// Read-write handles create the database and all required column families
+/// if they don't exist. Read-only and secondary handles cannot create
+/// anything, so they open exactly the column families found on disk.
+fn open_database<'a>(env: Env<'a>, path: String, access: Access, options: Term<'a>) -> NifResult<Term<'a>> {
+    let config = match OpenConfig::decode(env, options) {
+        Ok(config) => config,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    if config.db.transactional && access != Access::ReadWrite {
+        return Ok((atoms::error(), (atoms::invalid_option(), atoms::transactional())).encode(env));
+    }
+
+    let mut opts = Options::default();
+    opts.create_if_missing(config.db.create_if_missing.unwrap_or(true));
+    opts.create_missing_column_families(true);
+    if let Access::Secondary(_) = access {
+        // RocksDB requires secondaries to keep every table file open
+        opts.set_max_open_files(-1);
+    }
+    config.db.apply(&mut opts);
+    if let Some(listener) = config.db.event_listener {
+        if let Err(e) = add_event_listener(&mut opts, listener) {
+            return Ok((atoms::error(), (atoms::open_failed(), e.to_string())).encode(env));
+        }
+    }
+
+    // Column families created at runtime must be opened along with the
+    // built-in ones. A database that does not exist yet has none.
+    let mut cf_names: Vec<String> = match access {
+        Access::ReadWrite => CF_NAMES.iter().map(|name| name.to_string()).collect(),
+        Access::ReadOnly | Access::Secondary(_) => Vec::new(),
+    };
+    for name in DB::list_cf(&opts, &path).unwrap_or_default() {
+        if name != DEFAULT_COLUMN_FAMILY_NAME && !cf_names.contains(&name) {
+            cf_names.push(name);
+        }
+    }
+
+    if let Some((name, _)) = config.cf_overrides.iter().find(|(name, _)| !cf_names.contains(name)) {
+        let cf = rustler::Atom::from_str(env, name)?;
+        return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env));
+    }
+
+    // A single block cache is shared by all column families so that
+    // `block_cache_size` is a budget for the whole database.
+    let block_cache = config.db.block_cache_size.map(Cache::new_lru_cache);
+
+    // Create column family descriptors
+    let cf_descriptors: Vec<ColumnFamilyDescriptor> = cf_names
+        .iter()
+        .map(|name| {
+            let mut cf_opts = Options::default();
+            config.cf_settings(name).apply(name, &mut cf_opts, block_cache.as_ref());
+            ColumnFamilyDescriptor::new(name, cf_opts)
+        })
+        .collect();
+
+    let opened = match &access {
+        Access::ReadWrite if config.db.transactional => {
+            OptimisticTransactionDB::open_cf_descriptors(&opts, &path, cf_descriptors).map(Database::Transactional)
+        }
+        Access::ReadWrite => DB::open_cf_descriptors(&opts, &path, cf_descriptors).map(Database::Plain),
+        Access::ReadOnly => DB::open_cf_descriptors_read_only(&opts, &path, cf_descriptors, false).map(Database::Plain),
+        Access::Secondary(secondary_path) => {
+            DB::open_cf_descriptors_as_secondary(&opts, &path, secondary_path, cf_descriptors).map(Database::Plain)
+        }
+    };
+
+    // The copy shares the statistics object the database reports to
+    let statistics = config.db.enable_statistics.then(|| opts.clone());
+
+    let db = match opened {
+        Ok(db) => db,
+        Err(e) => return Ok((atoms::error(), (atoms::open_failed(), e.to_string())).encode(env)),
+    };
+
+    let cf_settings: HashMap<String, CfSettings> = cf_names
+        .iter()
+        .map(|name| (name.clone(), config.cf_settings(name).clone()))
+        .collect();
+
+    let db_ref = ResourceArc::new(DbRef::new(
+        db,
+        path,
+        access,
+        config.cf_defaults,
+        cf_settings,
+        block_cache,
+        statistics,
+    ));
+    Ok((atoms::ok(), db_ref).encode(env))
+}
+
+/// Opens a RocksDB database at the given path with column families.
+///
+/// Creates the database and all required column families if they don't exist,
+/// and reopens any column families created with `create_column_family`.
+/// Returns a ResourceArc containing the database handle.
+///
+/// # Arguments
+/// * `path` - Path to the database directory
+/// * `options` - Keyword list or map of tuning options. Top-level keys apply
+///   to the database and act as defaults for every column family;
+///   `column_families: [spo: [...]]` overrides them per column family.
+///   `create_if_missing: false` makes opening a missing database fail.
+///   `event_listener: pid` sends `{:rocksdb_event, kind, info}` messages
+///   for finished flushes and compactions and changed write stall
+///   conditions to `pid` (see `add_event_listener`).
+///
+/// # Returns
+/// * `{:ok, db_ref}` on success
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, {:invalid_cf, cf}}` if a column family override names a column family that does not exist
+/// * `{:error, {:open_failed, reason}}` on failure
+#[rustler::nif(schedule = "DirtyCpu")]
+fn open<'a>(env: Env<'a>, path: String, options: Term<'a>) -> NifResult<Term<'a>> {
+    open_database(env, path, Access::ReadWrite, options)
+}
+
+/// Opens an existing database read-only.
+///
+/// Any number of read-only handles can be open alongside a read-write one.
+/// A read-only handle sees the data as of the time it was opened, and every
+/// write NIF returns `{:error, :read_only}` on it.
+///
+/// # Arguments
+/// * `path` - Path to the database directory
+/// * `options` - Keyword list or map of tuning options, as for `open/2`;
+///   `transactional` is not allowed
+///
+/// # Returns
+/// * `{:ok, db_ref}` on success
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, {:invalid_cf, cf}}` if a column family override names a column family that does not exist
+/// * `{:error, {:open_failed, reason}}` if the database does not exist or cannot be opened
+#[rustler::nif(schedule = "DirtyIo")]
+fn open_read_only<'a>(env: Env<'a>, path: String, options: Term<'a>) -> NifResult<Term<'a>> {
+    open_database(env, path, Access::ReadOnly, options)
+}
+
+/// Opens an existing database as a secondary instance of its primary.
+///
+/// A secondary follows a primary opened read-write elsewhere: it sees the
+/// primary's data as of the time it was opened and picks up later writes with
+/// `try_catch_up_with_primary`. Every write NIF returns `{:error, :read_only}`
+/// on it.
+///
+/// # Arguments
+/// * `primary_path` - Path to the primary's database directory
+/// * `secondary_path` - Directory for the secondary's own info logs
+/// * `options` - Keyword list or map of tuning options, as for `open/2`;
+///   `transactional` is not allowed
+///
+/// # Returns
+/// * `{:ok, db_ref}` on success
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, {:invalid_cf, cf}}` if a column family override names a column family that does not exist
+/// * `{:error, {:open_failed, reason}}` if the database does not exist or cannot be opened
+#[rustler::nif(schedule = "DirtyIo")]
+fn open_as_secondary<'a>(
+    env: Env<'a>,
+    primary_path: String,
+    secondary_path: String,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    open_database(env, primary_path, Access::Secondary(secondary_path), options)
+}
+
+/// Replays the primary's new writes into a secondary instance.
+///
+/// Column families created by the primary after the secondary was opened
+/// stay invisible until the secondary is reopened.
+///
+/// # Arguments
+/// * `db_ref` - The database reference, opened with `open_as_secondary`
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :not_secondary}` if the database was not opened as a secondary
+/// * `{:error, {:catch_up_failed, reason}}` on failure
+#[rustler::nif(schedule = "DirtyIo")]
+fn try_catch_up_with_primary(env: Env, db_ref: ResourceArc<DbRef>) -> NifResult<Term> {
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(Database::Plain(db)) if matches!(db_ref.access, Access::Secondary(_)) => db,
+        Some(_) => return Ok((atoms::error(), atoms::not_secondary()).encode(env)),
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    match db.try_catch_up_with_primary() {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::catch_up_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Closes the database and releases all resources.
+///
+/// After calling close, the database handle is no longer valid.
+/// Subsequent operations will return `{:error, :already_closed}`.
+/// Waits for running calls on the handle, such as a manual compaction,
+/// to finish first.
+///
+/// # Arguments
+/// * `db_ref` - The database reference to close
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if already closed
+#[rustler::nif(schedule = "DirtyCpu")]
+fn close(env: Env, db_ref: ResourceArc<DbRef>) -> NifResult<Term> {
+    let mut db_guard = db_ref
+        .db
+        .write()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    if db_guard.is_none() {
+        return Ok((atoms::error(), atoms::already_closed()).encode(env));
+    }
+
+    // Drop the database to close it
+    *db_guard = None;
+    Ok(atoms::ok().encode(env))
+}
+
+/// Returns the path of the database.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+///
+/// # Returns
+/// * `{:ok, path}` with the database path
+#[rustler::nif]
+fn get_path(env: Env, db_ref: ResourceArc<DbRef>) -> NifResult<Term> {
+    Ok((atoms::ok(), db_ref.path.clone()).encode(env))
+}
+
+/// Lists the column families of the database as recorded on disk.
+///
+/// Includes column families created with `create_column_family`; RocksDB's
+/// own `default` column family is not used by TripleStore and is left out.
+/// Names are returned as atoms, or as binaries if too long for an atom.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+///
+/// # Returns
+/// * `{:ok, names}` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:list_cf_failed, reason}}` on failure
+#[rustler::nif(schedule = "DirtyIo")]
+fn list_column_families(env: Env, db_ref: ResourceArc<DbRef>) -> NifResult<Term> {
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    if db_guard.is_none() {
+        return Ok((atoms::error(), atoms::already_closed()).encode(env));
+    }
+
+    match DB::list_cf(&Options::default(), &db_ref.path) {
+        Ok(names) => {
+            let cfs: Vec<Term> = names
+                .iter()
+                .filter(|name| *name != DEFAULT_COLUMN_FAMILY_NAME)
+                .map(|name| encode_cf_name(env, name))
+                .collect();
+            Ok((atoms::ok(), cfs).encode(env))
+        }
+        Err(e) => Ok((atoms::error(), (atoms::list_cf_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Creates a column family at runtime.
+///
+/// The column family starts from the column family options given to `open`
+/// and `options` overrides them. Options are not persisted: a reopened
+/// database applies the `open` options, including any `:column_families`
+/// entry for this name.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `options` - Keyword list or map of column family options
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if the name is empty, `default` or a built-in column family
+/// * `{:error, {:cf_exists, cf}}` if the column family already exists
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, {:create_cf_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_column_family<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let name = match runtime_cf_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let pairs = match option_pairs(options) {
+        Ok(pairs) => pairs,
+        Err(term) => return Ok((atoms::error(), (atoms::invalid_option(), term)).encode(env)),
+    };
+
+    let mut settings = db_ref.cf_defaults.clone();
+    for (key, value) in pairs {
+        if !matches!(settings.set(key, value), Ok(true)) {
+            return Ok((atoms::error(), (atoms::invalid_option(), key)).encode(env));
+        }
+    }
+
+    let mut db_guard = db_ref
+        .db
+        .write()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_mut() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    if db.cf_handle(&name).is_some() {
+        return Ok((atoms::error(), (atoms::cf_exists(), cf)).encode(env));
+    }
+
+    let mut cf_opts = Options::default();
+    settings.apply(&name, &mut cf_opts, db_ref.block_cache.as_ref());
+
+    match db.create_cf(&name, &cf_opts) {
+        Ok(()) => {
+            let prefix_length = settings.prefix_length(&name);
+            if prefix_length > 0 {
+                let mut prefix_lengths = db_ref
+                    .prefix_lengths
+                    .write()
+                    .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+                prefix_lengths.insert(name, prefix_length);
+            }
+            Ok(atoms::ok().encode(env))
+        }
+        Err(e) => Ok((atoms::error(), (atoms::create_cf_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Drops a column family created at runtime and all of its data.
+///
+/// The built-in column families in `CF_NAMES` cannot be dropped. Iterators
+/// already open on the column family keep reading the data they saw.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if the column family does not exist or is built in
+/// * `{:error, {:drop_cf_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyIo")]
+fn drop_column_family<'a>(env: Env<'a>, db_ref: ResourceArc<DbRef>, cf: Term<'a>) -> NifResult<Term<'a>> {
+    let name = match runtime_cf_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let mut db_guard = db_ref
+        .db
+        .write()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_mut() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    if db.cf_handle(&name).is_none() {
+        return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env));
+    }
+
+    match db.drop_cf(&name) {
+        Ok(()) => {
+            let mut prefix_lengths = db_ref
+                .prefix_lengths
+                .write()
+                .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+            prefix_lengths.remove(&name);
+            Ok(atoms::ok().encode(env))
+        }
+        Err(e) => Ok((atoms::error(), (atoms::drop_cf_failed(), e.to_string())).encode(env)),
+    }
 }
 
 /// Checks if the database is open.
@@ -235,40 +1631,5163 @@ fn list_column_families(env: Env) -> NifResult<Term> {
 /// # Arguments
 /// * `db_ref` - The database reference
 ///
-/// # Returns
-/// * `true` if open, `false` if closed
-#[rustler::nif]
-fn is_open(db_ref: ResourceArc<DbRef>) -> NifResult<bool> {
-    let db_guard = db_ref
-        .db
-        .read()
-        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
-    Ok(db_guard.is_some())
+/// # Returns
+/// * `true` if open, `false` if closed
+#[rustler::nif]
+fn is_open(db_ref: ResourceArc<DbRef>) -> NifResult<bool> {
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+    Ok(db_guard.is_some())
+}
+
+/// Gets a value from a column family.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary, e.g. `:spo`
+/// * `key` - The key as a binary
+///
+/// # Returns
+/// * `{:ok, value}` if found
+/// * `:not_found` if key doesn't exist
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:get_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn get<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    key: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    match db.get_cf(&cf_handle, key.as_slice()) {
+        Ok(Some(value)) => {
+            let mut binary = NewBinary::new(env, value.len());
+            binary.as_mut_slice().copy_from_slice(&value);
+            Ok((atoms::ok(), Binary::from(binary)).encode(env))
+        }
+        Ok(None) => Ok(atoms::not_found().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::get_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Puts a key-value pair into a column family.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `key` - The key as a binary
+/// * `value` - The value as a binary
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:put_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn put<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    key: Binary<'a>,
+    value: Binary<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    match db.put_cf_opt(&cf_handle, key.as_slice(), value.as_slice(), &write_opts) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok(write_error(env, atoms::put_failed(), e)),
+    }
+}
+
+/// Deletes a key from a column family.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `key` - The key to delete
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
+///
+/// # Returns
+/// * `:ok` on success (even if key didn't exist)
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:delete_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn delete<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    key: Binary<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    match db.delete_cf_opt(&cf_handle, key.as_slice(), &write_opts) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok(write_error(env, atoms::delete_failed(), e)),
+    }
+}
+
+/// Checks if a key exists in a column family.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `key` - The key to check
+///
+/// # Returns
+/// * `{:ok, true}` if key exists
+/// * `{:ok, false}` if key doesn't exist
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:get_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn exists<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    key: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    // Check if key exists by attempting to get it
+    match db.get_cf(&cf_handle, key.as_slice()) {
+        Ok(Some(_)) => Ok((atoms::ok(), true).encode(env)),
+        Ok(None) => Ok((atoms::ok(), false).encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::get_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Gets the values for multiple keys from a column family in one batched lookup.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `keys` - List of keys as binaries
+///
+/// # Returns
+/// * `{:ok, results}` with one `{:ok, value}` or `:not_found` per key, in input order
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:get_failed, reason}}` if any lookup fails
+#[rustler::nif(schedule = "DirtyCpu")]
+fn multi_get<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    keys: Vec<Binary<'a>>,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let results = db.batched_multi_get_cf_opt(
+        &cf_handle,
+        keys.iter().map(|key| key.as_slice()),
+        false,
+        &ReadOptions::default(),
+    );
+
+    Ok(encode_multi_get_results(env, results))
+}
+
+/// Encodes batched lookup results as `{:ok, [{:ok, value} | :not_found]}`,
+/// or `{:error, {:get_failed, reason}}` for the first failed lookup.
+fn encode_multi_get_results<'a>(
+    env: Env<'a>,
+    results: Vec<Result<Option<rocksdb::DBPinnableSlice>, rocksdb::Error>>,
+) -> Term<'a> {
+    let mut terms: Vec<Term<'a>> = Vec::with_capacity(results.len());
+
+    for result in results {
+        match result {
+            Ok(Some(value)) => {
+                let mut binary = NewBinary::new(env, value.len());
+                binary.as_mut_slice().copy_from_slice(&value);
+                terms.push((atoms::ok(), Binary::from(binary)).encode(env));
+            }
+            Ok(None) => terms.push(atoms::not_found().encode(env)),
+            Err(e) => return (atoms::error(), (atoms::get_failed(), e.to_string())).encode(env),
+        }
+    }
+
+    (atoms::ok(), terms).encode(env)
+}
+
+/// Atomically writes multiple key-value pairs to column families.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `operations` - List of `{cf, key, value}` tuples
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:batch_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn write_batch<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    operations: Term<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let mut batch = WriteBatch::default();
+
+    // Parse the list of operations
+    let iter: ListIterator = operations
+        .decode()
+        .map_err(|_| rustler::Error::Term(Box::new("expected list")))?;
+
+    for item in iter {
+        // Each item should be a tuple {cf, key, value}
+        let tuple = rustler::types::tuple::get_tuple(item)
+            .map_err(|_| rustler::Error::Term(Box::new("expected tuple")))?;
+
+        if tuple.len() == 3 {
+            // Simple format: {cf, key, value} - treat as put
+            let cf_term = tuple[0];
+            let key: Binary = tuple[1]
+                .decode()
+                .map_err(|_| rustler::Error::Term(Box::new("expected binary for key")))?;
+            let value: Binary = tuple[2]
+                .decode()
+                .map_err(|_| rustler::Error::Term(Box::new("expected binary for value")))?;
+
+            let cf_name = match cf_term_to_name(cf_term) {
+                Some(name) => name,
+                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_term)).encode(env)),
+            };
+
+            let cf_handle = match db.cf_handle(&cf_name) {
+                Some(cf) => cf,
+                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_term)).encode(env)),
+            };
+
+            batch.put_cf(&cf_handle, key.as_slice(), value.as_slice());
+        } else if tuple.len() == 4 {
+            // Extended format: {:put, cf, key, value}
+            let op_atom: rustler::Atom = tuple[0]
+                .decode()
+                .map_err(|_| rustler::Error::Term(Box::new("expected atom for operation")))?;
+            let cf_term = tuple[1];
+            let key: Binary = tuple[2]
+                .decode()
+                .map_err(|_| rustler::Error::Term(Box::new("expected binary for key")))?;
+            let value: Binary = tuple[3]
+                .decode()
+                .map_err(|_| rustler::Error::Term(Box::new("expected binary for value")))?;
+
+            if op_atom != atoms::put() {
+                return Ok((atoms::error(), (atoms::invalid_operation(), op_atom)).encode(env));
+            }
+
+            let cf_name = match cf_term_to_name(cf_term) {
+                Some(name) => name,
+                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_term)).encode(env)),
+            };
+
+            let cf_handle = match db.cf_handle(&cf_name) {
+                Some(cf) => cf,
+                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_term)).encode(env)),
+            };
+
+            batch.put_cf(&cf_handle, key.as_slice(), value.as_slice());
+        } else {
+            return Ok((atoms::error(), atoms::invalid_operation()).encode(env));
+        }
+    }
+
+    match db.write_opt(batch, &write_opts) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok(write_error(env, atoms::batch_failed(), e)),
+    }
+}
+
+/// Atomically deletes multiple keys from column families.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `operations` - List of `{cf, key}` tuples
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:batch_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn delete_batch<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    operations: Term<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let mut batch = WriteBatch::default();
+
+    // Parse the list of operations
+    let iter: ListIterator = operations
+        .decode()
+        .map_err(|_| rustler::Error::Term(Box::new("expected list")))?;
+
+    for item in iter {
+        // Each item should be a tuple {cf, key}
+        let tuple = rustler::types::tuple::get_tuple(item)
+            .map_err(|_| rustler::Error::Term(Box::new("expected tuple")))?;
+
+        if tuple.len() != 2 {
+            return Ok((atoms::error(), atoms::invalid_operation()).encode(env));
+        }
+
+        let cf_term = tuple[0];
+        let key: Binary = tuple[1]
+            .decode()
+            .map_err(|_| rustler::Error::Term(Box::new("expected binary for key")))?;
+
+        let cf_name = match cf_term_to_name(cf_term) {
+            Some(name) => name,
+            None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_term)).encode(env)),
+        };
+
+        let cf_handle = match db.cf_handle(&cf_name) {
+            Some(cf) => cf,
+            None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_term)).encode(env)),
+        };
+
+        batch.delete_cf(&cf_handle, key.as_slice());
+    }
+
+    match db.write_opt(batch, &write_opts) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok(write_error(env, atoms::batch_failed(), e)),
+    }
+}
+
+/// Atomically performs mixed put, delete and merge operations.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `operations` - List of operations:
+///   - `{:put, cf, key, value}` for puts
+///   - `{:delete, cf, key}` for deletes
+///   - `{:merge, :counters, key, delta}` to add a signed delta to a counter
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid, or is not
+///   `:counters` in a merge
+/// * `{:error, {:invalid_operation, op}}` if operation type is invalid
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:batch_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn mixed_batch<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    operations: Term<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let mut batch = WriteBatch::default();
+
+    // Parse the list of operations
+    let iter: ListIterator = operations
+        .decode()
+        .map_err(|_| rustler::Error::Term(Box::new("expected list")))?;
+
+    for item in iter {
+        let tuple = rustler::types::tuple::get_tuple(item)
+            .map_err(|_| rustler::Error::Term(Box::new("expected tuple")))?;
+
+        if tuple.is_empty() {
+            return Ok((atoms::error(), atoms::invalid_operation()).encode(env));
+        }
+
+        let op_atom: rustler::Atom = tuple[0]
+            .decode()
+            .map_err(|_| rustler::Error::Term(Box::new("expected atom for operation")))?;
+
+        if op_atom == atoms::put() {
+            // {:put, cf, key, value}
+            if tuple.len() != 4 {
+                return Ok((atoms::error(), atoms::invalid_operation()).encode(env));
+            }
+
+            let cf_term = tuple[1];
+            let key: Binary = tuple[2]
+                .decode()
+                .map_err(|_| rustler::Error::Term(Box::new("expected binary for key")))?;
+            let value: Binary = tuple[3]
+                .decode()
+                .map_err(|_| rustler::Error::Term(Box::new("expected binary for value")))?;
+
+            let cf_name = match cf_term_to_name(cf_term) {
+                Some(name) => name,
+                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_term)).encode(env)),
+            };
+
+            let cf_handle = match db.cf_handle(&cf_name) {
+                Some(cf) => cf,
+                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_term)).encode(env)),
+            };
+
+            batch.put_cf(&cf_handle, key.as_slice(), value.as_slice());
+        } else if op_atom == atoms::delete() {
+            // {:delete, cf, key}
+            if tuple.len() != 3 {
+                return Ok((atoms::error(), atoms::invalid_operation()).encode(env));
+            }
+
+            let cf_term = tuple[1];
+            let key: Binary = tuple[2]
+                .decode()
+                .map_err(|_| rustler::Error::Term(Box::new("expected binary for key")))?;
+
+            let cf_name = match cf_term_to_name(cf_term) {
+                Some(name) => name,
+                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_term)).encode(env)),
+            };
+
+            let cf_handle = match db.cf_handle(&cf_name) {
+                Some(cf) => cf,
+                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_term)).encode(env)),
+            };
+
+            batch.delete_cf(&cf_handle, key.as_slice());
+        } else if op_atom == atoms::merge() {
+            // {:merge, :counters, key, delta}
+            if tuple.len() != 4 {
+                return Ok((atoms::error(), atoms::invalid_operation()).encode(env));
+            }
+
+            let cf_term = tuple[1];
+            let key: Binary = tuple[2]
+                .decode()
+                .map_err(|_| rustler::Error::Term(Box::new("expected binary for key")))?;
+            let delta: i64 = tuple[3]
+                .decode()
+                .map_err(|_| rustler::Error::Term(Box::new("expected integer for delta")))?;
+
+            let cf_handle = match counters_cf(db, cf_term) {
+                Some(cf) => cf,
+                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_term)).encode(env)),
+            };
+
+            batch.merge_cf(&cf_handle, key.as_slice(), delta.to_be_bytes());
+        } else {
+            return Ok((atoms::error(), (atoms::invalid_operation(), op_atom)).encode(env));
+        }
+    }
+
+    match db.write_opt(batch, &write_opts) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok(write_error(env, atoms::batch_failed(), e)),
+    }
+}
+
+/// Atomically deletes every key in `[start, end)` of a column family.
+///
+/// Writes a single range tombstone instead of one tombstone per key, so the
+/// cost does not depend on how many keys the range holds.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `start` - Inclusive start key
+/// * `end` - Exclusive end key
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, :invalid_range}` if `start` is greater than `end`
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:delete_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn delete_range<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    start: Binary<'a>,
+    end: Binary<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    if start.as_slice() > end.as_slice() {
+        return Ok((atoms::error(), atoms::invalid_range()).encode(env));
+    }
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let mut batch = WriteBatch::default();
+    batch.delete_range_cf(&cf_handle, start.as_slice(), end.as_slice());
+
+    match db.write_opt(batch, &write_opts) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok(write_error(env, atoms::delete_failed(), e)),
+    }
+}
+
+// ============================================================================
+// Triple Operations
+// ============================================================================
+
+/// Size in bytes of one packed `(s, p, o)` triple record.
+const TRIPLE_RECORD_SIZE: usize = 3 * TERM_ID_SIZE;
+
+/// Key layout of the `derived` column family, which holds one SPO-ordered
+/// key per inferred triple.
+const DERIVED_INDICES: [(&str, [usize; 3]); 1] = [("derived", [0, 1, 2])];
+
+/// Decodes a list of `{s, p, o}` integer tuples, or a binary of packed
+/// 24-byte big-endian records, into triples.
+fn decode_triples(triples: Term) -> Option<Vec<[u64; 3]>> {
+    if let Ok(binary) = triples.decode::<Binary>() {
+        if binary.len() % TRIPLE_RECORD_SIZE != 0 {
+            return None;
+        }
+        return binary
+            .as_slice()
+            .chunks_exact(TRIPLE_RECORD_SIZE)
+            .map(|record| {
+                Some([
+                    key_component(record, 0)?,
+                    key_component(record, 1)?,
+                    key_component(record, 2)?,
+                ])
+            })
+            .collect();
+    }
+
+    let iter: ListIterator = triples.decode().ok()?;
+    iter.map(|item| {
+        item.decode::<(u64, u64, u64)>()
+            .ok()
+            .map(|(s, p, o)| [s, p, o])
+    })
+    .collect()
+}
+
+/// Decodes the options of a triple NIF into the column families and key
+/// layouts to use.
+///
+/// Write options are accepted only when `write_opts` is given, and are applied
+/// to it. On failure returns the `{:invalid_option, key}` reason.
+fn decode_triple_target<'a>(
+    env: Env<'a>,
+    options: Term<'a>,
+    mut write_opts: Option<&mut WriteOptions>,
+) -> Result<&'static [(&'static str, [usize; 3])], Term<'a>> {
+    let pairs = option_pairs(options).map_err(|term| (atoms::invalid_option(), term).encode(env))?;
+    let mut indices: &'static [(&'static str, [usize; 3])] = &TRIPLE_INDICES;
+
+    for (key, value) in pairs {
+        if key != atoms::target() {
+            match write_opts.as_deref_mut().map(|opts| set_write_option(opts, key, value)) {
+                Some(Ok(true)) => continue,
+                _ => return Err((atoms::invalid_option(), key).encode(env)),
+            }
+        }
+        indices = match value.decode::<rustler::Atom>() {
+            Ok(atom) if atom == atoms::indices() => &TRIPLE_INDICES,
+            Ok(atom) if atom == atoms::derived() => &DERIVED_INDICES,
+            _ => return Err((atoms::invalid_option(), key).encode(env)),
+        };
+    }
+
+    Ok(indices)
+}
+
+/// Builds the index key for a triple in the given layout.
+fn triple_key(triple: &[u64; 3], layout: &[usize; 3]) -> [u8; TRIPLE_RECORD_SIZE] {
+    let mut key = [0u8; TRIPLE_RECORD_SIZE];
+    for (chunk, &position) in key.chunks_exact_mut(TERM_ID_SIZE).zip(layout) {
+        chunk.copy_from_slice(&triple[position].to_be_bytes());
+    }
+    key
+}
+
+/// Writes or deletes every index key of the given triples in one WriteBatch.
+fn write_triples<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    triples: Term<'a>,
+    options: Term<'a>,
+    delete: bool,
+) -> NifResult<Term<'a>> {
+    let mut write_opts = WriteOptions::default();
+    let indices = match decode_triple_target(env, options, Some(&mut write_opts)) {
+        Ok(indices) => indices,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let triples = match decode_triples(triples) {
+        Some(triples) => triples,
+        None => return Ok((atoms::error(), atoms::invalid_triple()).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let mut batch = WriteBatch::default();
+
+    for (cf_name, layout) in indices {
+        let cf_handle = match db.cf_handle(cf_name) {
+            Some(cf) => cf,
+            None => return Ok((atoms::error(), (atoms::invalid_cf(), *cf_name)).encode(env)),
+        };
+
+        for triple in &triples {
+            let key = triple_key(triple, layout);
+            if delete {
+                batch.delete_cf(&cf_handle, key);
+            } else {
+                batch.put_cf(&cf_handle, key, []);
+            }
+        }
+    }
+
+    match db.write_opt(batch, &write_opts) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok(write_error(env, atoms::batch_failed(), e)),
+    }
+}
+
+/// Atomically inserts triples into the SPO, POS and OSP indices.
+///
+/// Index keys are built natively and written with empty values in a single
+/// WriteBatch, so either all triples are inserted or none are.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `triples` - List of `{s, p, o}` term ID tuples, or a binary of packed
+///   24-byte records (three big-endian u64 IDs each)
+/// * `options` - Keyword list; `target: :derived` writes one SPO-ordered key
+///   per triple to the `derived` column family instead of the three indices;
+///   the write options `sync`, `disable_wal`, `no_slowdown` and `low_pri`
+///   are also accepted
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, :invalid_triple}` if a triple is not three u64 IDs
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:batch_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn insert_triples<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    triples: Term<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    write_triples(env, db_ref, triples, options, false)
+}
+
+/// Atomically deletes triples from the SPO, POS and OSP indices.
+///
+/// Deleting a triple that does not exist is a no-op.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `triples` - List of `{s, p, o}` term ID tuples, or a binary of packed
+///   24-byte records (three big-endian u64 IDs each)
+/// * `options` - Keyword list; `target: :derived` deletes from the `derived`
+///   column family instead of the three indices; the write options `sync`,
+///   `disable_wal`, `no_slowdown` and `low_pri` are also accepted
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, :invalid_triple}` if a triple is not three u64 IDs
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:batch_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn delete_triples<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    triples: Term<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    write_triples(env, db_ref, triples, options, true)
+}
+
+/// Deletes every triple whose leading term in `TRIPLE_INDICES[leading]` is `id`.
+///
+/// The matching keys are contiguous in that index, which is cleared with one
+/// range tombstone; the keys in the other two
+/// indices are found by scanning that block and deleted individually. All
+/// deletions are written in one WriteBatch.
+fn delete_triples_with_term<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    id: u64,
+    leading: usize,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let mut cf_handles = Vec::with_capacity(TRIPLE_INDICES.len());
+    for (cf_name, _) in &TRIPLE_INDICES {
+        match db.cf_handle(cf_name) {
+            Some(cf) => cf_handles.push(cf),
+            None => return Ok((atoms::error(), (atoms::invalid_cf(), *cf_name)).encode(env)),
+        }
+    }
+
+    let (cf_name, layout) = TRIPLE_INDICES[leading];
+
+    let prefix = id.to_be_bytes();
+    let read_opts = db_ref.prefix_read_options(cf_name, &prefix)?;
+    let mut iterator = db.raw_iterator_cf_opt(&cf_handles[leading], read_opts);
+    iterator.seek(&prefix);
+
+    let mut batch = WriteBatch::default();
+    let mut count: u64 = 0;
+    while let Some(key) = iterator.key().filter(|key| key.starts_with(&prefix)) {
+        let mut triple = [0u64; 3];
+        for (index, &slot) in layout.iter().enumerate() {
+            triple[slot] = key_component(key, index).unwrap_or_default();
+        }
+        for (other, (_, other_layout)) in TRIPLE_INDICES.iter().enumerate() {
+            if other != leading {
+                batch.delete_cf(&cf_handles[other], triple_key(&triple, other_layout));
+            }
+        }
+        count += 1;
+        iterator.next();
+    }
+
+    if let Err(e) = iterator.status() {
+        return Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env));
+    }
+    drop(iterator);
+
+    // An all-0xff ID has no successor; pad past every key that starts with it
+    let end = prefix_successor(&prefix).unwrap_or_else(|| {
+        let mut end = prefix.to_vec();
+        end.resize(TRIPLE_RECORD_SIZE + 1, 0xff);
+        end
+    });
+    batch.delete_range_cf(&cf_handles[leading], &prefix[..], &end[..]);
+
+    match db.write_opt(batch, &write_opts) {
+        Ok(()) => Ok((atoms::ok(), count).encode(env)),
+        Err(e) => Ok(write_error(env, atoms::batch_failed(), e)),
+    }
+}
+
+/// Atomically deletes every triple with the given subject.
+///
+/// The subject's block of the SPO index is removed with one range tombstone,
+/// and the matching POS and OSP keys are deleted in the same WriteBatch.
+/// Triples inserted concurrently with the call may survive in some indices.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `subject` - The subject term ID
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
+///
+/// # Returns
+/// * `{:ok, count}` with the number of triples deleted
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, {:iterator_failed, reason}}` on read errors
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:batch_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn delete_subject<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    subject: u64,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    delete_triples_with_term(env, db_ref, subject, 0, options)
+}
+
+/// Atomically deletes every triple with the given predicate.
+///
+/// The predicate's block of the POS index is removed with one range
+/// tombstone, and the matching SPO and OSP keys are deleted in the same
+/// WriteBatch. Triples inserted concurrently with the call may survive in
+/// some indices.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `predicate` - The predicate term ID
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
+///
+/// # Returns
+/// * `{:ok, count}` with the number of triples deleted
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, {:iterator_failed, reason}}` on read errors
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:batch_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn delete_predicate<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    predicate: u64,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    delete_triples_with_term(env, db_ref, predicate, 1, options)
+}
+
+// ============================================================================
+// Iterator Operations
+// ============================================================================
+
+/// Creates a prefix iterator for a column family.
+///
+/// The iterator returns all key-value pairs where the key starts with the given prefix.
+/// The iterator must be closed with `iterator_close` when done.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `prefix` - The prefix to iterate over
+///
+/// # Returns
+/// * `{:ok, iterator_ref}` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+#[rustler::nif(schedule = "DirtyCpu")]
+fn prefix_iterator<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    prefix: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let prefix_bytes = prefix.as_slice().to_vec();
+    let read_opts = db_ref.prefix_read_options(&cf_name, &prefix_bytes)?;
+
+    // Create the iterator with prefix mode
+    // Safety: We use unsafe to extend the lifetime because we're storing
+    // the db_ref Arc which keeps the database alive
+    let iterator = db.iterator_cf_opt(
+        &cf_handle,
+        read_opts,
+        IteratorMode::From(&prefix_bytes, rocksdb::Direction::Forward),
+    );
+
+    // SAFETY: We keep the DbRef alive via Arc, so the iterator remains valid
+    let static_iterator: DbIterator<'static> = unsafe {
+        std::mem::transmute(iterator)
+    };
+
+    let iter_ref = ResourceArc::new(IteratorRef {
+        iterator: Arc::new(Mutex::new(Some(static_iterator))),
+        _db_ref: Arc::new(db_ref.clone()),
+        txn_ref: None,
+        prefix: prefix_bytes,
+        _cf_name: cf_name.to_string(),
+    });
+
+    Ok((atoms::ok(), iter_ref).encode(env))
+}
+
+/// Creates an iterator over the keys in `[lower, upper)` of a column family.
+///
+/// The bounds are enforced by RocksDB itself, so entries outside the range are
+/// never read. A `:reverse` iterator starts at the last key below `upper` and
+/// walks backwards to `lower`. The iterator must be closed with
+/// `iterator_close` when done.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `lower` - Inclusive lower bound, or `nil` for unbounded
+/// * `upper` - Exclusive upper bound, or `nil` for unbounded
+/// * `direction` - `:forward` or `:reverse`
+///
+/// # Returns
+/// * `{:ok, iterator_ref}` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:invalid_direction, direction}}` if direction is invalid
+#[rustler::nif(schedule = "DirtyCpu")]
+fn range_iterator<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    lower: Option<Binary<'a>>,
+    upper: Option<Binary<'a>>,
+    direction: rustler::Atom,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let mode = match range_iterator_mode(direction) {
+        Some(mode) => mode,
+        None => return Ok((atoms::error(), (atoms::invalid_direction(), direction)).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let read_opts = range_read_options(lower, upper);
+    let iterator = db.iterator_cf_opt(&cf_handle, read_opts, mode);
+
+    // SAFETY: We keep the DbRef alive via Arc, so the iterator remains valid
+    let static_iterator: DbIterator<'static> = unsafe {
+        std::mem::transmute(iterator)
+    };
+
+    // An empty prefix matches every key; the bounds do the filtering
+    let iter_ref = ResourceArc::new(IteratorRef {
+        iterator: Arc::new(Mutex::new(Some(static_iterator))),
+        _db_ref: Arc::new(db_ref.clone()),
+        txn_ref: None,
+        prefix: Vec::new(),
+        _cf_name: cf_name.to_string(),
+    });
+
+    Ok((atoms::ok(), iter_ref).encode(env))
+}
+
+/// Gets the next key-value pair from the iterator.
+///
+/// # Arguments
+/// * `iter_ref` - The iterator reference
+///
+/// # Returns
+/// * `{:ok, key, value}` if there's a next item with matching prefix
+/// * `:end` if the iterator is exhausted or prefix no longer matches
+/// * `{:error, :iterator_closed}` if iterator was closed
+/// * `{:error, {:iterator_failed, reason}}` on error
+#[rustler::nif(schedule = "DirtyCpu")]
+fn iterator_next<'a>(env: Env<'a>, iter_ref: ResourceArc<IteratorRef>) -> NifResult<Term<'a>> {
+    let _txn_guard = iter_ref.lock_transaction()?;
+    let mut iter_guard = iter_ref
+        .iterator
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let iterator = match iter_guard.as_mut() {
+        Some(iter) => iter,
+        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    };
+
+    match iterator.next() {
+        Some(Ok((key, value))) => {
+            // Check if key still has the prefix
+            if !key.starts_with(&iter_ref.prefix) {
+                return Ok(atoms::iterator_end().encode(env));
+            }
+
+            let mut key_binary = NewBinary::new(env, key.len());
+            key_binary.as_mut_slice().copy_from_slice(&key);
+
+            let mut value_binary = NewBinary::new(env, value.len());
+            value_binary.as_mut_slice().copy_from_slice(&value);
+
+            Ok((atoms::ok(), Binary::from(key_binary), Binary::from(value_binary)).encode(env))
+        }
+        Some(Err(e)) => {
+            Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env))
+        }
+        None => Ok(atoms::iterator_end().encode(env)),
+    }
+}
+
+/// Seeks the iterator to a specific key.
+///
+/// After seeking, the iterator will return keys >= target that match the prefix.
+/// The iterator is repositioned in place, so its bounds, snapshot and prefix
+/// are kept; a target below the prefix seeks to the start of the prefix.
+/// This is essential for Leapfrog Triejoin in Phase 3.
+///
+/// # Arguments
+/// * `iter_ref` - The iterator reference
+/// * `target` - The key to seek to
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :iterator_closed}` if iterator was closed
+/// * `{:error, :already_closed}` if database is closed
+#[rustler::nif(schedule = "DirtyCpu")]
+fn iterator_seek<'a>(
+    env: Env<'a>,
+    iter_ref: ResourceArc<IteratorRef>,
+    target: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    seek_db_iterator(env, &iter_ref, target.as_slice(), rocksdb::Direction::Forward)
+}
+
+/// Seeks the iterator to the last key <= target and switches it to reverse.
+///
+/// After seeking, the iterator returns keys <= target that match the prefix in
+/// descending order. A target above the prefix seeks to the last key with the
+/// prefix. Used for reverse Leapfrog Triejoin steps.
+///
+/// # Arguments
+/// * `iter_ref` - The iterator reference
+/// * `target` - The key to seek to
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :iterator_closed}` if iterator was closed
+/// * `{:error, :already_closed}` if database is closed
+#[rustler::nif(schedule = "DirtyCpu")]
+fn iterator_seek_for_prev<'a>(
+    env: Env<'a>,
+    iter_ref: ResourceArc<IteratorRef>,
+    target: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    seek_db_iterator(env, &iter_ref, target.as_slice(), rocksdb::Direction::Reverse)
+}
+
+/// Shared implementation of `iterator_seek` and `iterator_seek_for_prev`.
+fn seek_db_iterator<'a>(
+    env: Env<'a>,
+    iter_ref: &IteratorRef,
+    target: &[u8],
+    direction: rocksdb::Direction,
+) -> NifResult<Term<'a>> {
+    let _txn_guard = iter_ref.lock_transaction()?;
+    let mut iter_guard = iter_ref
+        .iterator
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let iterator = match iter_guard.as_mut() {
+        Some(iter) => iter,
+        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    };
+
+    // Hold the database open while the iterator touches it
+    let db_guard = iter_ref
+        ._db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    if db_guard.is_none() {
+        return Ok((atoms::error(), atoms::already_closed()).encode(env));
+    }
+
+    seek_iterator(iterator, &iter_ref.prefix, target, direction);
+
+    Ok(atoms::ok().encode(env))
+}
+
+/// Closes the iterator and releases resources.
+///
+/// # Arguments
+/// * `iter_ref` - The iterator reference
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :iterator_closed}` if already closed
+#[rustler::nif]
+fn iterator_close<'a>(env: Env<'a>, iter_ref: ResourceArc<IteratorRef>) -> NifResult<Term<'a>> {
+    let mut iter_guard = iter_ref
+        .iterator
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    if iter_guard.is_none() {
+        return Ok((atoms::error(), atoms::iterator_closed()).encode(env));
+    }
+
+    // Drop the iterator
+    *iter_guard = None;
+
+    Ok(atoms::ok().encode(env))
+}
+
+/// Collects all remaining key-value pairs from an iterator into a list.
+///
+/// This is a convenience function that consumes the iterator and returns
+/// all matching entries. Useful for small result sets where streaming isn't needed.
+///
+/// # Arguments
+/// * `iter_ref` - The iterator reference
+///
+/// # Returns
+/// * `{:ok, [{key, value}, ...]}` with all remaining entries
+/// * `{:error, :iterator_closed}` if iterator was closed
+/// * `{:error, {:iterator_failed, reason}}` on error
+#[rustler::nif(schedule = "DirtyCpu")]
+fn iterator_collect<'a>(env: Env<'a>, iter_ref: ResourceArc<IteratorRef>) -> NifResult<Term<'a>> {
+    let _txn_guard = iter_ref.lock_transaction()?;
+    let mut iter_guard = iter_ref
+        .iterator
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let iterator = match iter_guard.as_mut() {
+        Some(iter) => iter,
+        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    };
+
+    let mut results: Vec<Term<'a>> = Vec::new();
+
+    for result in iterator.by_ref() {
+        match result {
+            Ok((key, value)) => {
+                // Check if key still has the prefix
+                if !key.starts_with(&iter_ref.prefix) {
+                    break;
+                }
+
+                let mut key_binary = NewBinary::new(env, key.len());
+                key_binary.as_mut_slice().copy_from_slice(&key);
+
+                let mut value_binary = NewBinary::new(env, value.len());
+                value_binary.as_mut_slice().copy_from_slice(&value);
+
+                results.push((Binary::from(key_binary), Binary::from(value_binary)).encode(env));
+            }
+            Err(e) => {
+                return Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env));
+            }
+        }
+    }
+
+    Ok((atoms::ok(), results).encode(env))
+}
+
+/// Reads up to `n` entries with the given prefix from an iterator.
+///
+/// Returns `{:ok, [{key, value}], :more}` when the chunk is full and
+/// `{:ok, [{key, value}], :end}` once the iterator is exhausted or leaves the
+/// prefix, so callers can stop without another round trip.
+fn next_n_entries<'a>(
+    env: Env<'a>,
+    iterator: &mut DbIterator<'static>,
+    prefix: &[u8],
+    n: usize,
+) -> Term<'a> {
+    let mut results: Vec<Term<'a>> = Vec::with_capacity(n.min(4096));
+
+    while results.len() < n {
+        match iterator.next() {
+            Some(Ok((key, value))) => {
+                if !key.starts_with(prefix) {
+                    return (atoms::ok(), results, atoms::end()).encode(env);
+                }
+
+                let mut key_binary = NewBinary::new(env, key.len());
+                key_binary.as_mut_slice().copy_from_slice(&key);
+
+                let mut value_binary = NewBinary::new(env, value.len());
+                value_binary.as_mut_slice().copy_from_slice(&value);
+
+                results.push((Binary::from(key_binary), Binary::from(value_binary)).encode(env));
+            }
+            Some(Err(e)) => {
+                return (atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env);
+            }
+            None => return (atoms::ok(), results, atoms::end()).encode(env),
+        }
+    }
+
+    (atoms::ok(), results, atoms::more()).encode(env)
+}
+
+/// Gets up to `n` key-value pairs from the iterator in one call.
+///
+/// # Arguments
+/// * `iter_ref` - The iterator reference
+/// * `n` - Maximum number of pairs to return
+///
+/// # Returns
+/// * `{:ok, pairs, :more}` if `n` pairs were read and more may follow
+/// * `{:ok, pairs, :end}` if the iterator is exhausted or prefix no longer matches
+/// * `{:error, :iterator_closed}` if iterator was closed
+/// * `{:error, {:iterator_failed, reason}}` on error
+#[rustler::nif(schedule = "DirtyCpu")]
+fn iterator_next_n<'a>(
+    env: Env<'a>,
+    iter_ref: ResourceArc<IteratorRef>,
+    n: usize,
+) -> NifResult<Term<'a>> {
+    let _txn_guard = iter_ref.lock_transaction()?;
+    let mut iter_guard = iter_ref
+        .iterator
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let iterator = match iter_guard.as_mut() {
+        Some(iter) => iter,
+        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    };
+
+    Ok(next_n_entries(env, iterator, &iter_ref.prefix, n))
+}
+
+// ============================================================================
+// Count Operations
+// ============================================================================
+
+/// Position of `key` within the file key range `[start, end]` as a fraction
+/// in `[0, 1]`, interpolating the eight bytes after the common prefix of
+/// `start` and `end` as a big-endian integer.
+fn key_fraction(key: &[u8], start: &[u8], end: &[u8]) -> f64 {
+    if key <= start {
+        return 0.0;
+    }
+    if key > end {
+        return 1.0;
+    }
+
+    let common = start.iter().zip(end).take_while(|(a, b)| a == b).count();
+    let position = |k: &[u8]| {
+        let mut bytes = [0u8; 8];
+        for (byte, value) in bytes.iter_mut().zip(k.iter().skip(common)) {
+            *byte = *value;
+        }
+        u64::from_be_bytes(bytes) as f64
+    };
+
+    let (low, high) = (position(start), position(end));
+    if high <= low {
+        return 1.0;
+    }
+    ((position(key) - low) / (high - low)).clamp(0.0, 1.0)
+}
+
+/// Counts the keys with the given prefix in a column family.
+///
+/// Iterates keys only: values are never read into the BEAM and no binaries
+/// are allocated, so this is much cheaper than enumerating a prefix stream.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `prefix` - The prefix to count (empty counts the whole column family)
+///
+/// # Returns
+/// * `{:ok, count}` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:iterator_failed, reason}}` on read errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn count_prefix<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    prefix: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let prefix = prefix.as_slice();
+    let read_opts = db_ref.prefix_read_options(&cf_name, prefix)?;
+    let mut iterator = db.raw_iterator_cf_opt(&cf_handle, read_opts);
+    iterator.seek(prefix);
+
+    let mut count: u64 = 0;
+    while iterator.key().is_some_and(|key| key.starts_with(prefix)) {
+        count += 1;
+        iterator.next();
+    }
+
+    match iterator.status() {
+        Ok(()) => Ok((atoms::ok(), count).encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Estimates the number of keys in `[lower, upper)` of a column family.
+///
+/// Starts from RocksDB's `rocksdb.estimate-num-keys` property. When a bound
+/// is given, the estimate is scaled by the share of flushed SST entries that
+/// fall in the range, interpolated from each file's smallest and largest key.
+/// Nothing is read from disk, so the result is instant but approximate; with
+/// no flushed files the whole column family's estimate is returned.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `lower` - Inclusive lower bound, or `nil` for unbounded
+/// * `upper` - Exclusive upper bound, or `nil` for unbounded
+///
+/// # Returns
+/// * `{:ok, estimate}` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:property_failed, reason}}` if RocksDB cannot report the estimate
+#[rustler::nif(schedule = "DirtyCpu")]
+fn approximate_count<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    lower: Option<Binary<'a>>,
+    upper: Option<Binary<'a>>,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let estimate = match db.property_int_value_cf(&cf_handle, "rocksdb.estimate-num-keys") {
+        Ok(estimate) => estimate.unwrap_or(0),
+        Err(e) => return Ok((atoms::error(), (atoms::property_failed(), e.to_string())).encode(env)),
+    };
+
+    if lower.is_none() && upper.is_none() {
+        return Ok((atoms::ok(), estimate).encode(env));
+    }
+
+    let files = match db.live_files() {
+        Ok(files) => files,
+        Err(e) => return Ok((atoms::error(), (atoms::property_failed(), e.to_string())).encode(env)),
+    };
+
+    let mut total_entries = 0.0;
+    let mut range_entries = 0.0;
+    for file in files.iter().filter(|file| file.column_family_name == cf_name) {
+        let (Some(start), Some(end)) = (&file.start_key, &file.end_key) else {
+            continue;
+        };
+        let entries = file.num_entries.saturating_sub(file.num_deletions) as f64;
+        let from = lower.as_ref().map_or(0.0, |key| key_fraction(key.as_slice(), start, end));
+        let to = upper.as_ref().map_or(1.0, |key| key_fraction(key.as_slice(), start, end));
+        total_entries += entries;
+        range_entries += entries * (to - from).max(0.0);
+    }
+
+    if total_entries == 0.0 {
+        return Ok((atoms::ok(), estimate).encode(env));
+    }
+
+    let scaled = (estimate as f64 * range_entries / total_entries).round() as u64;
+    Ok((atoms::ok(), scaled).encode(env))
+}
+
+// ============================================================================
+// Snapshot Operations
+// ============================================================================
+
+/// Creates a snapshot of the database for consistent point-in-time reads.
+///
+/// A snapshot provides a consistent view of the database at the time of creation.
+/// All reads using the snapshot will see the same data, regardless of subsequent
+/// writes to the database.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+///
+/// # Returns
+/// * `{:ok, snapshot_ref}` on success
+/// * `{:error, :already_closed}` if database is closed
+#[rustler::nif(schedule = "DirtyCpu")]
+fn snapshot<'a>(env: Env<'a>, db_ref: ResourceArc<DbRef>) -> NifResult<Term<'a>> {
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let snap = db.snapshot();
+
+    // SAFETY: We keep the DbRef alive via Arc, so the snapshot remains valid
+    let static_snapshot: DbSnapshot<'static> = unsafe {
+        std::mem::transmute(snap)
+    };
+
+    let snap_ref = ResourceArc::new(SnapshotRef {
+        snapshot: Mutex::new(Some(static_snapshot)),
+        db_ref: Arc::new(db_ref.clone()),
+    });
+
+    Ok((atoms::ok(), snap_ref).encode(env))
+}
+
+/// Gets a value from a column family using a snapshot.
+///
+/// This provides point-in-time consistent reads - the value returned
+/// is what existed at the time the snapshot was created.
+///
+/// # Arguments
+/// * `snapshot_ref` - The snapshot reference
+/// * `cf` - The column family name as an atom or binary
+/// * `key` - The key as a binary
+///
+/// # Returns
+/// * `{:ok, value}` if found
+/// * `:not_found` if key doesn't exist
+/// * `{:error, :snapshot_released}` if snapshot was released
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:get_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn snapshot_get<'a>(
+    env: Env<'a>,
+    snapshot_ref: ResourceArc<SnapshotRef>,
+    cf: Term<'a>,
+    key: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let snap_guard = snapshot_ref
+        .snapshot
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let snapshot = match snap_guard.as_ref() {
+        Some(snap) => snap,
+        None => return Ok((atoms::error(), atoms::snapshot_released()).encode(env)),
+    };
+
+    // Get the database to access column family handles
+    let db_guard = snapshot_ref.db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    // Use ReadOptions with snapshot
+    let mut read_opts = ReadOptions::default();
+    snapshot.apply(&mut read_opts);
+
+    match db.get_cf_opt(&cf_handle, key.as_slice(), &read_opts) {
+        Ok(Some(value)) => {
+            let mut binary = NewBinary::new(env, value.len());
+            binary.as_mut_slice().copy_from_slice(&value);
+            Ok((atoms::ok(), Binary::from(binary)).encode(env))
+        }
+        Ok(None) => Ok(atoms::not_found().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::get_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Gets the values for multiple keys from a snapshot in one batched lookup.
+///
+/// # Arguments
+/// * `snapshot_ref` - The snapshot reference
+/// * `cf` - The column family name as an atom or binary
+/// * `keys` - List of keys as binaries
+///
+/// # Returns
+/// * `{:ok, results}` with one `{:ok, value}` or `:not_found` per key, in input order
+/// * `{:error, :snapshot_released}` if snapshot was released
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:get_failed, reason}}` if any lookup fails
+#[rustler::nif(schedule = "DirtyCpu")]
+fn snapshot_multi_get<'a>(
+    env: Env<'a>,
+    snapshot_ref: ResourceArc<SnapshotRef>,
+    cf: Term<'a>,
+    keys: Vec<Binary<'a>>,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let snap_guard = snapshot_ref
+        .snapshot
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let snapshot = match snap_guard.as_ref() {
+        Some(snap) => snap,
+        None => return Ok((atoms::error(), atoms::snapshot_released()).encode(env)),
+    };
+
+    let db_guard = snapshot_ref.db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let mut read_opts = ReadOptions::default();
+    snapshot.apply(&mut read_opts);
+
+    let results = db.batched_multi_get_cf_opt(
+        &cf_handle,
+        keys.iter().map(|key| key.as_slice()),
+        false,
+        &read_opts,
+    );
+
+    Ok(encode_multi_get_results(env, results))
+}
+
+/// Creates a prefix iterator over a snapshot.
+///
+/// The iterator returns all key-value pairs where the key starts with the given prefix,
+/// using the consistent view from the snapshot.
+///
+/// # Arguments
+/// * `snapshot_ref` - The snapshot reference
+/// * `cf` - The column family name as an atom or binary
+/// * `prefix` - The prefix to iterate over
+///
+/// # Returns
+/// * `{:ok, iterator_ref}` on success
+/// * `{:error, :snapshot_released}` if snapshot was released
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+#[rustler::nif(schedule = "DirtyCpu")]
+fn snapshot_prefix_iterator<'a>(
+    env: Env<'a>,
+    snapshot_ref: ResourceArc<SnapshotRef>,
+    cf: Term<'a>,
+    prefix: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let snap_guard = snapshot_ref
+        .snapshot
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let snapshot = match snap_guard.as_ref() {
+        Some(snap) => snap,
+        None => return Ok((atoms::error(), atoms::snapshot_released()).encode(env)),
+    };
+
+    // Get the database to access column family handles
+    let db_guard = snapshot_ref.db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let prefix_bytes = prefix.as_slice().to_vec();
+
+    // Create read options with snapshot
+    let mut read_opts = snapshot_ref.db_ref.prefix_read_options(&cf_name, &prefix_bytes)?;
+    snapshot.apply(&mut read_opts);
+
+    // Create the iterator with snapshot
+    let iterator = db.iterator_cf_opt(
+        &cf_handle,
+        read_opts,
+        IteratorMode::From(&prefix_bytes, rocksdb::Direction::Forward),
+    );
+
+    // SAFETY: We keep the SnapshotRef alive via Arc, so the iterator remains valid
+    let static_iterator: DbIterator<'static> = unsafe {
+        std::mem::transmute(iterator)
+    };
+
+    let iter_ref = ResourceArc::new(SnapshotIteratorRef {
+        iterator: Mutex::new(Some(static_iterator)),
+        _snapshot_ref: Arc::new(snapshot_ref.clone()),
+        prefix: prefix_bytes,
+        _cf_name: cf_name.to_string(),
+    });
+
+    Ok((atoms::ok(), iter_ref).encode(env))
+}
+
+/// Creates an iterator over the keys in `[lower, upper)` of a snapshot.
+///
+/// Snapshot counterpart of `range_iterator`.
+///
+/// # Arguments
+/// * `snapshot_ref` - The snapshot reference
+/// * `cf` - The column family name as an atom or binary
+/// * `lower` - Inclusive lower bound, or `nil` for unbounded
+/// * `upper` - Exclusive upper bound, or `nil` for unbounded
+/// * `direction` - `:forward` or `:reverse`
+///
+/// # Returns
+/// * `{:ok, iterator_ref}` on success
+/// * `{:error, :snapshot_released}` if snapshot was released
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:invalid_direction, direction}}` if direction is invalid
+#[rustler::nif(schedule = "DirtyCpu")]
+fn snapshot_range_iterator<'a>(
+    env: Env<'a>,
+    snapshot_ref: ResourceArc<SnapshotRef>,
+    cf: Term<'a>,
+    lower: Option<Binary<'a>>,
+    upper: Option<Binary<'a>>,
+    direction: rustler::Atom,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let mode = match range_iterator_mode(direction) {
+        Some(mode) => mode,
+        None => return Ok((atoms::error(), (atoms::invalid_direction(), direction)).encode(env)),
+    };
+
+    let snap_guard = snapshot_ref
+        .snapshot
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let snapshot = match snap_guard.as_ref() {
+        Some(snap) => snap,
+        None => return Ok((atoms::error(), atoms::snapshot_released()).encode(env)),
+    };
+
+    let db_guard = snapshot_ref.db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let mut read_opts = range_read_options(lower, upper);
+    snapshot.apply(&mut read_opts);
+    let iterator = db.iterator_cf_opt(&cf_handle, read_opts, mode);
+
+    // SAFETY: We keep the SnapshotRef alive via Arc, so the iterator remains valid
+    let static_iterator: DbIterator<'static> = unsafe {
+        std::mem::transmute(iterator)
+    };
+
+    let iter_ref = ResourceArc::new(SnapshotIteratorRef {
+        iterator: Mutex::new(Some(static_iterator)),
+        _snapshot_ref: Arc::new(snapshot_ref.clone()),
+        prefix: Vec::new(),
+        _cf_name: cf_name.to_string(),
+    });
+
+    Ok((atoms::ok(), iter_ref).encode(env))
+}
+
+/// Gets the next key-value pair from a snapshot iterator.
+///
+/// # Arguments
+/// * `iter_ref` - The snapshot iterator reference
+///
+/// # Returns
+/// * `{:ok, key, value}` if there's a next item with matching prefix
+/// * `:iterator_end` if the iterator is exhausted or prefix no longer matches
+/// * `{:error, :iterator_closed}` if iterator was closed
+/// * `{:error, {:iterator_failed, reason}}` on error
+#[rustler::nif(schedule = "DirtyCpu")]
+fn snapshot_iterator_next<'a>(
+    env: Env<'a>,
+    iter_ref: ResourceArc<SnapshotIteratorRef>,
+) -> NifResult<Term<'a>> {
+    let mut iter_guard = iter_ref
+        .iterator
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let iterator = match iter_guard.as_mut() {
+        Some(iter) => iter,
+        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    };
+
+    match iterator.next() {
+        Some(Ok((key, value))) => {
+            // Check if key still has the prefix
+            if !key.starts_with(&iter_ref.prefix) {
+                return Ok(atoms::iterator_end().encode(env));
+            }
+
+            let mut key_binary = NewBinary::new(env, key.len());
+            key_binary.as_mut_slice().copy_from_slice(&key);
+
+            let mut value_binary = NewBinary::new(env, value.len());
+            value_binary.as_mut_slice().copy_from_slice(&value);
+
+            Ok((atoms::ok(), Binary::from(key_binary), Binary::from(value_binary)).encode(env))
+        }
+        Some(Err(e)) => {
+            Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env))
+        }
+        None => Ok(atoms::iterator_end().encode(env)),
+    }
+}
+
+/// Gets up to `n` key-value pairs from a snapshot iterator in one call.
+///
+/// # Arguments
+/// * `iter_ref` - The snapshot iterator reference
+/// * `n` - Maximum number of pairs to return
+///
+/// # Returns
+/// * `{:ok, pairs, :more}` if `n` pairs were read and more may follow
+/// * `{:ok, pairs, :end}` if the iterator is exhausted or prefix no longer matches
+/// * `{:error, :iterator_closed}` if iterator was closed
+/// * `{:error, {:iterator_failed, reason}}` on error
+#[rustler::nif(schedule = "DirtyCpu")]
+fn snapshot_iterator_next_n<'a>(
+    env: Env<'a>,
+    iter_ref: ResourceArc<SnapshotIteratorRef>,
+    n: usize,
+) -> NifResult<Term<'a>> {
+    let mut iter_guard = iter_ref
+        .iterator
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let iterator = match iter_guard.as_mut() {
+        Some(iter) => iter,
+        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    };
+
+    Ok(next_n_entries(env, iterator, &iter_ref.prefix, n))
+}
+
+/// Seeks a snapshot iterator to a specific key.
+///
+/// Snapshot counterpart of `iterator_seek`: repositions the iterator in place
+/// at the first key >= target, keeping the snapshot, bounds and prefix.
+///
+/// # Arguments
+/// * `iter_ref` - The snapshot iterator reference
+/// * `target` - The key to seek to
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :iterator_closed}` if iterator was closed
+#[rustler::nif(schedule = "DirtyCpu")]
+fn snapshot_iterator_seek<'a>(
+    env: Env<'a>,
+    iter_ref: ResourceArc<SnapshotIteratorRef>,
+    target: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    seek_snapshot_iterator(env, &iter_ref, target.as_slice(), rocksdb::Direction::Forward)
+}
+
+/// Seeks a snapshot iterator to the last key <= target and switches it to reverse.
+///
+/// Snapshot counterpart of `iterator_seek_for_prev`.
+///
+/// # Arguments
+/// * `iter_ref` - The snapshot iterator reference
+/// * `target` - The key to seek to
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :iterator_closed}` if iterator was closed
+#[rustler::nif(schedule = "DirtyCpu")]
+fn snapshot_iterator_seek_for_prev<'a>(
+    env: Env<'a>,
+    iter_ref: ResourceArc<SnapshotIteratorRef>,
+    target: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    seek_snapshot_iterator(env, &iter_ref, target.as_slice(), rocksdb::Direction::Reverse)
+}
+
+/// Shared implementation of `snapshot_iterator_seek` and `snapshot_iterator_seek_for_prev`.
+fn seek_snapshot_iterator<'a>(
+    env: Env<'a>,
+    iter_ref: &SnapshotIteratorRef,
+    target: &[u8],
+    direction: rocksdb::Direction,
+) -> NifResult<Term<'a>> {
+    let mut iter_guard = iter_ref
+        .iterator
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let iterator = match iter_guard.as_mut() {
+        Some(iter) => iter,
+        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    };
+
+    seek_iterator(iterator, &iter_ref.prefix, target, direction);
+
+    Ok(atoms::ok().encode(env))
+}
+
+/// Closes a snapshot iterator and releases resources.
+///
+/// # Arguments
+/// * `iter_ref` - The snapshot iterator reference
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :iterator_closed}` if already closed
+#[rustler::nif]
+fn snapshot_iterator_close<'a>(
+    env: Env<'a>,
+    iter_ref: ResourceArc<SnapshotIteratorRef>,
+) -> NifResult<Term<'a>> {
+    let mut iter_guard = iter_ref
+        .iterator
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    if iter_guard.is_none() {
+        return Ok((atoms::error(), atoms::iterator_closed()).encode(env));
+    }
+
+    // Drop the iterator
+    *iter_guard = None;
+
+    Ok(atoms::ok().encode(env))
+}
+
+/// Collects all remaining key-value pairs from a snapshot iterator into a list.
+///
+/// # Arguments
+/// * `iter_ref` - The snapshot iterator reference
+///
+/// # Returns
+/// * `{:ok, [{key, value}, ...]}` with all remaining entries
+/// * `{:error, :iterator_closed}` if iterator was closed
+/// * `{:error, {:iterator_failed, reason}}` on error
+#[rustler::nif(schedule = "DirtyCpu")]
+fn snapshot_iterator_collect<'a>(
+    env: Env<'a>,
+    iter_ref: ResourceArc<SnapshotIteratorRef>,
+) -> NifResult<Term<'a>> {
+    let mut iter_guard = iter_ref
+        .iterator
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let iterator = match iter_guard.as_mut() {
+        Some(iter) => iter,
+        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    };
+
+    let mut results: Vec<Term<'a>> = Vec::new();
+
+    for result in iterator.by_ref() {
+        match result {
+            Ok((key, value)) => {
+                // Check if key still has the prefix
+                if !key.starts_with(&iter_ref.prefix) {
+                    break;
+                }
+
+                let mut key_binary = NewBinary::new(env, key.len());
+                key_binary.as_mut_slice().copy_from_slice(&key);
+
+                let mut value_binary = NewBinary::new(env, value.len());
+                value_binary.as_mut_slice().copy_from_slice(&value);
+
+                results.push((Binary::from(key_binary), Binary::from(value_binary)).encode(env));
+            }
+            Err(e) => {
+                return Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env));
+            }
+        }
+    }
+
+    Ok((atoms::ok(), results).encode(env))
+}
+
+/// Releases a snapshot and frees resources.
+///
+/// After calling release, the snapshot handle is no longer valid.
+///
+/// # Arguments
+/// * `snapshot_ref` - The snapshot reference
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :snapshot_released}` if already released
+#[rustler::nif]
+fn release_snapshot<'a>(
+    env: Env<'a>,
+    snapshot_ref: ResourceArc<SnapshotRef>,
+) -> NifResult<Term<'a>> {
+    let mut snap_guard = snapshot_ref
+        .snapshot
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    if snap_guard.is_none() {
+        return Ok((atoms::error(), atoms::snapshot_released()).encode(env));
+    }
+
+    // Drop the snapshot
+    *snap_guard = None;
+
+    Ok(atoms::ok().encode(env))
+}
+
+// ============================================================================
+// Transactions
+// ============================================================================
+
+/// Maps a transaction error to its reason term.
+///
+/// Optimistic transactions report a write conflict as `Busy`, or as `TryAgain`
+/// when the memtable history is too short to check for one; both become
+/// `:conflict` so callers can retry. A commit rejected by `no_slowdown` is
+/// reported as `:write_stall`, as for the other write NIFs.
+fn transaction_error<'a>(env: Env<'a>, failure: rustler::Atom, error: Error) -> Term<'a> {
+    match error.kind() {
+        ErrorKind::Busy | ErrorKind::TryAgain => (atoms::error(), atoms::conflict()).encode(env),
+        _ => write_error(env, failure, error),
+    }
+}
+
+/// Runs `f` against an open transaction and the handle of a column family.
+///
+/// Holds the database read lock for the duration of the call so the database
+/// cannot be closed underneath the transaction.
+fn with_transaction<'a>(
+    env: Env<'a>,
+    txn_ref: &TransactionRef,
+    cf: Term<'a>,
+    f: impl FnOnce(&Transaction<'static, OptimisticTransactionDB>, &ColumnFamily) -> Term<'a>,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let db_guard = txn_ref
+        .db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let txn_guard = txn_ref
+        .txn
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    match txn_guard.as_ref() {
+        Some(txn) => Ok(f(txn, cf_handle)),
+        None => Ok((atoms::error(), atoms::transaction_closed()).encode(env)),
+    }
+}
+
+/// Ends a transaction, closing any iterators that read through it first.
+///
+/// Returns the transaction, or `None` if it already ended.
+fn end_transaction(txn_ref: &TransactionRef) -> NifResult<Option<Transaction<'static, OptimisticTransactionDB>>> {
+    let mut txn_guard = txn_ref
+        .txn
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let txn = match txn_guard.take() {
+        Some(txn) => txn,
+        None => return Ok(None),
+    };
+
+    // Iterators borrow the transaction, so they must go before it does
+    let iterators = std::mem::take(
+        &mut *txn_ref
+            .iterators
+            .lock()
+            .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?,
+    );
+    for iterator in iterators.iter().filter_map(Weak::upgrade) {
+        if let Ok(mut iter_guard) = iterator.lock() {
+            *iter_guard = None;
+        }
+    }
+
+    Ok(Some(txn))
+}
+
+/// Begins an optimistic transaction.
+///
+/// Writes made through the transaction are buffered and only become visible
+/// to other readers once `txn_commit` succeeds. Conflicts are detected at
+/// commit time against writes committed since the transaction began.
+///
+/// The write options apply to the commit, which writes all of the
+/// transaction's changes as one batch.
+///
+/// # Arguments
+/// * `db_ref` - The database reference, opened with `transactional: true`
+/// * `options` - Write options as for `put`: `sync`, `disable_wal`, `no_slowdown`, `low_pri`
+///
+/// # Returns
+/// * `{:ok, txn_ref}` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :not_transactional}` if the database was not opened as transactional
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+#[rustler::nif(schedule = "DirtyCpu")]
+fn transaction_begin<'a>(env: Env<'a>, db_ref: ResourceArc<DbRef>, options: Term<'a>) -> NifResult<Term<'a>> {
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(Database::Transactional(db)) => db,
+        Some(Database::Plain(_)) => return Ok((atoms::error(), atoms::not_transactional()).encode(env)),
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    // Taking a snapshot at begin makes commit reject writes to keys that
+    // changed after the transaction started, not just after they were read
+    let mut txn_opts = OptimisticTransactionOptions::default();
+    txn_opts.set_snapshot(true);
+    let txn = db.transaction_opt(&write_opts, &txn_opts);
+
+    // SAFETY: We keep the DbRef alive via Arc, so the transaction remains valid
+    let static_txn: Transaction<'static, OptimisticTransactionDB> = unsafe {
+        std::mem::transmute(txn)
+    };
+
+    let txn_ref = ResourceArc::new(TransactionRef {
+        txn: Mutex::new(Some(static_txn)),
+        iterators: Mutex::new(Vec::new()),
+        db_ref: Arc::new(db_ref.clone()),
+    });
+
+    Ok((atoms::ok(), txn_ref).encode(env))
+}
+
+/// Gets a value from a column family within a transaction.
+///
+/// Reads see the transaction's own uncommitted writes. With `for_update: true`
+/// the key is also tracked for conflict detection, so the commit fails if
+/// another writer changes it before the transaction commits.
+///
+/// # Arguments
+/// * `txn_ref` - The transaction reference
+/// * `cf` - The column family name as an atom or binary
+/// * `key` - The key as a binary
+/// * `options` - Keyword list or map; supports `for_update: boolean`
+///
+/// # Returns
+/// * `{:ok, value}` if found
+/// * `:not_found` if key doesn't exist
+/// * `{:error, :transaction_closed}` if the transaction has ended
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:invalid_option, key}}` for an unknown or malformed option
+/// * `{:error, {:get_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn txn_get<'a>(
+    env: Env<'a>,
+    txn_ref: ResourceArc<TransactionRef>,
+    cf: Term<'a>,
+    key: Binary<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let pairs = match option_pairs(options) {
+        Ok(pairs) => pairs,
+        Err(term) => return Ok((atoms::error(), (atoms::invalid_option(), term)).encode(env)),
+    };
+
+    let mut for_update = false;
+    for (option, value) in pairs {
+        match value.decode::<bool>() {
+            Ok(flag) if option == atoms::for_update() => for_update = flag,
+            _ => return Ok((atoms::error(), (atoms::invalid_option(), option)).encode(env)),
+        }
+    }
+
+    with_transaction(env, &txn_ref, cf, |txn, cf_handle| {
+        let read_opts = ReadOptions::default();
+        let result = if for_update {
+            txn.get_for_update_cf_opt(cf_handle, key.as_slice(), true, &read_opts)
+        } else {
+            txn.get_cf_opt(cf_handle, key.as_slice(), &read_opts)
+        };
+
+        match result {
+            Ok(Some(value)) => {
+                let mut binary = NewBinary::new(env, value.len());
+                binary.as_mut_slice().copy_from_slice(&value);
+                (atoms::ok(), Binary::from(binary)).encode(env)
+            }
+            Ok(None) => atoms::not_found().encode(env),
+            Err(e) => transaction_error(env, atoms::get_failed(), e),
+        }
+    })
+}
+
+/// Puts a key-value pair into a column family within a transaction.
+///
+/// # Arguments
+/// * `txn_ref` - The transaction reference
+/// * `cf` - The column family name as an atom or binary
+/// * `key` - The key as a binary
+/// * `value` - The value as a binary
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :transaction_closed}` if the transaction has ended
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:put_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn txn_put<'a>(
+    env: Env<'a>,
+    txn_ref: ResourceArc<TransactionRef>,
+    cf: Term<'a>,
+    key: Binary<'a>,
+    value: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    with_transaction(env, &txn_ref, cf, |txn, cf_handle| {
+        match txn.put_cf(cf_handle, key.as_slice(), value.as_slice()) {
+            Ok(()) => atoms::ok().encode(env),
+            Err(e) => transaction_error(env, atoms::put_failed(), e),
+        }
+    })
+}
+
+/// Deletes a key from a column family within a transaction.
+///
+/// # Arguments
+/// * `txn_ref` - The transaction reference
+/// * `cf` - The column family name as an atom or binary
+/// * `key` - The key as a binary
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :transaction_closed}` if the transaction has ended
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:delete_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn txn_delete<'a>(
+    env: Env<'a>,
+    txn_ref: ResourceArc<TransactionRef>,
+    cf: Term<'a>,
+    key: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    with_transaction(env, &txn_ref, cf, |txn, cf_handle| {
+        match txn.delete_cf(cf_handle, key.as_slice()) {
+            Ok(()) => atoms::ok().encode(env),
+            Err(e) => transaction_error(env, atoms::delete_failed(), e),
+        }
+    })
+}
+
+/// Creates a prefix iterator that reads through a transaction.
+///
+/// The iterator sees committed data merged with the transaction's own
+/// uncommitted writes. It is used with the regular `iterator_*` functions and
+/// is closed automatically when the transaction commits or rolls back.
+///
+/// # Arguments
+/// * `txn_ref` - The transaction reference
+/// * `cf` - The column family name as an atom or binary
+/// * `prefix` - The prefix to iterate over
+///
+/// # Returns
+/// * `{:ok, iterator_ref}` on success
+/// * `{:error, :transaction_closed}` if the transaction has ended
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+#[rustler::nif(schedule = "DirtyCpu")]
+fn txn_prefix_iterator<'a>(
+    env: Env<'a>,
+    txn_ref: ResourceArc<TransactionRef>,
+    cf: Term<'a>,
+    prefix: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+    let prefix_bytes = prefix.as_slice().to_vec();
+    let db_ref = txn_ref.db_ref.clone();
+    let read_opts = db_ref.prefix_read_options(&cf_name, &prefix_bytes)?;
+
+    with_transaction(env, &txn_ref, cf, |txn, cf_handle| {
+        let iterator = DbIterator::Transaction(txn.iterator_cf_opt(
+            cf_handle,
+            read_opts,
+            IteratorMode::From(&prefix_bytes, rocksdb::Direction::Forward),
+        ));
+
+        // SAFETY: The TransactionRef Arc keeps the transaction alive, and
+        // ending the transaction closes the iterator first
+        let static_iterator: DbIterator<'static> = unsafe {
+            std::mem::transmute(iterator)
+        };
+        let iterator = Arc::new(Mutex::new(Some(static_iterator)));
+
+        if let Ok(mut iterators) = txn_ref.iterators.lock() {
+            iterators.retain(|weak| weak.strong_count() > 0);
+            iterators.push(Arc::downgrade(&iterator));
+        }
+
+        let iter_ref = ResourceArc::new(IteratorRef {
+            iterator,
+            _db_ref: db_ref.clone(),
+            txn_ref: Some(Arc::new(txn_ref.clone())),
+            prefix: prefix_bytes.clone(),
+            _cf_name: cf_name.to_string(),
+        });
+
+        (atoms::ok(), iter_ref).encode(env)
+    })
+}
+
+/// Commits a transaction.
+///
+/// The transaction ends whether or not the commit succeeds; on conflict the
+/// caller should begin a new transaction and retry.
+///
+/// # Arguments
+/// * `txn_ref` - The transaction reference
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :conflict}` if a key it wrote or read for update was changed by another writer
+/// * `{:error, :transaction_closed}` if the transaction has already ended
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :write_stall}` if begun with `no_slowdown` and the commit would stall
+/// * `{:error, {:commit_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn txn_commit<'a>(env: Env<'a>, txn_ref: ResourceArc<TransactionRef>) -> NifResult<Term<'a>> {
+    let db_guard = txn_ref
+        .db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    if db_guard.is_none() {
+        return Ok((atoms::error(), atoms::already_closed()).encode(env));
+    }
+
+    match end_transaction(&txn_ref)? {
+        Some(txn) => match txn.commit() {
+            Ok(()) => Ok(atoms::ok().encode(env)),
+            Err(e) => Ok(transaction_error(env, atoms::commit_failed(), e)),
+        },
+        None => Ok((atoms::error(), atoms::transaction_closed()).encode(env)),
+    }
+}
+
+/// Rolls back a transaction, discarding all of its writes.
+///
+/// # Arguments
+/// * `txn_ref` - The transaction reference
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :transaction_closed}` if the transaction has already ended
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:rollback_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn txn_rollback<'a>(env: Env<'a>, txn_ref: ResourceArc<TransactionRef>) -> NifResult<Term<'a>> {
+    let db_guard = txn_ref
+        .db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    if db_guard.is_none() {
+        return Ok((atoms::error(), atoms::already_closed()).encode(env));
+    }
+
+    match end_transaction(&txn_ref)? {
+        Some(txn) => match txn.rollback() {
+            Ok(()) => Ok(atoms::ok().encode(env)),
+            Err(e) => Ok((atoms::error(), (atoms::rollback_failed(), e.to_string())).encode(env)),
+        },
+        None => Ok((atoms::error(), atoms::transaction_closed()).encode(env)),
+    }
+}
+
+// ============================================================================
+// Leapfrog Triejoin
+// ============================================================================
+
+/// A triple pattern element after variable names are resolved.
+#[derive(Clone, Copy, PartialEq)]
+enum PatternTerm {
+    /// A bound term ID
+    Bound(u64),
+    /// A variable, as its position in the join's variable order
+    Var(usize),
+}
+
+/// Returns the distinct variables of a pattern in join order.
+fn pattern_vars(pattern: &[PatternTerm; 3]) -> Vec<usize> {
+    let mut vars: Vec<usize> = pattern
+        .iter()
+        .filter_map(|term| match term {
+            PatternTerm::Var(var) => Some(*var),
+            PatternTerm::Bound(_) => None,
+        })
+        .collect();
+    vars.sort_unstable();
+    vars.dedup();
+    vars
+}
+
+/// Reads the term ID at component `index` of an index key.
+fn key_component(key: &[u8], index: usize) -> Option<u64> {
+    let bytes = key.get(index * TERM_ID_SIZE..(index + 1) * TERM_ID_SIZE)?;
+    Some(u64::from_be_bytes(bytes.try_into().ok()?))
+}
+
+/// Trie view of the index keys that start with a prefix of bound term IDs.
+///
+/// Each level of the trie is the next 64-bit key component. The keys of the
+/// open levels are kept in `keys`, so moving back up never touches RocksDB.
+struct IndexTrie {
+    iterator: DbRawIterator<'static>,
+    /// Number of bound components before the first trie level
+    bound_components: usize,
+    /// Key bytes of the bound components
+    prefix: Vec<u8>,
+    /// Current key at each open level
+    keys: Vec<u64>,
+    /// Whether the iterator is on the first key under the current keys
+    positioned: bool,
+    at_end: bool,
+}
+
+impl IndexTrie {
+    /// Returns the bound prefix followed by the keys of the first `depth` levels.
+    fn path(&self, depth: usize) -> Vec<u8> {
+        let mut path = self.prefix.clone();
+        for key in &self.keys[..depth] {
+            path.extend_from_slice(&key.to_be_bytes());
+        }
+        path
+    }
+
+    fn open(&mut self) {
+        // Deeper levels may have moved the iterator since this path was reached
+        if !self.positioned {
+            let path = self.path(self.keys.len());
+            self.iterator.seek(&path);
+            self.positioned = true;
+        }
+
+        // The iterator is on the first key under the current path, so the new
+        // level starts at that key's next component
+        let path_len = self.prefix.len() + self.keys.len() * TERM_ID_SIZE;
+        let index = self.bound_components + self.keys.len();
+        let component = match self.iterator.key() {
+            Some(key) if key.len() >= path_len => key_component(key, index),
+            _ => None,
+        };
+        self.keys.push(component.unwrap_or(0));
+        self.at_end = component.is_none();
+    }
+
+    fn up(&mut self) {
+        self.keys.pop();
+        self.positioned = false;
+        self.at_end = false;
+    }
+
+    fn seek(&mut self, target: u64) {
+        let depth = self.keys.len();
+        let mut seek_key = self.path(depth - 1);
+        let parent_len = seek_key.len();
+        seek_key.extend_from_slice(&target.to_be_bytes());
+
+        self.iterator.seek(&seek_key);
+        self.positioned = true;
+
+        let index = self.bound_components + depth - 1;
+        let component = match self.iterator.key() {
+            Some(key) if key.starts_with(&seek_key[..parent_len]) => key_component(key, index),
+            _ => None,
+        };
+        match component {
+            Some(component) => self.keys[depth - 1] = component,
+            None => self.at_end = true,
+        }
+    }
+}
+
+/// Trie over sorted, deduplicated tuples held in memory.
+///
+/// Used for patterns whose variable order is not the key order of any index.
+struct TupleTrie {
+    tuples: Vec<Vec<u64>>,
+    /// Current position and exclusive end of each open level
+    levels: Vec<(usize, usize)>,
+}
+
+impl TupleTrie {
+    fn open(&mut self) {
+        let (start, end) = match self.levels.last() {
+            None => (0, self.tuples.len()),
+            Some(&(pos, end)) => {
+                let depth = self.levels.len();
+                let key = self.tuples[pos][depth - 1];
+                let run_end = pos + self.tuples[pos..end].partition_point(|t| t[depth - 1] == key);
+                (pos, run_end)
+            }
+        };
+        self.levels.push((start, end));
+    }
+
+    fn seek_while(&mut self, before: impl Fn(u64) -> bool) {
+        let depth = self.levels.len();
+        let (pos, end) = self.levels[depth - 1];
+        let offset = self.tuples[pos..end].partition_point(|t| before(t[depth - 1]));
+        self.levels[depth - 1].0 = pos + offset;
+    }
+}
+
+/// Trie iterator over the matches of one triple pattern.
+enum TrieIterator {
+    Index(IndexTrie),
+    Tuples(TupleTrie),
+}
+
+impl TrieIterator {
+    fn open(&mut self) {
+        match self {
+            TrieIterator::Index(trie) => trie.open(),
+            TrieIterator::Tuples(trie) => trie.open(),
+        }
+    }
+
+    fn up(&mut self) {
+        match self {
+            TrieIterator::Index(trie) => trie.up(),
+            TrieIterator::Tuples(trie) => {
+                trie.levels.pop();
+            }
+        }
+    }
+
+    fn key(&self) -> u64 {
+        match self {
+            TrieIterator::Index(trie) => trie.keys[trie.keys.len() - 1],
+            TrieIterator::Tuples(trie) => {
+                let depth = trie.levels.len();
+                trie.tuples[trie.levels[depth - 1].0][depth - 1]
+            }
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        match self {
+            TrieIterator::Index(trie) => trie.at_end,
+            TrieIterator::Tuples(trie) => {
+                let (pos, end) = trie.levels[trie.levels.len() - 1];
+                pos >= end
+            }
+        }
+    }
+
+    /// Moves to the next key at the current level.
+    fn next(&mut self) {
+        let key = self.key();
+        match self {
+            TrieIterator::Index(trie) => match key.checked_add(1) {
+                Some(target) => trie.seek(target),
+                None => trie.at_end = true,
+            },
+            TrieIterator::Tuples(trie) => trie.seek_while(|k| k <= key),
+        }
+    }
+
+    /// Moves to the first key >= `target` at the current level.
+    fn seek(&mut self, target: u64) {
+        if self.key() >= target {
+            return;
+        }
+        match self {
+            TrieIterator::Index(trie) => trie.seek(target),
+            TrieIterator::Tuples(trie) => trie.seek_while(|k| k < target),
+        }
+    }
+
+    fn status(&self) -> Result<(), rocksdb::Error> {
+        match self {
+            TrieIterator::Index(trie) => trie.iterator.status(),
+            TrieIterator::Tuples(_) => Ok(()),
+        }
+    }
+}
+
+/// Where a join resumes on the next call.
+#[derive(Clone, Copy)]
+enum JoinStep {
+    /// Open the tries of a variable and find its first common key
+    Open(usize),
+    /// Find the next common key of a variable
+    Next(usize),
+    /// Close the tries of a variable and continue with the one above
+    Up(usize),
+    Done,
+}
+
+/// Resumable Leapfrog Triejoin over one trie per triple pattern.
+struct LeapfrogJoin {
+    tries: Vec<TrieIterator>,
+    /// Indexes into `tries` of the tries containing each variable, in
+    /// leapfrog order while that variable is open
+    participants: Vec<Vec<usize>>,
+    /// Leapfrog position in `participants` for each variable
+    positions: Vec<usize>,
+    /// Current binding of each variable
+    binding: Vec<u64>,
+    step: JoinStep,
+}
+
+impl LeapfrogJoin {
+    fn new(tries: Vec<TrieIterator>, participants: Vec<Vec<usize>>) -> Self {
+        let num_vars = participants.len();
+        LeapfrogJoin {
+            tries,
+            participants,
+            positions: vec![0; num_vars],
+            binding: vec![0; num_vars],
+            step: JoinStep::Open(0),
+        }
+    }
+
+    /// A join that produces no rows.
+    fn empty() -> Self {
+        let mut join = LeapfrogJoin::new(Vec::new(), Vec::new());
+        join.step = JoinStep::Done;
+        join
+    }
+
+    /// Produces up to `n` binding rows, returning whether the join is exhausted.
+    fn next_rows(&mut self, n: usize, rows: &mut Vec<Vec<u64>>) -> Result<bool, rocksdb::Error> {
+        let num_vars = self.participants.len();
+
+        while rows.len() < n {
+            let found = match self.step {
+                JoinStep::Done => break,
+                JoinStep::Open(_) if num_vars == 0 => {
+                    // Only bound patterns, all of which matched
+                    rows.push(Vec::new());
+                    self.step = JoinStep::Done;
+                    continue;
+                }
+                JoinStep::Open(var) => self.open(var),
+                JoinStep::Next(var) => self.leapfrog_next(var),
+                JoinStep::Up(var) => {
+                    for &trie in &self.participants[var] {
+                        self.tries[trie].up();
+                    }
+                    self.step = if var == 0 { JoinStep::Done } else { JoinStep::Next(var - 1) };
+                    continue;
+                }
+            };
+
+            let var = match self.step {
+                JoinStep::Open(var) | JoinStep::Next(var) => var,
+                _ => unreachable!(),
+            };
+
+            self.step = match found {
+                Some(key) => {
+                    self.binding[var] = key;
+                    if var + 1 == num_vars {
+                        rows.push(self.binding.clone());
+                        JoinStep::Next(var)
+                    } else {
+                        JoinStep::Open(var + 1)
+                    }
+                }
+                None => JoinStep::Up(var),
+            };
+        }
+
+        for trie in &self.tries {
+            trie.status()?;
+        }
+
+        Ok(matches!(self.step, JoinStep::Done))
+    }
+
+    fn open(&mut self, var: usize) -> Option<u64> {
+        for &trie in &self.participants[var] {
+            self.tries[trie].open();
+        }
+        if self.participants[var].iter().any(|&trie| self.tries[trie].at_end()) {
+            return None;
+        }
+
+        let tries = &self.tries;
+        self.participants[var].sort_by_key(|&trie| tries[trie].key());
+        self.positions[var] = 0;
+        self.leapfrog_search(var)
+    }
+
+    fn leapfrog_next(&mut self, var: usize) -> Option<u64> {
+        let trie = self.participants[var][self.positions[var]];
+        self.tries[trie].next();
+        if self.tries[trie].at_end() {
+            return None;
+        }
+        self.positions[var] = (self.positions[var] + 1) % self.participants[var].len();
+        self.leapfrog_search(var)
+    }
+
+    /// Seeks the lowest trie to the highest key until all tries agree.
+    fn leapfrog_search(&mut self, var: usize) -> Option<u64> {
+        let count = self.participants[var].len();
+        let mut pos = self.positions[var];
+        let mut max_key = self.tries[self.participants[var][(pos + count - 1) % count]].key();
+
+        loop {
+            let trie = self.participants[var][pos];
+            if self.tries[trie].key() == max_key {
+                self.positions[var] = pos;
+                return Some(max_key);
+            }
+            self.tries[trie].seek(max_key);
+            if self.tries[trie].at_end() {
+                self.positions[var] = pos;
+                return None;
+            }
+            max_key = self.tries[trie].key();
+            pos = (pos + 1) % count;
+        }
+    }
+}
+
+/// Decodes a pattern element: `{:bound, id}` or `{:var, name}` with `name`
+/// listed in `variable_order`.
+fn decode_pattern_term<'a>(term: Term<'a>, variable_order: &[Term<'a>]) -> Option<PatternTerm> {
+    let tuple = rustler::types::tuple::get_tuple(term).ok()?;
+    if tuple.len() != 2 {
+        return None;
+    }
+    let tag: rustler::Atom = tuple[0].decode().ok()?;
+    if tag == atoms::bound() {
+        tuple[1].decode().ok().map(PatternTerm::Bound)
+    } else if tag == atoms::var() {
+        variable_order.iter().position(|name| *name == tuple[1]).map(PatternTerm::Var)
+    } else {
+        None
+    }
+}
+
+/// Decodes the `{s, p, o}` pattern specs of a join.
+fn decode_join_patterns<'a>(
+    patterns: Term<'a>,
+    variable_order: &[Term<'a>],
+) -> Result<Vec<[PatternTerm; 3]>, Term<'a>> {
+    let env = patterns.get_env();
+    let invalid = |term: Term<'a>| (atoms::error(), (atoms::invalid_pattern(), term)).encode(env);
+
+    let iter: ListIterator = patterns.decode().map_err(|_| invalid(patterns))?;
+    let mut decoded = Vec::new();
+
+    for pattern in iter {
+        let elements = rustler::types::tuple::get_tuple(pattern).map_err(|_| invalid(pattern))?;
+        if elements.len() != 3 {
+            return Err(invalid(pattern));
+        }
+        let mut terms = [PatternTerm::Bound(0); 3];
+        for (slot, element) in terms.iter_mut().zip(elements) {
+            *slot = decode_pattern_term(element, variable_order).ok_or_else(|| invalid(pattern))?;
+        }
+        decoded.push(terms);
+    }
+
+    if decoded.is_empty() {
+        return Err(invalid(patterns));
+    }
+
+    Ok(decoded)
+}
+
+/// Builds the trie for one pattern, or `None` if the pattern has no matches.
+///
+/// Patterns whose variables appear in join order in one of the indices are
+/// read straight from that index. Otherwise the matches are read from the
+/// index with the bound terms as prefix, reordered and sorted in memory.
+fn build_pattern_trie(
+    db: &Database,
+    prefix_lengths: &HashMap<String, usize>,
+    snapshot: Option<&DbSnapshot<'static>>,
+    pattern: &[PatternTerm; 3],
+) -> Result<Option<TrieIterator>, rocksdb::Error> {
+    let distinct_vars = pattern_vars(pattern);
+
+    // Bound terms first, then variables in join order
+    let bound_first = |layout: &[usize; 3]| {
+        let terms: Vec<PatternTerm> = layout.iter().map(|&pos| pattern[pos]).collect();
+        let split = terms.iter().position(|t| matches!(t, PatternTerm::Var(_))).unwrap_or(3);
+        let tail_is_vars = terms[split..].iter().all(|t| matches!(t, PatternTerm::Var(_)));
+        (tail_is_vars, split, terms)
+    };
+
+    let direct = TRIPLE_INDICES.iter().find(|(_, layout)| {
+        let (tail_is_vars, split, terms) = bound_first(layout);
+        let order: Vec<usize> = terms[split..]
+            .iter()
+            .filter_map(|t| match t {
+                PatternTerm::Var(var) => Some(*var),
+                PatternTerm::Bound(_) => None,
+            })
+            .collect();
+        tail_is_vars && order == distinct_vars
+    });
+    let (cf_name, layout) = match direct {
+        Some(&(cf_name, layout)) => (cf_name, layout),
+        None => *TRIPLE_INDICES
+            .iter()
+            .find(|(_, layout)| bound_first(layout).0)
+            .expect("every pattern has an index with its bound terms first"),
+    };
+
+    let (_, split, terms) = bound_first(&layout);
+    let mut prefix = Vec::with_capacity(split * TERM_ID_SIZE);
+    for term in &terms[..split] {
+        if let PatternTerm::Bound(id) = term {
+            prefix.extend_from_slice(&id.to_be_bytes());
+        }
+    }
+
+    let cf_handle = match db.cf_handle(cf_name) {
+        Some(cf) => cf,
+        None => return Ok(None),
+    };
+    let mut read_opts = prefix_read_options(prefix_lengths, cf_name, &prefix);
+    if let Some(snapshot) = snapshot {
+        snapshot.apply(&mut read_opts);
+    }
+
+    let mut iterator = db.raw_iterator_cf_opt(&cf_handle, read_opts);
+    iterator.seek(&prefix);
+    let matched = iterator.valid() && iterator.key().is_some_and(|key| key.starts_with(&prefix));
+    iterator.status()?;
+    if !matched {
+        return Ok(None);
+    }
+
+    if direct.is_some() {
+        // SAFETY: The join source keeps the database or snapshot alive, so the iterator remains valid
+        let iterator: DbRawIterator<'static> = unsafe { std::mem::transmute(iterator) };
+        return Ok(Some(TrieIterator::Index(IndexTrie {
+            iterator,
+            bound_components: split,
+            prefix,
+            keys: Vec::new(),
+            positioned: true,
+            at_end: false,
+        })));
+    }
+
+    // Key component index of each variable occurrence
+    let mut columns: Vec<(usize, usize)> = Vec::new();
+    for (index, term) in terms.iter().enumerate() {
+        if let PatternTerm::Var(var) = term {
+            columns.push((*var, index));
+        }
+    }
+
+    let mut tuples = Vec::new();
+    while iterator.valid() {
+        let key = match iterator.key() {
+            Some(key) if key.starts_with(&prefix) => key,
+            _ => break,
+        };
+        let mut values = [0u64; 3];
+        let mut complete = true;
+        for (index, value) in values.iter_mut().enumerate() {
+            match key_component(key, index) {
+                Some(component) => *value = component,
+                None => complete = false,
+            }
+        }
+        // A variable repeated in the pattern must bind the same ID everywhere
+        let consistent = columns.iter().all(|&(var, index)| {
+            columns.iter().all(|&(other, other_index)| other != var || values[other_index] == values[index])
+        });
+        if complete && consistent {
+            tuples.push(
+                distinct_vars
+                    .iter()
+                    .map(|var| {
+                        let index = columns.iter().find(|(v, _)| v == var).map(|(_, i)| *i).unwrap_or(0);
+                        values[index]
+                    })
+                    .collect::<Vec<u64>>(),
+            );
+        }
+        iterator.next();
+    }
+    iterator.status()?;
+
+    tuples.sort_unstable();
+    tuples.dedup();
+    if tuples.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(TrieIterator::Tuples(TupleTrie {
+        tuples,
+        levels: Vec::new(),
+    })))
+}
+
+/// Builds a join over `patterns` with `num_vars` variables.
+fn build_leapfrog_join(
+    db: &Database,
+    prefix_lengths: &HashMap<String, usize>,
+    snapshot: Option<&DbSnapshot<'static>>,
+    patterns: &[[PatternTerm; 3]],
+    num_vars: usize,
+) -> Result<LeapfrogJoin, rocksdb::Error> {
+    let mut tries = Vec::new();
+    let mut participants = vec![Vec::new(); num_vars];
+
+    for pattern in patterns {
+        let trie = match build_pattern_trie(db, prefix_lengths, snapshot, pattern)? {
+            Some(trie) => trie,
+            None => return Ok(LeapfrogJoin::empty()),
+        };
+
+        // Fully bound patterns only need to exist
+        let vars = pattern_vars(pattern);
+        if vars.is_empty() {
+            continue;
+        }
+        for var in vars {
+            participants[var].push(tries.len());
+        }
+        tries.push(trie);
+    }
+
+    Ok(LeapfrogJoin::new(tries, participants))
+}
+
+/// Starts a Leapfrog Triejoin over the triple indices.
+///
+/// Each pattern is a `{s, p, o}` tuple whose elements are `{:bound, id}` or
+/// `{:var, name}`. The join opens one RocksDB iterator per pattern and
+/// intersects them one variable at a time in `variable_order`, so only
+/// matching bindings cross the NIF boundary. Rows are read with
+/// `leapfrog_join_next`.
+///
+/// # Arguments
+/// * `source` - A database or snapshot reference
+/// * `patterns` - List of `{s, p, o}` pattern specs
+/// * `variable_order` - List of variable names, each used by some pattern
+///
+/// # Returns
+/// * `{:ok, join_ref}` on success
+/// * `{:error, {:invalid_pattern, pattern}}` if a pattern is malformed or uses an unknown variable
+/// * `{:error, {:invalid_variable, name}}` if a variable is repeated in the order or unused
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :snapshot_released}` if snapshot was released
+/// * `{:error, {:iterator_failed, reason}}` on read errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn leapfrog_join<'a>(
+    env: Env<'a>,
+    source: Term<'a>,
+    patterns: Term<'a>,
+    variable_order: Vec<Term<'a>>,
+) -> NifResult<Term<'a>> {
+    for (index, name) in variable_order.iter().enumerate() {
+        if variable_order[..index].contains(name) {
+            return Ok((atoms::error(), (atoms::invalid_variable(), *name)).encode(env));
+        }
+    }
+
+    let patterns = match decode_join_patterns(patterns, &variable_order) {
+        Ok(patterns) => patterns,
+        Err(error) => return Ok(error),
+    };
+
+    for (var, name) in variable_order.iter().enumerate() {
+        let used = patterns.iter().any(|pattern| pattern.contains(&PatternTerm::Var(var)));
+        if !used {
+            return Ok((atoms::error(), (atoms::invalid_variable(), *name)).encode(env));
+        }
+    }
+
+    let num_vars = variable_order.len();
+
+    let (join, join_source) = if let Ok(db_ref) = source.decode::<ResourceArc<DbRef>>() {
+        let db_guard = db_ref
+            .db
+            .read()
+            .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+        let db = match db_guard.as_ref() {
+            Some(db) => db,
+            None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+        };
+
+        let join = build_leapfrog_join(db, &*db_ref.prefix_lengths()?, None, &patterns, num_vars);
+        (join, ReadSource::Db { _db_ref: Arc::new(db_ref.clone()) })
+    } else {
+        let snapshot_ref: ResourceArc<SnapshotRef> = source.decode()?;
+
+        let snap_guard = snapshot_ref
+            .snapshot
+            .lock()
+            .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+        let snapshot = match snap_guard.as_ref() {
+            Some(snap) => snap,
+            None => return Ok((atoms::error(), atoms::snapshot_released()).encode(env)),
+        };
+
+        let db_guard = snapshot_ref.db_ref
+            .db
+            .read()
+            .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+        let db = match db_guard.as_ref() {
+            Some(db) => db,
+            None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+        };
+
+        let join = build_leapfrog_join(db, &*snapshot_ref.db_ref.prefix_lengths()?, Some(snapshot), &patterns, num_vars);
+        (join, ReadSource::Snapshot { _snapshot_ref: Arc::new(snapshot_ref.clone()) })
+    };
+
+    match join {
+        Ok(join) => {
+            let join_ref = ResourceArc::new(LeapfrogRef {
+                join: Mutex::new(Some(join)),
+                _source: join_source,
+            });
+            Ok((atoms::ok(), join_ref).encode(env))
+        }
+        Err(e) => Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Gets up to `n` binding rows from a leapfrog join.
+///
+/// Each row is a list of term IDs, one per variable in the join's variable
+/// order. Rows are produced in ascending order of their bindings.
+///
+/// # Arguments
+/// * `join_ref` - The join reference
+/// * `n` - Maximum number of rows to return
+///
+/// # Returns
+/// * `{:ok, rows, :more}` if `n` rows were produced and more may follow
+/// * `{:ok, rows, :end}` if the join is exhausted
+/// * `{:error, :iterator_closed}` if the join was closed
+/// * `{:error, {:iterator_failed, reason}}` on read errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn leapfrog_join_next<'a>(
+    env: Env<'a>,
+    join_ref: ResourceArc<LeapfrogRef>,
+    n: usize,
+) -> NifResult<Term<'a>> {
+    let mut join_guard = join_ref
+        .join
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let join = match join_guard.as_mut() {
+        Some(join) => join,
+        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    };
+
+    let mut rows = Vec::with_capacity(n.min(4096));
+    match join.next_rows(n, &mut rows) {
+        Ok(true) => Ok((atoms::ok(), rows, atoms::end()).encode(env)),
+        Ok(false) => Ok((atoms::ok(), rows, atoms::more()).encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Closes a leapfrog join and releases its iterators.
+///
+/// # Arguments
+/// * `join_ref` - The join reference
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :iterator_closed}` if already closed
+#[rustler::nif]
+fn leapfrog_join_close<'a>(env: Env<'a>, join_ref: ResourceArc<LeapfrogRef>) -> NifResult<Term<'a>> {
+    let mut join_guard = join_ref
+        .join
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    if join_guard.is_none() {
+        return Ok((atoms::error(), atoms::iterator_closed()).encode(env));
+    }
+
+    *join_guard = None;
+
+    Ok(atoms::ok().encode(env))
+}
+
+// ============================================================================
+// Pattern Lookup
+// ============================================================================
+
+/// Reads the triples matching a `{s, p, o}` pattern from one index.
+struct PatternLookup {
+    iterator: DbRawIterator<'static>,
+    /// Key prefix built from the scope and the pattern's leading bound terms
+    prefix: Vec<u8>,
+    /// Length of the scope (e.g. a graph ID) that precedes the triple in every key
+    scope_len: usize,
+    /// Key layout of the index as positions in `(s, p, o)`
+    layout: [usize; 3],
+    /// Bound term IDs in `(s, p, o)` order, checked against every key
+    pattern: [Option<u64>; 3],
+    at_end: bool,
+}
+
+impl PatternLookup {
+    /// Appends up to `n` matching triples in `(s, p, o)` order.
+    /// Returns `true` once the lookup is exhausted.
+    fn next_triples(&mut self, n: usize, triples: &mut Vec<(u64, u64, u64)>) -> Result<bool, rocksdb::Error> {
+        while !self.at_end && triples.len() < n {
+            let key = match self.iterator.key() {
+                Some(key) if self.iterator.valid() && key.starts_with(&self.prefix) => key,
+                _ => {
+                    self.iterator.status()?;
+                    self.at_end = true;
+                    break;
+                }
+            };
+
+            if let Some(triple) = self.decode(key) {
+                triples.push(triple);
+            }
+            self.iterator.next();
+        }
+
+        Ok(self.at_end)
+    }
+
+    /// Reorders an index key to `(s, p, o)`, or returns `None` if the key is
+    /// malformed or fails a bound term the index prefix does not cover.
+    fn decode(&self, key: &[u8]) -> Option<(u64, u64, u64)> {
+        if key.len() != self.scope_len + TRIPLE_RECORD_SIZE {
+            return None;
+        }
+
+        let record = &key[self.scope_len..];
+        let mut triple = [0u64; 3];
+        for (index, &position) in self.layout.iter().enumerate() {
+            triple[position] = key_component(record, index)?;
+        }
+
+        let matches = self
+            .pattern
+            .iter()
+            .zip(&triple)
+            .all(|(bound, id)| bound.is_none_or(|bound| bound == *id));
+        matches.then_some((triple[0], triple[1], triple[2]))
+    }
+}
+
+/// Decodes a `{s, p, o}` lookup pattern whose elements are term IDs or `nil`.
+fn decode_lookup_pattern(pattern: Term) -> Option<[Option<u64>; 3]> {
+    let terms = rustler::types::tuple::get_tuple(pattern).ok()?;
+    if terms.len() != 3 {
+        return None;
+    }
+
+    Some([
+        terms[0].decode().ok()?,
+        terms[1].decode().ok()?,
+        terms[2].decode().ok()?,
+    ])
+}
+
+/// Selects the index whose key order puts the most bound terms first, and
+/// builds the key prefix from those terms. Ties go to the earliest index, so
+/// a pattern with no bound terms scans SPO.
+fn select_lookup_index(
+    indices: &'static [(&'static str, [usize; 3])],
+    pattern: &[Option<u64>; 3],
+) -> (&'static str, [usize; 3], Vec<u8>) {
+    let leading_bound = |layout: &[usize; 3]| layout.iter().map_while(|&position| pattern[position]).count();
+
+    // `max_by_key` returns the last maximum, so search in reverse
+    let &(cf_name, layout) = indices
+        .iter()
+        .rev()
+        .max_by_key(|(_, layout)| leading_bound(layout))
+        .expect("at least one index");
+
+    let prefix = layout
+        .iter()
+        .map_while(|&position| pattern[position])
+        .flat_map(u64::to_be_bytes)
+        .collect();
+
+    (cf_name, layout, prefix)
+}
+
+/// Opens a pattern lookup over the index selected from `indices`.
+///
+/// Every key of the index starts with `scope`, which is empty for the triple
+/// indices and the graph ID for the graph indices.
+fn open_pattern_lookup<'a>(
+    env: Env<'a>,
+    db: &Database,
+    prefix_lengths: &HashMap<String, usize>,
+    snapshot: Option<&DbSnapshot<'static>>,
+    indices: &'static [(&'static str, [usize; 3])],
+    pattern: [Option<u64>; 3],
+    scope: &[u8],
+) -> Result<PatternLookup, Term<'a>> {
+    let (cf_name, layout, triple_prefix) = select_lookup_index(indices, &pattern);
+    let prefix = [scope, &triple_prefix].concat();
+
+    let cf_handle = match db.cf_handle(cf_name) {
+        Some(cf) => cf,
+        None => return Err((atoms::invalid_cf(), cf_name).encode(env)),
+    };
+    let mut read_opts = prefix_read_options(prefix_lengths, cf_name, &prefix);
+    if let Some(snapshot) = snapshot {
+        snapshot.apply(&mut read_opts);
+    }
+
+    let mut iterator = db.raw_iterator_cf_opt(&cf_handle, read_opts);
+    iterator.seek(&prefix);
+    // SAFETY: The read source keeps the database or snapshot alive, so the iterator remains valid
+    let iterator: DbRawIterator<'static> = unsafe { std::mem::transmute(iterator) };
+
+    Ok(PatternLookup {
+        iterator,
+        prefix,
+        scope_len: scope.len(),
+        layout,
+        pattern,
+        at_end: false,
+    })
+}
+
+/// Starts a lookup of the triples matching a pattern.
+///
+/// Selects the index whose key order puts the pattern's bound terms first,
+/// scans the matching key prefix, and decodes each key back to `{s, p, o}`,
+/// filtering on any bound term the prefix does not cover (e.g. the predicate
+/// of `{s, nil, o}`). Triples are read with `lookup_pattern_next`.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `pattern` - `{s, p, o}` tuple of term IDs or `nil` for unbound positions
+/// * `options` - Keyword list; `target: :derived` looks up the `derived`
+///   column family instead of the three indices
+///
+/// # Returns
+/// * `{:ok, lookup_ref}` on success
+/// * `{:error, {:invalid_pattern, pattern}}` if the pattern is malformed
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :already_closed}` if database is closed
+#[rustler::nif(schedule = "DirtyCpu")]
+fn lookup_pattern<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    pattern: Term<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let indices = match decode_triple_target(env, options, None) {
+        Ok(indices) => indices,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let bound = match decode_lookup_pattern(pattern) {
+        Some(bound) => bound,
+        None => return Ok((atoms::error(), (atoms::invalid_pattern(), pattern)).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    match open_pattern_lookup(env, db, &*db_ref.prefix_lengths()?, None, indices, bound, &[]) {
+        Ok(lookup) => {
+            let lookup_ref = ResourceArc::new(PatternLookupRef {
+                lookup: Mutex::new(Some(lookup)),
+                _source: ReadSource::Db { _db_ref: Arc::new(db_ref.clone()) },
+            });
+            Ok((atoms::ok(), lookup_ref).encode(env))
+        }
+        Err(reason) => Ok((atoms::error(), reason).encode(env)),
+    }
+}
+
+/// Starts a lookup of the triples matching a pattern using a snapshot.
+///
+/// Snapshot counterpart of `lookup_pattern`.
+///
+/// # Arguments
+/// * `snapshot_ref` - The snapshot reference
+/// * `pattern` - `{s, p, o}` tuple of term IDs or `nil` for unbound positions
+/// * `options` - Keyword list; `target: :derived` looks up the `derived`
+///   column family instead of the three indices
+///
+/// # Returns
+/// * `{:ok, lookup_ref}` on success
+/// * `{:error, {:invalid_pattern, pattern}}` if the pattern is malformed
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :snapshot_released}` if snapshot was released
+/// * `{:error, :already_closed}` if database is closed
+#[rustler::nif(schedule = "DirtyCpu")]
+fn snapshot_lookup_pattern<'a>(
+    env: Env<'a>,
+    snapshot_ref: ResourceArc<SnapshotRef>,
+    pattern: Term<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let indices = match decode_triple_target(env, options, None) {
+        Ok(indices) => indices,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let bound = match decode_lookup_pattern(pattern) {
+        Some(bound) => bound,
+        None => return Ok((atoms::error(), (atoms::invalid_pattern(), pattern)).encode(env)),
+    };
+
+    let snap_guard = snapshot_ref
+        .snapshot
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let snapshot = match snap_guard.as_ref() {
+        Some(snap) => snap,
+        None => return Ok((atoms::error(), atoms::snapshot_released()).encode(env)),
+    };
+
+    let db_guard = snapshot_ref.db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    match open_pattern_lookup(env, db, &*snapshot_ref.db_ref.prefix_lengths()?, Some(snapshot), indices, bound, &[]) {
+        Ok(lookup) => {
+            let lookup_ref = ResourceArc::new(PatternLookupRef {
+                lookup: Mutex::new(Some(lookup)),
+                _source: ReadSource::Snapshot { _snapshot_ref: Arc::new(snapshot_ref.clone()) },
+            });
+            Ok((atoms::ok(), lookup_ref).encode(env))
+        }
+        Err(reason) => Ok((atoms::error(), reason).encode(env)),
+    }
+}
+
+/// Gets up to `n` matching triples from a pattern lookup.
+///
+/// Triples are returned as `{s, p, o}` tuples of term IDs, in the key order
+/// of the selected index.
+///
+/// # Arguments
+/// * `lookup_ref` - The lookup reference
+/// * `n` - Maximum number of triples to return
+///
+/// # Returns
+/// * `{:ok, triples, :more}` if `n` triples were read and more may follow
+/// * `{:ok, triples, :end}` if the lookup is exhausted
+/// * `{:error, :iterator_closed}` if the lookup was closed
+/// * `{:error, {:iterator_failed, reason}}` on read errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn lookup_pattern_next<'a>(
+    env: Env<'a>,
+    lookup_ref: ResourceArc<PatternLookupRef>,
+    n: usize,
+) -> NifResult<Term<'a>> {
+    let mut lookup_guard = lookup_ref
+        .lookup
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let lookup = match lookup_guard.as_mut() {
+        Some(lookup) => lookup,
+        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    };
+
+    let mut triples = Vec::with_capacity(n.min(4096));
+    match lookup.next_triples(n, &mut triples) {
+        Ok(true) => Ok((atoms::ok(), triples, atoms::end()).encode(env)),
+        Ok(false) => Ok((atoms::ok(), triples, atoms::more()).encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Closes a pattern lookup and releases its iterator.
+///
+/// # Arguments
+/// * `lookup_ref` - The lookup reference
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :iterator_closed}` if already closed
+#[rustler::nif]
+fn lookup_pattern_close<'a>(env: Env<'a>, lookup_ref: ResourceArc<PatternLookupRef>) -> NifResult<Term<'a>> {
+    let mut lookup_guard = lookup_ref
+        .lookup
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    if lookup_guard.is_none() {
+        return Ok((atoms::error(), atoms::iterator_closed()).encode(env));
+    }
+
+    *lookup_guard = None;
+
+    Ok(atoms::ok().encode(env))
+}
+
+// ============================================================================
+// Backup Operations
+// ============================================================================
+
+/// Metadata of one backup in a backup directory, encoded as an Elixir map.
+#[derive(rustler::NifMap)]
+struct BackupInfo {
+    id: u32,
+    timestamp: i64,
+    size: u64,
+    num_files: u32,
+}
+
+/// Opens the BackupEngine for a backup directory, creating it if needed.
+fn open_backup_engine(backup_dir: &str, max_background_operations: Option<i32>) -> Result<BackupEngine, Error> {
+    let mut backup_opts = BackupEngineOptions::new(backup_dir)?;
+    if let Some(operations) = max_background_operations {
+        backup_opts.set_max_background_operations(operations);
+    }
+    BackupEngine::open(&backup_opts, &rocksdb::Env::new()?)
+}
+
+/// Creates a checkpoint: an openable copy of the database in `dir`.
+///
+/// SST files are hard-linked when `dir` is on the same filesystem, so a
+/// checkpoint is cheap to take while the database is in use.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `dir` - Directory to create; it must not already exist
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:checkpoint_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_checkpoint<'a>(env: Env<'a>, db_ref: ResourceArc<DbRef>, dir: String) -> NifResult<Term<'a>> {
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let result = with_database!(db, db => Checkpoint::new(db).and_then(|checkpoint| checkpoint.create_checkpoint(&dir)));
+
+    match result {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::checkpoint_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Creates a new backup of the database in a backup directory.
+///
+/// Backups are incremental: SST files already present in the directory are
+/// shared rather than copied again.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `backup_dir` - The backup directory, created if it does not exist
+/// * `options` - Keyword list or map; supports `flush: boolean` to flush the
+///   memtables first instead of backing up the WAL, and
+///   `max_background_operations: integer` for parallel file copies
+///
+/// # Returns
+/// * `{:ok, backup_id}` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, {:backup_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyIo")]
+fn backup_create<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    backup_dir: String,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let pairs = match option_pairs(options) {
+        Ok(pairs) => pairs,
+        Err(term) => return Ok((atoms::error(), (atoms::invalid_option(), term)).encode(env)),
+    };
+
+    let mut flush = false;
+    let mut max_background_operations = None;
+    for (key, value) in pairs {
+        let valid = if key == atoms::flush() {
+            value.decode().map(|flag| flush = flag).is_ok()
+        } else if key == atoms::max_background_operations() {
+            value.decode().map(|count| max_background_operations = Some(count)).is_ok()
+        } else {
+            false
+        };
+        if !valid {
+            return Ok((atoms::error(), (atoms::invalid_option(), key)).encode(env));
+        }
+    }
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    let result = open_backup_engine(&backup_dir, max_background_operations).and_then(|mut engine| {
+        with_database!(db, db => engine.create_new_backup_flush(db, flush))?;
+        Ok(engine.get_backup_info().iter().map(|info| info.backup_id).max().unwrap_or_default())
+    });
+
+    match result {
+        Ok(backup_id) => Ok((atoms::ok(), backup_id).encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::backup_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Lists the backups in a backup directory, oldest first.
+///
+/// # Arguments
+/// * `backup_dir` - The backup directory
+///
+/// # Returns
+/// * `{:ok, [%{id, timestamp, size, num_files}]}` on success
+/// * `{:error, {:backup_failed, reason}}` on error
+#[rustler::nif(schedule = "DirtyIo")]
+fn backup_list(env: Env, backup_dir: String) -> NifResult<Term> {
+    match open_backup_engine(&backup_dir, None) {
+        Ok(engine) => {
+            let backups: Vec<BackupInfo> = engine
+                .get_backup_info()
+                .into_iter()
+                .map(|info| BackupInfo {
+                    id: info.backup_id,
+                    timestamp: info.timestamp,
+                    size: info.size,
+                    num_files: info.num_files,
+                })
+                .collect();
+            Ok((atoms::ok(), backups).encode(env))
+        }
+        Err(e) => Ok((atoms::error(), (atoms::backup_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Deletes all but the newest `keep` backups in a backup directory.
+///
+/// # Arguments
+/// * `backup_dir` - The backup directory
+/// * `keep` - Number of most recent backups to keep
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, {:backup_failed, reason}}` on error
+#[rustler::nif(schedule = "DirtyIo")]
+fn backup_purge(env: Env, backup_dir: String, keep: usize) -> NifResult<Term> {
+    match open_backup_engine(&backup_dir, None).and_then(|mut engine| engine.purge_old_backups(keep)) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::backup_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Verifies that every file of a backup exists with its recorded size.
+///
+/// File checksums are not checked.
+///
+/// # Arguments
+/// * `backup_dir` - The backup directory
+/// * `backup_id` - The backup to verify
+///
+/// # Returns
+/// * `:ok` if the backup is intact
+/// * `{:error, {:backup_failed, reason}}` if it is missing or damaged
+#[rustler::nif(schedule = "DirtyIo")]
+fn backup_verify(env: Env, backup_dir: String, backup_id: u32) -> NifResult<Term> {
+    match open_backup_engine(&backup_dir, None).and_then(|engine| engine.verify_backup(backup_id)) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::backup_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Restores a backup into a database directory.
+///
+/// The target database must not be open. Existing files in `target_path` are
+/// replaced.
+///
+/// # Arguments
+/// * `backup_dir` - The backup directory
+/// * `target_path` - Database directory to restore into
+/// * `backup_id` - The backup to restore
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, {:restore_failed, reason}}` on error
+#[rustler::nif(schedule = "DirtyIo")]
+fn backup_restore(env: Env, backup_dir: String, target_path: String, backup_id: u32) -> NifResult<Term> {
+    let result = open_backup_engine(&backup_dir, None).and_then(|mut engine| {
+        engine.restore_from_backup(&target_path, &target_path, &RestoreOptions::default(), backup_id)
+    });
+
+    match result {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::restore_failed(), e.to_string())).encode(env)),
+    }
+}
+
+// ============================================================================
+// SST Bulk Loading
+// ============================================================================
+
+/// An open SST file writer and the bookkeeping needed to finish it.
+pub struct SstWriter {
+    /// The RocksDB writer. Declared first so it drops before its options.
+    /// Safety: `_options` is boxed and owned alongside, so the borrow stays valid.
+    writer: SstFileWriter<'static>,
+    /// Options the writer was created with
+    _options: Box<Options>,
+    /// Path of the file being written
+    path: String,
+    /// Last key written, to reject out-of-order keys with a clear error
+    last_key: Option<Vec<u8>>,
+    /// Number of entries written so far
+    entries: u64,
+}
+
+/// Result of `sst_writer_finish`, encoded as an Elixir map.
+#[derive(rustler::NifMap)]
+struct SstFileInfo {
+    path: String,
+    entries: u64,
+    file_size: u64,
+}
+
+/// Opens an SST file writer for a bulk load.
+///
+/// The writer should be created with the options of the column family the
+/// file will be ingested into, so that its bloom filters and prefix extractor
+/// match. `cf: name` starts from that column family's defaults, or from the
+/// top-level defaults for a column family without built-in ones, such as one
+/// created with `create_column_family`; the other keys are the column family
+/// options accepted by `open/2`.
+///
+/// # Arguments
+/// * `path` - Path of the SST file to create
+/// * `cf_options` - Keyword list or map of column family options
+///
+/// # Returns
+/// * `{:ok, writer_ref}` on success
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, {:sst_write_failed, reason}}` if the file cannot be created
+#[rustler::nif(schedule = "DirtyIo")]
+fn sst_writer_open<'a>(env: Env<'a>, path: String, cf_options: Term<'a>) -> NifResult<Term<'a>> {
+    let pairs = match option_pairs(cf_options) {
+        Ok(pairs) => pairs,
+        Err(term) => return Ok((atoms::error(), (atoms::invalid_option(), term)).encode(env)),
+    };
+
+    let mut cf_name = String::new();
+    let mut settings = CfSettings::default();
+    for (key, value) in pairs {
+        let valid = if key == atoms::cf() {
+            match cf_term_to_name(value) {
+                Some(name) => {
+                    cf_name = name;
+                    true
+                }
+                None => false,
+            }
+        } else {
+            matches!(settings.set(key, value), Ok(true))
+        };
+        if !valid {
+            return Ok((atoms::error(), (atoms::invalid_option(), key)).encode(env));
+        }
+    }
+
+    let mut options = Box::new(Options::default());
+    settings.apply(&cf_name, &mut options, None);
+
+    // SAFETY: The options are boxed and stored next to the writer, which is
+    // dropped first, so the borrow outlives every use of the writer
+    let writer: SstFileWriter<'static> = unsafe {
+        std::mem::transmute(SstFileWriter::create(&options))
+    };
+
+    // SAFETY: `open` only uses the reference for the duration of the call
+    let writer_ref: &'static SstFileWriter<'static> = unsafe { &*(&writer as *const SstFileWriter<'static>) };
+    if let Err(e) = writer_ref.open(&path) {
+        return Ok((atoms::error(), (atoms::sst_write_failed(), e.to_string())).encode(env));
+    }
+
+    let sst_ref = ResourceArc::new(SstWriterRef {
+        writer: Mutex::new(Some(SstWriter {
+            writer,
+            _options: options,
+            path,
+            last_key: None,
+            entries: 0,
+        })),
+    });
+
+    Ok((atoms::ok(), sst_ref).encode(env))
+}
+
+/// Appends key-value pairs to an SST file.
+///
+/// Keys must be in strictly ascending byte order, across calls as well as
+/// within one call. A rejected call leaves the entries before the offending
+/// key written.
+///
+/// # Arguments
+/// * `sst_ref` - The writer reference
+/// * `entries` - List of `{key, value}` binaries in ascending key order
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :writer_closed}` if the writer was finished
+/// * `{:error, {:unsorted_key, key}}` if a key is not greater than the previous one
+/// * `{:error, {:sst_write_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyIo")]
+fn sst_writer_put_sorted<'a>(
+    env: Env<'a>,
+    sst_ref: ResourceArc<SstWriterRef>,
+    entries: Vec<(Binary<'a>, Binary<'a>)>,
+) -> NifResult<Term<'a>> {
+    let mut writer_guard = sst_ref
+        .writer
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let sst = match writer_guard.as_mut() {
+        Some(sst) => sst,
+        None => return Ok((atoms::error(), atoms::writer_closed()).encode(env)),
+    };
+
+    for (key, value) in entries {
+        if sst.last_key.as_deref().is_some_and(|last| key.as_slice() <= last) {
+            return Ok((atoms::error(), (atoms::unsorted_key(), key)).encode(env));
+        }
+        if let Err(e) = sst.writer.put(key.as_slice(), value.as_slice()) {
+            return Ok((atoms::error(), (atoms::sst_write_failed(), e.to_string())).encode(env));
+        }
+        sst.last_key = Some(key.as_slice().to_vec());
+        sst.entries += 1;
+    }
+
+    Ok(atoms::ok().encode(env))
+}
+
+/// Finishes an SST file so it can be ingested, and closes the writer.
+///
+/// # Arguments
+/// * `sst_ref` - The writer reference
+///
+/// # Returns
+/// * `{:ok, %{path, entries, file_size}}` on success
+/// * `{:error, :writer_closed}` if already finished
+/// * `{:error, {:sst_write_failed, reason}}` on error, e.g. when no entries were written
+#[rustler::nif(schedule = "DirtyIo")]
+fn sst_writer_finish<'a>(env: Env<'a>, sst_ref: ResourceArc<SstWriterRef>) -> NifResult<Term<'a>> {
+    let mut writer_guard = sst_ref
+        .writer
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let mut sst = match writer_guard.take() {
+        Some(sst) => sst,
+        None => return Ok((atoms::error(), atoms::writer_closed()).encode(env)),
+    };
+
+    match sst.writer.finish() {
+        Ok(()) => {
+            let info = SstFileInfo {
+                file_size: sst.writer.file_size(),
+                path: std::mem::take(&mut sst.path),
+                entries: sst.entries,
+            };
+            Ok((atoms::ok(), info).encode(env))
+        }
+        Err(e) => Ok((atoms::error(), (atoms::sst_write_failed(), e.to_string())).encode(env)),
+    }
+}
+
+/// Ingests finished SST files into a column family.
+///
+/// Files whose key range does not overlap existing data are placed directly
+/// in the bottom level, skipping the memtable, WAL and compaction.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `paths` - Paths of files written with `sst_writer_*`
+/// * `options` - Keyword list or map of booleans: `move_files`,
+///   `snapshot_consistency`, `allow_global_seqno`, `allow_blocking_flush`
+///   and `ingest_behind`
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, {:ingest_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyIo")]
+fn ingest_external_files<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    paths: Vec<String>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let pairs = match option_pairs(options) {
+        Ok(pairs) => pairs,
+        Err(term) => return Ok((atoms::error(), (atoms::invalid_option(), term)).encode(env)),
+    };
+
+    let mut ingest_opts = IngestExternalFileOptions::default();
+    for (key, value) in pairs {
+        let flag: bool = match value.decode() {
+            Ok(flag) => flag,
+            Err(_) => return Ok((atoms::error(), (atoms::invalid_option(), key)).encode(env)),
+        };
+        if key == atoms::move_files() {
+            ingest_opts.set_move_files(flag);
+        } else if key == atoms::snapshot_consistency() {
+            ingest_opts.set_snapshot_consistency(flag);
+        } else if key == atoms::allow_global_seqno() {
+            ingest_opts.set_allow_global_seqno(flag);
+        } else if key == atoms::allow_blocking_flush() {
+            ingest_opts.set_allow_blocking_flush(flag);
+        } else if key == atoms::ingest_behind() {
+            ingest_opts.set_ingest_behind(flag);
+        } else {
+            return Ok((atoms::error(), (atoms::invalid_option(), key)).encode(env));
+        }
+    }
+
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
+
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
+
+    match db.ingest_external_file_cf_opts(cf_handle, &ingest_opts, paths) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::ingest_failed(), e.to_string())).encode(env)),
+    }
+}
+
+// ============================================================================
+// N-Triples Bulk Load
+// ============================================================================
+
+/// Number of triples parsed per sorted run written to disk.
+const DEFAULT_BULK_CHUNK_SIZE: usize = 1_000_000;
+
+/// Number of dictionary entries cached in memory before the cache is reset.
+const DEFAULT_BULK_CACHE_SIZE: usize = 1_000_000;
+
+/// Type tags in the high 4 bits of a term ID, as in `TripleStore.Dictionary`.
+const TYPE_URI: u64 = 1;
+const TYPE_BNODE: u64 = 2;
+const TYPE_LITERAL: u64 = 3;
+const TYPE_INTEGER: u64 = 4;
+const TYPE_DECIMAL: u64 = 5;
+const TYPE_DATETIME: u64 = 6;
+
+/// Mask of the 60-bit value below the type tag.
+const TERM_VALUE_MASK: u64 = (1 << 60) - 1;
+
+/// Largest sequence number a dictionary-allocated ID can carry.
+const MAX_SEQUENCE: u64 = (1 << 59) - 1;
+
+/// IDs skipped past a persisted sequence counter, as on `SequenceCounter` recovery.
+const SEQUENCE_SAFETY_MARGIN: u64 = 1000;
+
+/// Largest term accepted by `Dictionary.validate_term/2`.
+const MAX_TERM_SIZE: usize = 16_384;
+
+/// `str2id` keys of the persisted URI, blank node and literal sequence counters.
+const SEQUENCE_COUNTER_KEYS: [&str; 3] = ["__seq_counter__uri", "__seq_counter__bnode", "__seq_counter__literal"];
+
+const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+const XSD_DECIMAL: &str = "http://www.w3.org/2001/XMLSchema#decimal";
+const XSD_DATETIME: &str = "http://www.w3.org/2001/XMLSchema#dateTime";
+const RDF_LANG_STRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
+
+/// Result of `bulk_load_ntriples`, encoded as an Elixir map.
+#[derive(rustler::NifMap)]
+struct BulkLoadStats {
+    triples: u64,
+    unique_triples: u64,
+    terms_created: u64,
+}
+
+/// An RDF term as written in an N-Triples statement.
+enum NtTerm {
+    Iri(String),
+    BNode(String),
+    Literal {
+        value: String,
+        datatype: Option<String>,
+        language: Option<String>,
+    },
+}
+
+/// How a term maps to an ID: inline, or through the dictionary with the given
+/// `str2id` key and sequence type index.
+enum TermEncoding {
+    Inline(u64),
+    Dictionary(Vec<u8>, usize),
+}
+
+/// Why a statement could not be loaded; encoded as `{reason, line_number}`.
+#[derive(Clone, Copy)]
+enum StatementError {
+    Parse,
+    TermTooLarge,
+    NullByteInUri,
+    InvalidUtf8,
+}
+
+impl StatementError {
+    fn atom(self) -> rustler::Atom {
+        match self {
+            StatementError::Parse => atoms::parse_error(),
+            StatementError::TermTooLarge => atoms::term_too_large(),
+            StatementError::NullByteInUri => atoms::null_byte_in_uri(),
+            StatementError::InvalidUtf8 => atoms::invalid_utf8(),
+        }
+    }
+}
+
+/// Line-level N-Triples parser over one statement.
+struct NtParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> NtParser<'a> {
+    fn new(line: &'a str) -> Self {
+        NtParser { input: line.as_bytes(), pos: 0 }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.input.get(self.pos), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Returns true if only whitespace or a comment remains.
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        matches!(self.input.get(self.pos), None | Some(b'#'))
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.skip_whitespace();
+        if self.input.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// Parses `subject predicate object .`, or returns `Ok(None)` for a
+    /// blank or comment line.
+    fn statement(&mut self) -> Result<Option<[NtTerm; 3]>, StatementError> {
+        if self.at_end() {
+            return Ok(None);
+        }
+        let subject = match self.input[self.pos] {
+            b'<' => NtTerm::Iri(self.iri()?),
+            b'_' => NtTerm::BNode(self.blank_node()?),
+            _ => return Err(StatementError::Parse),
+        };
+        self.skip_whitespace();
+        if self.input.get(self.pos) != Some(&b'<') {
+            return Err(StatementError::Parse);
+        }
+        let predicate = NtTerm::Iri(self.iri()?);
+        self.skip_whitespace();
+        let object = match self.input.get(self.pos) {
+            Some(b'<') => NtTerm::Iri(self.iri()?),
+            Some(b'_') => NtTerm::BNode(self.blank_node()?),
+            Some(b'"') => self.literal()?,
+            _ => return Err(StatementError::Parse),
+        };
+        self.expect(b'.').ok_or(StatementError::Parse)?;
+        if !self.at_end() {
+            return Err(StatementError::Parse);
+        }
+        Ok(Some([subject, predicate, object]))
+    }
+
+    /// Parses `<iri>`, resolving `\u` and `\U` escapes.
+    fn iri(&mut self) -> Result<String, StatementError> {
+        self.pos += 1;
+        let mut iri = String::new();
+        loop {
+            let start = self.pos;
+            while !matches!(self.input.get(self.pos), None | Some(b'>' | b'\\')) {
+                self.pos += 1;
+            }
+            iri.push_str(self.text(start)?);
+            match self.input.get(self.pos) {
+                Some(b'>') => {
+                    self.pos += 1;
+                    return Ok(iri);
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escape = self.input.get(self.pos).copied();
+                    self.pos += 1;
+                    iri.push(self.unicode_escape(escape)?);
+                }
+                _ => return Err(StatementError::Parse),
+            }
+        }
+    }
+
+    /// Parses `_:label` and returns the label.
+    fn blank_node(&mut self) -> Result<String, StatementError> {
+        if self.input.get(self.pos + 1) != Some(&b':') {
+            return Err(StatementError::Parse);
+        }
+        self.pos += 2;
+        let start = self.pos;
+        while !matches!(self.input.get(self.pos), None | Some(b' ' | b'\t' | b'<' | b'"' | b'#')) {
+            self.pos += 1;
+        }
+        // A label may contain dots but not end with one
+        while self.pos > start && self.input[self.pos - 1] == b'.' {
+            self.pos -= 1;
+        }
+        if self.pos == start {
+            return Err(StatementError::Parse);
+        }
+        Ok(self.text(start)?.to_string())
+    }
+
+    /// Parses a quoted literal with an optional `@lang` or `^^<datatype>`.
+    fn literal(&mut self) -> Result<NtTerm, StatementError> {
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            let start = self.pos;
+            while !matches!(self.input.get(self.pos), None | Some(b'"' | b'\\')) {
+                self.pos += 1;
+            }
+            value.push_str(self.text(start)?);
+            match self.input.get(self.pos) {
+                Some(b'"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escape = self.input.get(self.pos).copied();
+                    self.pos += 1;
+                    value.push(match escape {
+                        Some(b't') => '\t',
+                        Some(b'b') => '\u{8}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b'f') => '\u{c}',
+                        Some(b'"') => '"',
+                        Some(b'\'') => '\'',
+                        Some(b'\\') => '\\',
+                        other => self.unicode_escape(other)?,
+                    });
+                }
+                _ => return Err(StatementError::Parse),
+            }
+        }
+
+        let mut datatype = None;
+        let mut language = None;
+        match self.input.get(self.pos) {
+            Some(b'@') => {
+                self.pos += 1;
+                let start = self.pos;
+                while self.input.get(self.pos).is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'-') {
+                    self.pos += 1;
+                }
+                let tag = self.text(start)?;
+                if tag.is_empty() || !tag.as_bytes()[0].is_ascii_alphabetic() {
+                    return Err(StatementError::Parse);
+                }
+                language = Some(tag.to_ascii_lowercase());
+            }
+            Some(b'^') => {
+                if self.input.get(self.pos + 1) != Some(&b'^') || self.input.get(self.pos + 2) != Some(&b'<') {
+                    return Err(StatementError::Parse);
+                }
+                self.pos += 2;
+                datatype = Some(self.iri()?);
+            }
+            _ => {}
+        }
+
+        Ok(NtTerm::Literal { value, datatype, language })
+    }
+
+    /// Decodes the hex digits of a `\uXXXX` or `\UXXXXXXXX` escape.
+    fn unicode_escape(&mut self, escape: Option<u8>) -> Result<char, StatementError> {
+        let digits = match escape {
+            Some(b'u') => 4,
+            Some(b'U') => 8,
+            _ => return Err(StatementError::Parse),
+        };
+        let hex = self.input.get(self.pos..self.pos + digits).ok_or(StatementError::Parse)?;
+        self.pos += digits;
+        std::str::from_utf8(hex)
+            .ok()
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32)
+            .ok_or(StatementError::Parse)
+    }
+
+    fn text(&self, start: usize) -> Result<&'a str, StatementError> {
+        std::str::from_utf8(&self.input[start..self.pos]).map_err(|_| StatementError::InvalidUtf8)
+    }
+}
+
+/// Parses an `xsd:integer` lexical form into its sign and digits without
+/// leading zeros (`"0"` for zero, which is never negative).
+fn parse_integer_lexical(lexical: &str) -> Option<(bool, &str)> {
+    let (negative, digits) = match lexical.as_bytes().first()? {
+        b'-' => (true, &lexical[1..]),
+        b'+' => (false, &lexical[1..]),
+        _ => (false, lexical),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    match digits.trim_start_matches('0') {
+        "" => Some((false, "0")),
+        digits => Some((negative, digits)),
+    }
+}
+
+/// Encodes an `xsd:integer` the way `Dictionary.encode_integer/1` does, or
+/// returns its canonical lexical form if it is out of the inline range.
+fn encode_integer_literal(lexical: &str) -> Option<Result<u64, String>> {
+    let (negative, digits) = parse_integer_lexical(lexical)?;
+    let inline = digits.parse::<i64>().ok().map(|value| if negative { -value } else { value });
+    Some(match inline {
+        Some(value) if (-(1i64 << 59)..1i64 << 59).contains(&value) => {
+            Ok((TYPE_INTEGER << 60) | (value as u64 & TERM_VALUE_MASK))
+        }
+        _ => Err(format!("{}{}", if negative { "-" } else { "" }, digits)),
+    })
+}
+
+/// Encodes an `xsd:decimal` the way `Dictionary.encode_decimal/1` does for
+/// RDF.ex's canonical decimal value (trailing zeros dropped down to one
+/// fractional digit), or returns the value's `Decimal.to_string/1` form if it
+/// is out of the inline range.
+fn encode_decimal_literal(lexical: &str) -> Option<Result<u64, String>> {
+    let (negative, unsigned) = match lexical.as_bytes().first()? {
+        b'-' => (true, &lexical[1..]),
+        b'+' => (false, &lexical[1..]),
+        _ => (false, lexical),
+    };
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    if !integer.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut coef: String = format!("{}{}", integer, fraction).trim_start_matches('0').to_string();
+    if coef.is_empty() {
+        return Some(Ok(TYPE_DECIMAL << 60));
+    }
+    let mut exp = -(fraction.len() as i64);
+    if exp == 0 {
+        coef.push('0');
+        exp = -1;
+    }
+    while exp < -1 && coef.ends_with('0') {
+        coef.pop();
+        exp += 1;
+    }
+
+    let biased_exp = exp + 1023;
+    let mantissa = coef.parse::<u64>().ok().filter(|coef| *coef < 1 << 48);
+    if let (Some(mantissa), true) = (mantissa, biased_exp >= 0) {
+        let sign_bit = u64::from(negative);
+        return Some(Ok((TYPE_DECIMAL << 60) | (sign_bit << 59) | ((biased_exp as u64) << 48) | mantissa));
+    }
+
+    // Decimal.to_string/1 in its default :scientific format
+    let adjusted = exp + coef.len() as i64 - 1;
+    let mut string = String::from(if negative { "-" } else { "" });
+    if adjusted >= -6 {
+        let point = coef.len() as i64 + exp;
+        if point > 0 {
+            string.push_str(&coef[..point as usize]);
+            string.push('.');
+            string.push_str(&coef[point as usize..]);
+        } else {
+            string.push_str("0.");
+            string.push_str(&"0".repeat(-point as usize));
+            string.push_str(&coef);
+        }
+    } else {
+        string.push_str(&coef[..1]);
+        if coef.len() > 1 {
+            string.push('.');
+            string.push_str(&coef[1..]);
+        }
+        string.push_str(&format!("E{}", adjusted));
+    }
+    Some(Err(string))
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Date of a day count since 1970-01-01, as `(year, month, day)`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// Encodes an `xsd:dateTime` with a timezone the way
+/// `Dictionary.encode_datetime/1` does, or returns its `DateTime.to_iso8601/1`
+/// form in UTC if it is before 1970. Returns `None` for invalid lexical forms
+/// and for datetimes without a timezone, which RDF.ex does not inline.
+fn encode_datetime_literal(lexical: &str) -> Option<Result<u64, String>> {
+    let bytes = lexical.as_bytes();
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = bytes.get(range)?;
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        std::str::from_utf8(digits).ok()?.parse().ok()
+    };
+    if bytes.len() < 19 || bytes[4] != b'-' || bytes[7] != b'-' || bytes[10] != b'T' || bytes[13] != b':' || bytes[16] != b':' {
+        return None;
+    }
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = [31, if leap { 29 } else { 28 }, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    if !(1..=12).contains(&month) || day < 1 || day > month_days[month as usize - 1] || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    let mut rest = &lexical[19..];
+    let mut fraction = "";
+    if let Some(after_point) = rest.strip_prefix('.') {
+        let len = after_point.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
+        }
+        fraction = &after_point[..len.min(6)];
+        rest = &after_point[len..];
+    }
+    let offset = match rest.as_bytes() {
+        [b'Z'] => 0,
+        [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+            let (hours, minutes) = (number(lexical.len() - 5..lexical.len() - 3)?, number(lexical.len() - 2..lexical.len())?);
+            if hours > 14 || minutes > 59 {
+                return None;
+            }
+            let offset = (hours * 60 + minutes) * 60;
+            if *sign == b'-' { -offset } else { offset }
+        }
+        _ => return None,
+    };
+
+    let seconds = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    if seconds >= 0 {
+        let millis: i64 = format!("{:0<3}", &fraction[..fraction.len().min(3)]).parse().ok()?;
+        return Some(Ok((TYPE_DATETIME << 60) | (seconds * 1000 + millis) as u64));
+    }
+
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+    let mut string = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    );
+    if !fraction.is_empty() {
+        string.push('.');
+        string.push_str(fraction);
+    }
+    string.push('Z');
+    Some(Err(string))
+}
+
+/// Checks a term string the way `Dictionary.validate_term/2` does.
+fn validate_term(value: &str, is_uri: bool) -> Result<(), StatementError> {
+    if value.len() > MAX_TERM_SIZE {
+        Err(StatementError::TermTooLarge)
+    } else if is_uri && value.contains('\0') {
+        Err(StatementError::NullByteInUri)
+    } else {
+        Ok(())
+    }
+}
+
+/// Normalizes text to NFC, as `Dictionary.normalize_unicode/1` does.
+fn normalize_unicode(text: String) -> String {
+    if unicode_normalization::is_nfc(&text) {
+        text
+    } else {
+        text.nfc().collect()
+    }
+}
+
+/// Maps a term to an inline ID or to its `str2id` key, matching
+/// `Adapter.from_rdf_term/2` and `StringToId.encode_term/1`: IRIs and
+/// literal values are NFC-normalized after validation, blank node labels
+/// and datatype IRIs are kept as written.
+fn encode_nt_term(term: NtTerm) -> Result<TermEncoding, StatementError> {
+    match term {
+        NtTerm::Iri(iri) => {
+            validate_term(&iri, true)?;
+            let iri = normalize_unicode(iri);
+            let mut key = Vec::with_capacity(iri.len() + 1);
+            key.push(TYPE_URI as u8);
+            key.extend_from_slice(iri.as_bytes());
+            Ok(TermEncoding::Dictionary(key, 0))
+        }
+        NtTerm::BNode(label) => {
+            validate_term(&label, false)?;
+            let mut key = Vec::with_capacity(label.len() + 1);
+            key.push(TYPE_BNODE as u8);
+            key.extend_from_slice(label.as_bytes());
+            Ok(TermEncoding::Dictionary(key, 1))
+        }
+        NtTerm::Literal { value, language: Some(language), .. } => {
+            validate_term(&value, false)?;
+            let value = normalize_unicode(value);
+            let mut key = vec![TYPE_LITERAL as u8, 2];
+            key.extend_from_slice(language.as_bytes());
+            key.push(0);
+            key.extend_from_slice(value.as_bytes());
+            Ok(TermEncoding::Dictionary(key, 2))
+        }
+        NtTerm::Literal { value, datatype, .. } => {
+            let datatype = datatype.unwrap_or_else(|| XSD_STRING.to_string());
+            if datatype == RDF_LANG_STRING {
+                return Err(StatementError::Parse);
+            }
+            let encoded = match datatype.as_str() {
+                XSD_INTEGER => encode_integer_literal(&value),
+                XSD_DECIMAL => encode_decimal_literal(&value),
+                XSD_DATETIME => encode_datetime_literal(&value),
+                _ => None,
+            };
+            let value = match encoded {
+                Some(Ok(id)) => return Ok(TermEncoding::Inline(id)),
+                Some(Err(canonical)) => canonical,
+                None => value,
+            };
+            validate_term(&value, false)?;
+            let value = normalize_unicode(value);
+            let mut key = vec![TYPE_LITERAL as u8, 1];
+            key.extend_from_slice(datatype.as_bytes());
+            key.push(0);
+            key.extend_from_slice(value.as_bytes());
+            Ok(TermEncoding::Dictionary(key, 2))
+        }
+    }
+}
+
+/// Options decoded from the `bulk_load_ntriples/3` keyword list.
+struct BulkLoadOptions {
+    progress: Option<rustler::LocalPid>,
+    tmp_dir: Option<String>,
+    chunk_size: usize,
+    cache_size: usize,
+}
+
+/// Decodes the options of `bulk_load_ntriples`, returning the offending key on failure.
+fn decode_bulk_load_options(options: Term) -> Result<BulkLoadOptions, Term> {
+    let mut decoded = BulkLoadOptions {
+        progress: None,
+        tmp_dir: None,
+        chunk_size: DEFAULT_BULK_CHUNK_SIZE,
+        cache_size: DEFAULT_BULK_CACHE_SIZE,
+    };
+
+    for (key, value) in option_pairs(options)? {
+        let valid = if key == atoms::progress() {
+            value.decode().map(|pid| decoded.progress = Some(pid)).is_ok()
+        } else if key == atoms::tmp_dir() {
+            value.decode().map(|dir| decoded.tmp_dir = Some(dir)).is_ok()
+        } else if key == atoms::chunk_size() {
+            value.decode().ok().filter(|size| *size > 0).map(|size| decoded.chunk_size = size).is_some()
+        } else if key == atoms::cache_size() {
+            value.decode().ok().filter(|size| *size > 0).map(|size| decoded.cache_size = size).is_some()
+        } else {
+            false
+        };
+        if !valid {
+            return Err(key.to_term(options.get_env()));
+        }
+    }
+
+    Ok(decoded)
+}
+
+/// Why a bulk load stopped, before it is encoded as an error reason.
+enum BulkLoadError {
+    Statement(StatementError, u64),
+    SequenceOverflow,
+    Io(String),
+    Db(Error),
+}
+
+impl From<std::io::Error> for BulkLoadError {
+    fn from(e: std::io::Error) -> Self {
+        BulkLoadError::Io(e.to_string())
+    }
+}
+
+impl From<Error> for BulkLoadError {
+    fn from(e: Error) -> Self {
+        BulkLoadError::Db(e)
+    }
+}
+
+/// ID of a term seen during a bulk load: known, or the index of a dictionary
+/// term whose ID is assigned when the chunk is resolved.
+#[derive(Clone, Copy)]
+enum BulkId {
+    Known(u64),
+    Pending(usize),
+}
+
+/// Assigns dictionary IDs during a bulk load.
+///
+/// Terms missing from the cache are collected per chunk and resolved in one
+/// step: they are looked up in `str2id`, and the new entries are written in a
+/// WriteBatch together with the sequence counters they advance, so a crash
+/// never leaves IDs in the dictionary that a restarted `SequenceCounter` could
+/// hand out again. The database's sequence counters are locked only while a
+/// chunk is resolved, so `dictionary_get_or_create` calls can run between
+/// chunks.
+struct BulkDictionary<'a> {
+    db: &'a Database,
+    str2id: &'a ColumnFamily,
+    id2str: &'a ColumnFamily,
+    cache: HashMap<Vec<u8>, u64>,
+    cache_size: usize,
+    sequences: &'a Mutex<Option<[u64; 3]>>,
+    pending: Vec<(Vec<u8>, usize)>,
+    pending_index: HashMap<Vec<u8>, usize>,
+    terms_created: u64,
+}
+
+impl<'a> BulkDictionary<'a> {
+    fn new(db: &'a Database, sequences: &'a Mutex<Option<[u64; 3]>>, cache_size: usize) -> Result<Self, BulkLoadError> {
+        let str2id = db.cf_handle("str2id").ok_or_else(|| BulkLoadError::Io("missing str2id".into()))?;
+        let id2str = db.cf_handle("id2str").ok_or_else(|| BulkLoadError::Io("missing id2str".into()))?;
+
+        Ok(BulkDictionary {
+            db,
+            str2id,
+            id2str,
+            cache: HashMap::new(),
+            cache_size,
+            sequences,
+            pending: Vec::new(),
+            pending_index: HashMap::new(),
+            terms_created: 0,
+        })
+    }
+
+    /// Returns the ID of a term, or marks it pending until `resolve`.
+    fn id(&mut self, term: NtTerm) -> Result<BulkId, BulkLoadError> {
+        let (key, type_index) = match encode_nt_term(term).map_err(|e| BulkLoadError::Statement(e, 0))? {
+            TermEncoding::Inline(id) => return Ok(BulkId::Known(id)),
+            TermEncoding::Dictionary(key, type_index) => (key, type_index),
+        };
+        if let Some(id) = self.cache.get(&key) {
+            return Ok(BulkId::Known(*id));
+        }
+        if let Some(index) = self.pending_index.get(&key) {
+            return Ok(BulkId::Pending(*index));
+        }
+
+        let index = self.pending.len();
+        self.pending_index.insert(key.clone(), index);
+        self.pending.push((key, type_index));
+        Ok(BulkId::Pending(index))
+    }
+
+    /// Looks up or allocates the IDs of the pending terms, in pending order,
+    /// and writes the new entries with the advanced counters.
+    ///
+    /// The pending keys are read in one batch before the counters are locked;
+    /// only the terms that batch missed are read again under the lock, in
+    /// case a concurrent `dictionary_get_or_create` created them meanwhile.
+    fn resolve(&mut self) -> Result<Vec<u64>, BulkLoadError> {
+        let pending = std::mem::take(&mut self.pending);
+        self.pending_index.clear();
+        if pending.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut found = Vec::with_capacity(pending.len());
+        for result in self.db.batched_multi_get_cf_opt(
+            self.str2id,
+            pending.iter().map(|(key, _)| key.as_slice()),
+            false,
+            &ReadOptions::default(),
+        ) {
+            found.push(result?.map(|value| decode_str2id_value(&value)).transpose()?);
+        }
+
+        let mut sequences = self
+            .sequences
+            .lock()
+            .map_err(|_| BulkLoadError::Io("lock poisoned".into()))?;
+        let mut counters = match *sequences {
+            Some(counters) => counters,
+            None => load_sequences(self.db, self.str2id).map_err(BulkLoadError::Io)?,
+        };
+
+        let mut batch = WriteBatch::default();
+        let mut ids = Vec::with_capacity(pending.len());
+        for ((key, type_index), found) in pending.iter().zip(found) {
+            let existing = match found {
+                Some(id) => Some(id),
+                None => self.db.get_cf(self.str2id, key)?.map(|value| decode_str2id_value(&value)).transpose()?,
+            };
+            let id = match existing {
+                Some(id) => id,
+                None => {
+                    if counters[*type_index] >= MAX_SEQUENCE {
+                        return Err(BulkLoadError::SequenceOverflow);
+                    }
+                    counters[*type_index] += 1;
+                    let id = ((*type_index as u64 + 1) << 60) | counters[*type_index];
+                    batch.put_cf(self.str2id, key, id.to_be_bytes());
+                    batch.put_cf(self.id2str, id.to_be_bytes(), key);
+                    self.terms_created += 1;
+                    id
+                }
+            };
+            ids.push(id);
+        }
+
+        if !batch.is_empty() {
+            for (counter, key) in counters.iter().zip(SEQUENCE_COUNTER_KEYS) {
+                batch.put_cf(self.str2id, key, counter.to_be_bytes());
+            }
+            self.db.write_opt(batch, &WriteOptions::default())?;
+            *sequences = Some(counters);
+        }
+        drop(sequences);
+
+        if self.cache.len() + pending.len() > self.cache_size {
+            self.cache.clear();
+        }
+        for ((key, _), id) in pending.into_iter().zip(&ids).take(self.cache_size) {
+            self.cache.insert(key, *id);
+        }
+        Ok(ids)
+    }
+}
+
+/// Decodes the big-endian ID stored under a `str2id` key.
+fn decode_str2id_value(value: &[u8]) -> Result<u64, BulkLoadError> {
+    value
+        .try_into()
+        .map(u64::from_be_bytes)
+        .map_err(|_| BulkLoadError::Io("malformed str2id entry".into()))
+}
+
+/// Sorts and deduplicates one chunk of triples into a run file per index.
+fn write_sorted_runs(dir: &std::path::Path, chunk: &[[u64; 3]], run: usize) -> Result<(), BulkLoadError> {
+    use std::io::Write;
+
+    for (cf_name, layout) in &TRIPLE_INDICES {
+        let mut keys: Vec<[u8; TRIPLE_RECORD_SIZE]> = chunk.iter().map(|triple| triple_key(triple, layout)).collect();
+        keys.sort_unstable();
+        keys.dedup();
+
+        let file = std::fs::File::create(dir.join(format!("{}_{}.run", cf_name, run)))?;
+        let mut writer = std::io::BufWriter::new(file);
+        for key in &keys {
+            writer.write_all(key)?;
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Merges the sorted runs of one index into a single SST file, dropping
+/// duplicate keys. The file is built with the settings `cf_name` was opened
+/// with, so its filters and prefix extractor match the column family.
+/// Returns the number of keys written.
+fn merge_runs_to_sst(
+    db_ref: &DbRef,
+    dir: &std::path::Path,
+    cf_name: &str,
+    runs: usize,
+    sst_path: &std::path::Path,
+) -> Result<u64, BulkLoadError> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+    use std::io::Read;
+
+    let mut readers = Vec::with_capacity(runs);
+    for run in 0..runs {
+        let file = std::fs::File::open(dir.join(format!("{}_{}.run", cf_name, run)))?;
+        readers.push(std::io::BufReader::new(file));
+    }
+
+    let read_key = |reader: &mut std::io::BufReader<std::fs::File>| -> Result<Option<[u8; TRIPLE_RECORD_SIZE]>, BulkLoadError> {
+        let mut key = [0u8; TRIPLE_RECORD_SIZE];
+        match reader.read_exact(&mut key) {
+            Ok(()) => Ok(Some(key)),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    };
+
+    let mut heap = BinaryHeap::with_capacity(runs);
+    for (run, reader) in readers.iter_mut().enumerate() {
+        if let Some(key) = read_key(reader)? {
+            heap.push(Reverse((key, run)));
+        }
+    }
+
+    let mut options = Options::default();
+    db_ref.cf_settings(cf_name).apply(cf_name, &mut options, db_ref.block_cache.as_ref());
+    let mut writer = SstFileWriter::create(&options);
+    writer.open(sst_path)?;
+
+    let mut last_key: Option<[u8; TRIPLE_RECORD_SIZE]> = None;
+    let mut written = 0;
+    while let Some(Reverse((key, run))) = heap.pop() {
+        if last_key != Some(key) {
+            writer.put(key, [])?;
+            last_key = Some(key);
+            written += 1;
+        }
+        if let Some(next) = read_key(&mut readers[run])? {
+            heap.push(Reverse((next, run)));
+        }
+    }
+
+    writer.finish()?;
+    Ok(written)
+}
+
+/// Checks that every statement of the file parses and that each of its terms
+/// can be encoded, without writing anything.
+///
+/// Runs before the load proper, so a bad statement fails the load before any
+/// dictionary entry is written.
+fn validate_ntriples(path: &str) -> Result<(), BulkLoadError> {
+    use std::io::BufRead;
+
+    let mut reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut line = Vec::new();
+    let mut line_number = 0u64;
+
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        line_number += 1;
+        let text = std::str::from_utf8(&line)
+            .map_err(|_| BulkLoadError::Statement(StatementError::InvalidUtf8, line_number))?;
+        let statement = NtParser::new(text.trim_end_matches(['\n', '\r']))
+            .statement()
+            .map_err(|e| BulkLoadError::Statement(e, line_number))?;
+
+        for term in statement.into_iter().flatten() {
+            encode_nt_term(term).map_err(|e| BulkLoadError::Statement(e, line_number))?;
+        }
+        line.clear();
+    }
+    Ok(())
 }
 
-/// Gets a value from a column family.
+/// Parses the file, assigns IDs and writes the sorted runs. Returns the
+/// number of statements and of runs written.
+fn parse_into_runs(
+    env: Env,
+    path: &str,
+    dictionary: &mut BulkDictionary,
+    work_dir: &std::path::Path,
+    options: &BulkLoadOptions,
+) -> Result<(u64, usize), BulkLoadError> {
+    use std::io::BufRead;
+
+    let mut reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut line = Vec::new();
+    let mut line_number = 0u64;
+    let mut chunk: Vec<[BulkId; 3]> = Vec::with_capacity(options.chunk_size);
+    let mut triples = 0u64;
+    let mut runs = 0;
+
+    loop {
+        line.clear();
+        let eof = reader.read_until(b'\n', &mut line)? == 0;
+        if !eof {
+            line_number += 1;
+            let text = std::str::from_utf8(&line)
+                .map_err(|_| BulkLoadError::Statement(StatementError::InvalidUtf8, line_number))?;
+            let statement = NtParser::new(text.trim_end_matches(['\n', '\r']))
+                .statement()
+                .map_err(|e| BulkLoadError::Statement(e, line_number))?;
+
+            if let Some(terms) = statement {
+                let mut triple = [BulkId::Known(0); 3];
+                for (slot, term) in triple.iter_mut().zip(terms) {
+                    *slot = dictionary.id(term).map_err(|e| match e {
+                        BulkLoadError::Statement(reason, _) => BulkLoadError::Statement(reason, line_number),
+                        other => other,
+                    })?;
+                }
+                chunk.push(triple);
+                triples += 1;
+            }
+        }
+
+        if chunk.len() >= options.chunk_size || (eof && !chunk.is_empty()) {
+            let ids = dictionary.resolve()?;
+            let resolved: Vec<[u64; 3]> = chunk
+                .iter()
+                .map(|triple| {
+                    triple.map(|id| match id {
+                        BulkId::Known(id) => id,
+                        BulkId::Pending(index) => ids[index],
+                    })
+                })
+                .collect();
+            write_sorted_runs(work_dir, &resolved, runs)?;
+            runs += 1;
+            chunk.clear();
+            if let Some(pid) = &options.progress {
+                let _ = env.send(pid, (atoms::bulk_load_progress(), atoms::parsing(), triples));
+            }
+        }
+        if eof {
+            break;
+        }
+    }
+
+    Ok((triples, runs))
+}
+
+/// Runs a bulk load inside its working directory.
+fn run_bulk_load(
+    env: Env,
+    db_ref: &DbRef,
+    db: &Database,
+    path: &str,
+    work_dir: &std::path::Path,
+    options: &BulkLoadOptions,
+) -> Result<BulkLoadStats, BulkLoadError> {
+    validate_ntriples(path)?;
+
+    let mut dictionary = BulkDictionary::new(db, &db_ref.sequences, options.cache_size)?;
+    let (triples, runs) = parse_into_runs(env, path, &mut dictionary, work_dir, options)?;
+    let terms_created = dictionary.terms_created;
+    drop(dictionary);
+
+    let mut unique_triples = 0;
+    if runs > 0 {
+        // Build every index's SST before ingesting any, so a failed merge
+        // leaves all three indices untouched
+        let mut sst_files = Vec::with_capacity(TRIPLE_INDICES.len());
+        for (cf_name, _) in &TRIPLE_INDICES {
+            let sst_path = work_dir.join(format!("{}.sst", cf_name));
+            let written = merge_runs_to_sst(db_ref, work_dir, cf_name, runs, &sst_path)?;
+            let cf_handle = db.cf_handle(cf_name).ok_or_else(|| BulkLoadError::Io(format!("missing {}", cf_name)))?;
+            sst_files.push((*cf_name, cf_handle, sst_path.to_string_lossy().into_owned(), written));
+        }
+
+        let mut ingest_opts = IngestExternalFileOptions::default();
+        ingest_opts.set_move_files(true);
+
+        for (cf_name, cf_handle, sst_path, written) in sst_files {
+            db.ingest_external_file_cf_opts(cf_handle, &ingest_opts, vec![sst_path])?;
+            unique_triples = written;
+
+            if let Some(pid) = &options.progress {
+                let cf_atom = match cf_name {
+                    "spo" => atoms::spo(),
+                    "pos" => atoms::pos(),
+                    _ => atoms::osp(),
+                };
+                let _ = env.send(pid, (atoms::bulk_load_progress(), cf_atom, written));
+            }
+        }
+    }
+
+    Ok(BulkLoadStats { triples, unique_triples, terms_created })
+}
+
+/// Loads an N-Triples file directly into the dictionary and triple indices.
+///
+/// Terms get IDs with the same layout as `Dictionary.encode_id/2`: integers,
+/// decimals and timezoned dateTimes are inlined, and every other term is
+/// looked up in `str2id` or allocated from the sequence counters shared with
+/// `dictionary_get_or_create`. New dictionary entries are written with the
+/// advanced counters in one batch per run, holding the counters only for that
+/// write, so `dictionary_get_or_create` calls are not blocked for the whole
+/// load. Triples are sorted in runs of
+/// `chunk_size` on disk, merged into one SST file per index and ingested.
+///
+/// The whole file is validated before anything is written, so an invalid
+/// statement leaves the database unchanged. A failure after that, such as an
+/// I/O error, can leave dictionary entries without triples, and the three
+/// index ingests are separate steps that can stop after one or two indices.
+///
+/// IRIs and literal values are NFC-normalized like in `StringToId`, so a
+/// term gets the same ID whichever path adds it first. Must not run while a
+/// `Dictionary.Manager` allocates IDs for the same database.
 ///
 /// # Arguments
 /// * `db_ref` - The database reference
-/// * `cf` - The column family atom (:id2str, :str2id, :spo, :pos, :osp, :derived)
-/// * `key` - The key as a binary
+/// * `path` - Path of the N-Triples file
+/// * `options` - Keyword list or map: `progress` (pid sent
+///   `{:bulk_load_progress, stage, count}`), `tmp_dir`, `chunk_size` and
+///   `cache_size`
 ///
 /// # Returns
-/// * `{:ok, value}` if found
-/// * `:not_found` if key doesn't exist
+/// * `{:ok, %{triples, unique_triples, terms_created}}` on success
 /// * `{:error, :already_closed}` if database is closed
-/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
-/// * `{:error, {:get_failed, reason}}` on other errors
-#[rustler::nif(schedule = "DirtyCpu")]
-fn get<'a>(
-    env: Env<'a>,
-    db_ref: ResourceArc<DbRef>,
-    cf: rustler::Atom,
-    key: Binary<'a>,
-) -> NifResult<Term<'a>> {
-    let cf_name = match cf_atom_to_name(cf) {
-        Some(name) => name,
-        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, {reason, line}}` for a statement that cannot be loaded, where
+///   reason is `:parse_error`, `:term_too_large`, `:null_byte_in_uri` or
+///   `:invalid_utf8`
+/// * `{:error, :sequence_overflow}` if a sequence counter is exhausted
+/// * `{:error, {:bulk_load_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyIo")]
+fn bulk_load_ntriples<'a>(env: Env<'a>, db_ref: ResourceArc<DbRef>, path: String, options: Term<'a>) -> NifResult<Term<'a>> {
+    let options = match decode_bulk_load_options(options) {
+        Ok(options) => options,
+        Err(term) => return Ok((atoms::error(), (atoms::invalid_option(), term)).encode(env)),
     };
 
     let db_guard = db_ref
@@ -281,46 +6800,97 @@ fn get<'a>(
         None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let cf_handle = match db.cf_handle(cf_name) {
-        Some(cf) => cf,
-        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let unique = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    let work_dir = match &options.tmp_dir {
+        Some(dir) => std::path::Path::new(dir).join(format!("bulk_load_{}", unique)),
+        None => std::path::PathBuf::from(format!("{}.bulk_load_{}", db_ref.path, unique)),
     };
+    if let Err(e) = std::fs::create_dir_all(&work_dir) {
+        return Ok((atoms::error(), (atoms::bulk_load_failed(), e.to_string())).encode(env));
+    }
 
-    match db.get_cf(&cf_handle, key.as_slice()) {
-        Ok(Some(value)) => {
-            let mut binary = NewBinary::new(env, value.len());
-            binary.as_mut_slice().copy_from_slice(&value);
-            Ok((atoms::ok(), Binary::from(binary)).encode(env))
-        }
-        Ok(None) => Ok(atoms::not_found().encode(env)),
-        Err(e) => Ok((atoms::error(), (atoms::get_failed(), e.to_string())).encode(env)),
+    let result = run_bulk_load(env, &db_ref, db, &path, &work_dir, &options);
+    let _ = std::fs::remove_dir_all(&work_dir);
+
+    match result {
+        Ok(stats) => Ok((atoms::ok(), stats).encode(env)),
+        Err(BulkLoadError::Statement(reason, line)) => Ok((atoms::error(), (reason.atom(), line)).encode(env)),
+        Err(BulkLoadError::SequenceOverflow) => Ok((atoms::error(), atoms::sequence_overflow()).encode(env)),
+        Err(BulkLoadError::Io(reason)) => Ok((atoms::error(), (atoms::bulk_load_failed(), reason)).encode(env)),
+        Err(BulkLoadError::Db(e)) => Ok((atoms::error(), (atoms::bulk_load_failed(), e.to_string())).encode(env)),
     }
 }
 
-/// Puts a key-value pair into a column family.
+// ============================================================================
+// Counter Operations
+// ============================================================================
+
+/// Associative merge operator of the `counters` column family.
+///
+/// Values and operands are 8-byte big-endian integers; operands are added
+/// with wrapping arithmetic, so a negative delta in two's complement
+/// subtracts. Returns `None`, failing the merge, on a malformed value.
+fn u64_add_merge(_key: &[u8], existing: Option<&[u8]>, operands: &MergeOperands) -> Option<Vec<u8>> {
+    let decode = |bytes: &[u8]| bytes.try_into().ok().map(u64::from_be_bytes);
+    let mut total = match existing {
+        Some(value) => decode(value)?,
+        None => 0,
+    };
+    for operand in operands {
+        total = total.wrapping_add(decode(operand)?);
+    }
+    Some(total.to_be_bytes().to_vec())
+}
+
+/// Resolves `cf` to the handle of the counters column family.
+fn counters_cf<'d>(db: &'d Database, cf: Term) -> Option<&'d ColumnFamily> {
+    builtin_cf_name(cf)
+        .filter(|name| *name == COUNTERS_CF)
+        .and_then(|name| db.cf_handle(name))
+}
+
+/// Adds a signed delta to a counter without reading it.
+///
+/// Counters live in the `counters` column family as 8-byte big-endian u64
+/// values and start at 0. Concurrent merges never lose updates; use
+/// `mixed_batch` with `{:merge, :counters, key, delta}` to update a counter
+/// atomically with other writes.
 ///
 /// # Arguments
 /// * `db_ref` - The database reference
-/// * `cf` - The column family atom
-/// * `key` - The key as a binary
-/// * `value` - The value as a binary
+/// * `cf` - The column family atom; must be `:counters`
+/// * `key` - The counter key
+/// * `delta` - Signed amount to add; results wrap around at 2^64
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
 ///
 /// # Returns
 /// * `:ok` on success
 /// * `{:error, :already_closed}` if database is closed
-/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
-/// * `{:error, {:put_failed, reason}}` on other errors
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if column family is not `:counters`
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:merge_failed, reason}}` on other errors
 #[rustler::nif(schedule = "DirtyCpu")]
-fn put<'a>(
+fn merge<'a>(
     env: Env<'a>,
     db_ref: ResourceArc<DbRef>,
-    cf: rustler::Atom,
+    cf: Term<'a>,
     key: Binary<'a>,
-    value: Binary<'a>,
+    delta: i64,
+    options: Term<'a>,
 ) -> NifResult<Term<'a>> {
-    let cf_name = match cf_atom_to_name(cf) {
-        Some(name) => name,
-        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
     };
 
     let db_guard = db_ref
@@ -333,39 +6903,58 @@ fn put<'a>(
         None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let cf_handle = match db.cf_handle(cf_name) {
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let cf_handle = match counters_cf(db, cf) {
         Some(cf) => cf,
         None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
     };
 
-    match db.put_cf(&cf_handle, key.as_slice(), value.as_slice()) {
+    let mut batch = WriteBatch::default();
+    batch.merge_cf(&cf_handle, key.as_slice(), delta.to_be_bytes());
+
+    match db.write_opt(batch, &write_opts) {
         Ok(()) => Ok(atoms::ok().encode(env)),
-        Err(e) => Ok((atoms::error(), (atoms::put_failed(), e.to_string())).encode(env)),
+        Err(e) => Ok(write_error(env, atoms::merge_failed(), e)),
     }
 }
 
-/// Deletes a key from a column family.
+/// Adds a signed delta to a counter and returns the new value.
+///
+/// Calls are serialized per database, so concurrent callers each see their
+/// own increment and never receive the same value. Plain `merge` calls
+/// running at the same time may be folded into the returned value.
 ///
 /// # Arguments
 /// * `db_ref` - The database reference
-/// * `cf` - The column family atom
-/// * `key` - The key to delete
+/// * `cf` - The column family atom; must be `:counters`
+/// * `key` - The counter key
+/// * `delta` - Signed amount to add; results wrap around at 2^64
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
 ///
 /// # Returns
-/// * `:ok` on success (even if key didn't exist)
+/// * `{:ok, value}` with the counter value after the increment
 /// * `{:error, :already_closed}` if database is closed
-/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
-/// * `{:error, {:delete_failed, reason}}` on other errors
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if column family is not `:counters`
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:merge_failed, reason}}` on other errors
 #[rustler::nif(schedule = "DirtyCpu")]
-fn delete<'a>(
+fn increment_and_get<'a>(
     env: Env<'a>,
     db_ref: ResourceArc<DbRef>,
-    cf: rustler::Atom,
+    cf: Term<'a>,
     key: Binary<'a>,
+    delta: i64,
+    options: Term<'a>,
 ) -> NifResult<Term<'a>> {
-    let cf_name = match cf_atom_to_name(cf) {
-        Some(name) => name,
-        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
     };
 
     let db_guard = db_ref
@@ -378,42 +6967,129 @@ fn delete<'a>(
         None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let cf_handle = match db.cf_handle(cf_name) {
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let cf_handle = match counters_cf(db, cf) {
         Some(cf) => cf,
         None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
     };
 
-    match db.delete_cf(&cf_handle, key.as_slice()) {
-        Ok(()) => Ok(atoms::ok().encode(env)),
-        Err(e) => Ok((atoms::error(), (atoms::delete_failed(), e.to_string())).encode(env)),
+    let _counter_guard = db_ref
+        .counter_lock
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let mut batch = WriteBatch::default();
+    batch.merge_cf(&cf_handle, key.as_slice(), delta.to_be_bytes());
+    if let Err(e) = db.write_opt(batch, &write_opts) {
+        return Ok(write_error(env, atoms::merge_failed(), e));
+    }
+
+    match db.get_cf(&cf_handle, key.as_slice()) {
+        Ok(Some(value)) => match <[u8; 8]>::try_from(value.as_slice()) {
+            Ok(bytes) => Ok((atoms::ok(), u64::from_be_bytes(bytes)).encode(env)),
+            Err(_) => Ok((atoms::error(), (atoms::merge_failed(), "malformed counter value")).encode(env)),
+        },
+        Ok(None) => Ok((atoms::error(), (atoms::merge_failed(), "counter missing after merge")).encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::merge_failed(), e.to_string())).encode(env)),
     }
 }
 
-/// Checks if a key exists in a column family.
+// ============================================================================
+// Dictionary Operations
+// ============================================================================
+
+/// Reads the persisted sequence counters from `str2id` and skips the
+/// recovery safety margin, as `SequenceCounter` does when it starts.
+///
+/// Fails on a counter that is not 8 bytes rather than restarting it, which
+/// would hand out IDs that are already in use.
+fn load_sequences(db: &Database, str2id: &ColumnFamily) -> Result<[u64; 3], String> {
+    let mut counters = [0; 3];
+    for (counter, key) in counters.iter_mut().zip(SEQUENCE_COUNTER_KEYS) {
+        let persisted = match db.get_cf(str2id, key.as_bytes()).map_err(|e| e.to_string())? {
+            Some(value) => value
+                .try_into()
+                .map(u64::from_be_bytes)
+                .map_err(|_| "malformed sequence counter".to_string())?,
+            None => 0,
+        };
+        *counter = persisted + SEQUENCE_SAFETY_MARGIN;
+    }
+    Ok(counters)
+}
+
+/// Maps a dictionary type atom to its sequence counter index.
+fn sequence_index(type_atom: rustler::Atom) -> Option<usize> {
+    if type_atom == atoms::uri() {
+        Some(0)
+    } else if type_atom == atoms::bnode() {
+        Some(1)
+    } else if type_atom == atoms::literal() {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Looks up or creates the IDs of encoded dictionary terms.
+///
+/// Existing terms are read from `str2id`. Missing terms get the next ID of
+/// their type from sequence counters held by the database handle, loaded on
+/// first use from the persisted `__seq_counter__*` keys plus the recovery
+/// safety margin. Both mapping directions and the advanced counters are
+/// written in one WriteBatch, and calls are serialized, so a term is never
+/// assigned two IDs by concurrent callers. A term repeated in the input gets
+/// the same ID each time.
+///
+/// Must not be mixed with a `SequenceCounter` allocating IDs for the same
+/// database.
 ///
 /// # Arguments
 /// * `db_ref` - The database reference
-/// * `cf` - The column family atom
-/// * `key` - The key to check
+/// * `terms` - List of `str2id` keys as built by `StringToId.encode_term/1`
+/// * `types` - List of `:uri`, `:bnode` or `:literal`, one per term
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
 ///
 /// # Returns
-/// * `{:ok, true}` if key exists
-/// * `{:ok, false}` if key doesn't exist
+/// * `{:ok, ids}` with one ID per term, in input order
 /// * `{:error, :already_closed}` if database is closed
-/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
-/// * `{:error, {:get_failed, reason}}` on other errors
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, :length_mismatch}` if `terms` and `types` differ in length
+/// * `{:error, {:invalid_type, type}}` if a type is not a dictionary type
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :sequence_overflow}` if a sequence counter is exhausted
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:get_failed, reason}}` on read errors or a malformed sequence counter
+/// * `{:error, {:batch_failed, reason}}` on write errors
 #[rustler::nif(schedule = "DirtyCpu")]
-fn exists<'a>(
+fn dictionary_get_or_create<'a>(
     env: Env<'a>,
     db_ref: ResourceArc<DbRef>,
-    cf: rustler::Atom,
-    key: Binary<'a>,
+    terms: Vec<Binary<'a>>,
+    types: Vec<rustler::Atom>,
+    options: Term<'a>,
 ) -> NifResult<Term<'a>> {
-    let cf_name = match cf_atom_to_name(cf) {
-        Some(name) => name,
-        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
     };
 
+    if terms.len() != types.len() {
+        return Ok((atoms::error(), atoms::length_mismatch()).encode(env));
+    }
+
+    let mut type_indices = Vec::with_capacity(types.len());
+    for type_atom in types {
+        match sequence_index(type_atom) {
+            Some(index) => type_indices.push(index),
+            None => return Ok((atoms::error(), (atoms::invalid_type(), type_atom)).encode(env)),
+        }
+    }
+
     let db_guard = db_ref
         .db
         .read()
@@ -424,36 +7100,168 @@ fn exists<'a>(
         None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let cf_handle = match db.cf_handle(cf_name) {
-        Some(cf) => cf,
-        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let (str2id, id2str) = match (db.cf_handle("str2id"), db.cf_handle("id2str")) {
+        (Some(str2id), Some(id2str)) => (str2id, id2str),
+        _ => return Ok((atoms::error(), (atoms::invalid_cf(), atoms::str2id())).encode(env)),
     };
 
-    // Check if key exists by attempting to get it
-    match db.get_cf(&cf_handle, key.as_slice()) {
-        Ok(Some(_)) => Ok((atoms::ok(), true).encode(env)),
-        Ok(None) => Ok((atoms::ok(), false).encode(env)),
-        Err(e) => Ok((atoms::error(), (atoms::get_failed(), e.to_string())).encode(env)),
+    let mut sequences = db_ref
+        .sequences
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    let mut counters = match *sequences {
+        Some(counters) => counters,
+        None => match load_sequences(db, str2id) {
+            Ok(counters) => counters,
+            Err(reason) => return Ok((atoms::error(), (atoms::get_failed(), reason)).encode(env)),
+        },
+    };
+
+    let mut batch = WriteBatch::default();
+    let mut created: HashMap<&[u8], u64> = HashMap::new();
+    let mut ids = Vec::with_capacity(terms.len());
+
+    for (term, type_index) in terms.iter().zip(type_indices) {
+        let key = term.as_slice();
+        if let Some(id) = created.get(key) {
+            ids.push(*id);
+            continue;
+        }
+
+        match db.get_cf(str2id, key) {
+            Ok(Some(value)) => match <[u8; 8]>::try_from(value.as_slice()) {
+                Ok(bytes) => ids.push(u64::from_be_bytes(bytes)),
+                Err(_) => return Ok((atoms::error(), (atoms::get_failed(), "malformed str2id entry")).encode(env)),
+            },
+            Ok(None) => {
+                if counters[type_index] >= MAX_SEQUENCE {
+                    return Ok((atoms::error(), atoms::sequence_overflow()).encode(env));
+                }
+                counters[type_index] += 1;
+                let id = ((type_index as u64 + 1) << 60) | counters[type_index];
+                batch.put_cf(str2id, key, id.to_be_bytes());
+                batch.put_cf(id2str, id.to_be_bytes(), key);
+                created.insert(key, id);
+                ids.push(id);
+            }
+            Err(e) => return Ok((atoms::error(), (atoms::get_failed(), e.to_string())).encode(env)),
+        }
+    }
+
+    if !created.is_empty() {
+        for (counter, key) in counters.iter().zip(SEQUENCE_COUNTER_KEYS) {
+            batch.put_cf(str2id, key, counter.to_be_bytes());
+        }
+        if let Err(e) = db.write_opt(batch, &write_opts) {
+            return Ok(write_error(env, atoms::batch_failed(), e));
+        }
+        *sequences = Some(counters);
     }
+
+    Ok((atoms::ok(), ids).encode(env))
 }
 
-/// Atomically writes multiple key-value pairs to column families.
-///
-/// # Arguments
-/// * `db_ref` - The database reference
-/// * `operations` - List of `{cf, key, value}` tuples
-///
-/// # Returns
-/// * `:ok` on success
-/// * `{:error, :already_closed}` if database is closed
-/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
-/// * `{:error, {:batch_failed, reason}}` on other errors
-#[rustler::nif(schedule = "DirtyCpu")]
-fn write_batch<'a>(
+// ============================================================================
+// Quad Operations
+// ============================================================================
+
+/// Size in bytes of one packed `(g, s, p, o)` quad record.
+const QUAD_RECORD_SIZE: usize = 4 * TERM_ID_SIZE;
+
+/// Graph indices with the key layout of the triple after the leading graph
+/// ID, as positions in `(s, p, o)`.
+const GRAPH_INDICES: [(&str, [usize; 3]); 3] = [
+    ("gspo", [0, 1, 2]),
+    ("gpos", [1, 2, 0]),
+    ("gosp", [2, 0, 1]),
+];
+
+/// Index of quads keyed by the SPO triple followed by the graph ID, so the
+/// graphs containing a triple are contiguous.
+const SPOG_CF: &str = "spog";
+
+/// Decodes a list of `{g, s, p, o}` integer tuples, or a binary of packed
+/// 32-byte big-endian records, into `(graph, triple)` pairs.
+fn decode_quads(quads: Term) -> Option<Vec<(u64, [u64; 3])>> {
+    if let Ok(binary) = quads.decode::<Binary>() {
+        if binary.len() % QUAD_RECORD_SIZE != 0 {
+            return None;
+        }
+        return binary
+            .as_slice()
+            .chunks_exact(QUAD_RECORD_SIZE)
+            .map(|record| {
+                Some((
+                    key_component(record, 0)?,
+                    [
+                        key_component(record, 1)?,
+                        key_component(record, 2)?,
+                        key_component(record, 3)?,
+                    ],
+                ))
+            })
+            .collect();
+    }
+
+    let iter: ListIterator = quads.decode().ok()?;
+    iter.map(|item| {
+        item.decode::<(u64, u64, u64, u64)>()
+            .ok()
+            .map(|(g, s, p, o)| (g, [s, p, o]))
+    })
+    .collect()
+}
+
+/// Builds the key of a quad in a graph index: the graph ID followed by the
+/// triple in the given layout.
+fn graph_key(graph: u64, triple: &[u64; 3], layout: &[usize; 3]) -> [u8; QUAD_RECORD_SIZE] {
+    let mut key = [0u8; QUAD_RECORD_SIZE];
+    key[..TERM_ID_SIZE].copy_from_slice(&graph.to_be_bytes());
+    key[TERM_ID_SIZE..].copy_from_slice(&triple_key(triple, layout));
+    key
+}
+
+/// Builds the `spog` key of a quad.
+fn spog_key(graph: u64, triple: &[u64; 3]) -> [u8; QUAD_RECORD_SIZE] {
+    let mut key = [0u8; QUAD_RECORD_SIZE];
+    key[..TRIPLE_RECORD_SIZE].copy_from_slice(&triple_key(triple, &[0, 1, 2]));
+    key[TRIPLE_RECORD_SIZE..].copy_from_slice(&graph.to_be_bytes());
+    key
+}
+
+/// Looks up the handles of the graph indices followed by `spog`.
+fn quad_cf_handles(db: &Database) -> Result<Vec<&ColumnFamily>, &'static str> {
+    GRAPH_INDICES
+        .iter()
+        .map(|(cf_name, _)| *cf_name)
+        .chain([SPOG_CF])
+        .map(|cf_name| db.cf_handle(cf_name).ok_or(cf_name))
+        .collect()
+}
+
+/// Writes or deletes every index key of the given quads in one WriteBatch.
+fn write_quads<'a>(
     env: Env<'a>,
     db_ref: ResourceArc<DbRef>,
-    operations: Term<'a>,
+    quads: Term<'a>,
+    options: Term<'a>,
+    delete: bool,
 ) -> NifResult<Term<'a>> {
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
+    let quads = match decode_quads(quads) {
+        Some(quads) => quads,
+        None => return Ok((atoms::error(), atoms::invalid_quad()).encode(env)),
+    };
+
     let db_guard = db_ref
         .db
         .read()
@@ -464,99 +7272,130 @@ fn write_batch<'a>(
         None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let mut batch = WriteBatch::default();
-
-    // Parse the list of operations
-    let iter: ListIterator = operations
-        .decode()
-        .map_err(|_| rustler::Error::Term(Box::new("expected list")))?;
-
-    for item in iter {
-        // Each item should be a tuple {cf, key, value}
-        let tuple = rustler::types::tuple::get_tuple(item)
-            .map_err(|_| rustler::Error::Term(Box::new("expected tuple")))?;
-
-        if tuple.len() == 3 {
-            // Simple format: {cf, key, value} - treat as put
-            let cf_atom: rustler::Atom = tuple[0]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected atom for cf")))?;
-            let key: Binary = tuple[1]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected binary for key")))?;
-            let value: Binary = tuple[2]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected binary for value")))?;
-
-            let cf_name = match cf_atom_to_name(cf_atom) {
-                Some(name) => name,
-                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_atom)).encode(env)),
-            };
-
-            let cf_handle = match db.cf_handle(cf_name) {
-                Some(cf) => cf,
-                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_atom)).encode(env)),
-            };
-
-            batch.put_cf(&cf_handle, key.as_slice(), value.as_slice());
-        } else if tuple.len() == 4 {
-            // Extended format: {:put, cf, key, value}
-            let op_atom: rustler::Atom = tuple[0]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected atom for operation")))?;
-            let cf_atom: rustler::Atom = tuple[1]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected atom for cf")))?;
-            let key: Binary = tuple[2]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected binary for key")))?;
-            let value: Binary = tuple[3]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected binary for value")))?;
-
-            if op_atom != atoms::put() {
-                return Ok((atoms::error(), (atoms::invalid_operation(), op_atom)).encode(env));
-            }
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
 
-            let cf_name = match cf_atom_to_name(cf_atom) {
-                Some(name) => name,
-                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_atom)).encode(env)),
-            };
+    let cf_handles = match quad_cf_handles(db) {
+        Ok(cf_handles) => cf_handles,
+        Err(cf_name) => return Ok((atoms::error(), (atoms::invalid_cf(), cf_name)).encode(env)),
+    };
 
-            let cf_handle = match db.cf_handle(cf_name) {
-                Some(cf) => cf,
-                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_atom)).encode(env)),
-            };
+    let mut batch = WriteBatch::default();
 
-            batch.put_cf(&cf_handle, key.as_slice(), value.as_slice());
-        } else {
-            return Ok((atoms::error(), atoms::invalid_operation()).encode(env));
+    for (graph, triple) in &quads {
+        let keys = GRAPH_INDICES
+            .iter()
+            .map(|(_, layout)| graph_key(*graph, triple, layout))
+            .chain([spog_key(*graph, triple)]);
+
+        for (cf_handle, key) in cf_handles.iter().zip(keys) {
+            if delete {
+                batch.delete_cf(cf_handle, key);
+            } else {
+                batch.put_cf(cf_handle, key, []);
+            }
         }
     }
 
-    match db.write(batch) {
+    match db.write_opt(batch, &write_opts) {
         Ok(()) => Ok(atoms::ok().encode(env)),
-        Err(e) => Ok((atoms::error(), (atoms::batch_failed(), e.to_string())).encode(env)),
+        Err(e) => Ok(write_error(env, atoms::batch_failed(), e)),
     }
 }
 
-/// Atomically deletes multiple keys from column families.
+/// Atomically inserts quads into the GSPO, GPOS, GOSP and SPOG indices.
+///
+/// Quads belong to named graphs; the default graph stays in the triple
+/// indices. All keys are written with empty values in a single WriteBatch.
 ///
 /// # Arguments
 /// * `db_ref` - The database reference
-/// * `operations` - List of `{cf, key}` tuples
+/// * `quads` - List of `{g, s, p, o}` term ID tuples, or a binary of packed
+///   32-byte records (four big-endian u64 IDs each)
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
 ///
 /// # Returns
 /// * `:ok` on success
 /// * `{:error, :already_closed}` if database is closed
-/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, :invalid_quad}` if a quad is not four u64 IDs
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
 /// * `{:error, {:batch_failed, reason}}` on other errors
 #[rustler::nif(schedule = "DirtyCpu")]
-fn delete_batch<'a>(
+fn quad_insert<'a>(
     env: Env<'a>,
     db_ref: ResourceArc<DbRef>,
-    operations: Term<'a>,
+    quads: Term<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    write_quads(env, db_ref, quads, options, false)
+}
+
+/// Atomically deletes quads from the GSPO, GPOS, GOSP and SPOG indices.
+///
+/// Deleting a quad that does not exist is a no-op.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `quads` - List of `{g, s, p, o}` term ID tuples, or a binary of packed
+///   32-byte records (four big-endian u64 IDs each)
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
+///
+/// # Returns
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, :invalid_quad}` if a quad is not four u64 IDs
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:batch_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn quad_delete<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    quads: Term<'a>,
+    options: Term<'a>,
+) -> NifResult<Term<'a>> {
+    write_quads(env, db_ref, quads, options, true)
+}
+
+/// Atomically deletes a named graph.
+///
+/// The graph's block of each graph index is removed with one range
+/// tombstone; the SPOG keys are found by scanning the GSPO block and deleted
+/// in the same WriteBatch. Quads inserted concurrently with the call may
+/// survive in some indices.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `graph` - The graph term ID
+/// * `options` - Keyword list or map of write options: `sync`, `disable_wal`,
+///   `no_slowdown` and `low_pri`
+///
+/// # Returns
+/// * `{:ok, count}` with the number of quads deleted
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+/// * `{:error, {:iterator_failed, reason}}` on read errors
+/// * `{:error, :write_stall}` if `no_slowdown` is set and the write would stall
+/// * `{:error, {:batch_failed, reason}}` on other errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn delete_graph<'a>(
+    env: Env<'a>,
+    db_ref: ResourceArc<DbRef>,
+    graph: u64,
+    options: Term<'a>,
 ) -> NifResult<Term<'a>> {
+    let write_opts = match decode_write_options(env, options) {
+        Ok(write_opts) => write_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
+    };
+
     let db_guard = db_ref
         .db
         .read()
@@ -567,68 +7406,83 @@ fn delete_batch<'a>(
         None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let mut batch = WriteBatch::default();
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
 
-    // Parse the list of operations
-    let iter: ListIterator = operations
-        .decode()
-        .map_err(|_| rustler::Error::Term(Box::new("expected list")))?;
+    let cf_handles = match quad_cf_handles(db) {
+        Ok(cf_handles) => cf_handles,
+        Err(cf_name) => return Ok((atoms::error(), (atoms::invalid_cf(), cf_name)).encode(env)),
+    };
+    let spog_handle = cf_handles[GRAPH_INDICES.len()];
 
-    for item in iter {
-        // Each item should be a tuple {cf, key}
-        let tuple = rustler::types::tuple::get_tuple(item)
-            .map_err(|_| rustler::Error::Term(Box::new("expected tuple")))?;
+    let (cf_name, layout) = GRAPH_INDICES[0];
+    let prefix = graph.to_be_bytes();
+    let read_opts = db_ref.prefix_read_options(cf_name, &prefix)?;
+    let mut iterator = db.raw_iterator_cf_opt(&cf_handles[0], read_opts);
+    iterator.seek(&prefix);
 
-        if tuple.len() != 2 {
-            return Ok((atoms::error(), atoms::invalid_operation()).encode(env));
+    let mut batch = WriteBatch::default();
+    let mut count: u64 = 0;
+    while let Some(key) = iterator.key().filter(|key| key.starts_with(&prefix)) {
+        let record = &key[TERM_ID_SIZE..];
+        let mut triple = [0u64; 3];
+        for (index, &slot) in layout.iter().enumerate() {
+            triple[slot] = key_component(record, index).unwrap_or_default();
         }
+        batch.delete_cf(&spog_handle, spog_key(graph, &triple));
+        count += 1;
+        iterator.next();
+    }
 
-        let cf_atom: rustler::Atom = tuple[0]
-            .decode()
-            .map_err(|_| rustler::Error::Term(Box::new("expected atom for cf")))?;
-        let key: Binary = tuple[1]
-            .decode()
-            .map_err(|_| rustler::Error::Term(Box::new("expected binary for key")))?;
-
-        let cf_name = match cf_atom_to_name(cf_atom) {
-            Some(name) => name,
-            None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_atom)).encode(env)),
-        };
-
-        let cf_handle = match db.cf_handle(cf_name) {
-            Some(cf) => cf,
-            None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_atom)).encode(env)),
-        };
+    if let Err(e) = iterator.status() {
+        return Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env));
+    }
+    drop(iterator);
 
-        batch.delete_cf(&cf_handle, key.as_slice());
+    // An all-0xff ID has no successor; pad past every key that starts with it
+    let end = prefix_successor(&prefix).unwrap_or_else(|| {
+        let mut end = prefix.to_vec();
+        end.resize(QUAD_RECORD_SIZE + 1, 0xff);
+        end
+    });
+    for cf_handle in &cf_handles[..GRAPH_INDICES.len()] {
+        batch.delete_range_cf(cf_handle, &prefix[..], &end[..]);
     }
 
-    match db.write(batch) {
-        Ok(()) => Ok(atoms::ok().encode(env)),
-        Err(e) => Ok((atoms::error(), (atoms::batch_failed(), e.to_string())).encode(env)),
+    match db.write_opt(batch, &write_opts) {
+        Ok(()) => Ok((atoms::ok(), count).encode(env)),
+        Err(e) => Ok(write_error(env, atoms::batch_failed(), e)),
     }
 }
 
-/// Atomically performs mixed put and delete operations.
+/// Starts a lookup of the triples matching a pattern in one named graph.
+///
+/// Works like `lookup_pattern` over the GSPO, GPOS and GOSP indices with the
+/// graph ID as the leading key component, so only that graph is scanned.
+/// Triples are read with `lookup_pattern_next`.
 ///
 /// # Arguments
 /// * `db_ref` - The database reference
-/// * `operations` - List of operations:
-///   - `{:put, cf, key, value}` for puts
-///   - `{:delete, cf, key}` for deletes
+/// * `graph` - The graph term ID
+/// * `pattern` - `{s, p, o}` tuple of term IDs or `nil` for unbound positions
 ///
 /// # Returns
-/// * `:ok` on success
+/// * `{:ok, lookup_ref}` on success
+/// * `{:error, {:invalid_pattern, pattern}}` if the pattern is malformed
 /// * `{:error, :already_closed}` if database is closed
-/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
-/// * `{:error, {:invalid_operation, op}}` if operation type is invalid
-/// * `{:error, {:batch_failed, reason}}` on other errors
 #[rustler::nif(schedule = "DirtyCpu")]
-fn mixed_batch<'a>(
+fn lookup_graph_pattern<'a>(
     env: Env<'a>,
     db_ref: ResourceArc<DbRef>,
-    operations: Term<'a>,
+    graph: u64,
+    pattern: Term<'a>,
 ) -> NifResult<Term<'a>> {
+    let bound = match decode_lookup_pattern(pattern) {
+        Some(bound) => bound,
+        None => return Ok((atoms::error(), (atoms::invalid_pattern(), pattern)).encode(env)),
+    };
+
     let db_guard = db_ref
         .db
         .read()
@@ -639,117 +7493,262 @@ fn mixed_batch<'a>(
         None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let mut batch = WriteBatch::default();
+    match open_pattern_lookup(env, db, &*db_ref.prefix_lengths()?, None, &GRAPH_INDICES, bound, &graph.to_be_bytes()) {
+        Ok(lookup) => {
+            let lookup_ref = ResourceArc::new(PatternLookupRef {
+                lookup: Mutex::new(Some(lookup)),
+                _source: ReadSource::Db { _db_ref: Arc::new(db_ref.clone()) },
+            });
+            Ok((atoms::ok(), lookup_ref).encode(env))
+        }
+        Err(reason) => Ok((atoms::error(), reason).encode(env)),
+    }
+}
 
-    // Parse the list of operations
-    let iter: ListIterator = operations
-        .decode()
-        .map_err(|_| rustler::Error::Term(Box::new("expected list")))?;
+/// Lists the named graphs that contain a triple, in ID order.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `triple` - `{s, p, o}` tuple of term IDs
+///
+/// # Returns
+/// * `{:ok, graphs}` with the graph term IDs
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :invalid_triple}` if the triple is not three u64 IDs
+/// * `{:error, {:iterator_failed, reason}}` on read errors
+#[rustler::nif(schedule = "DirtyCpu")]
+fn triple_graphs<'a>(env: Env<'a>, db_ref: ResourceArc<DbRef>, triple: Term<'a>) -> NifResult<Term<'a>> {
+    let triple = match triple.decode::<(u64, u64, u64)>() {
+        Ok((s, p, o)) => [s, p, o],
+        Err(_) => return Ok((atoms::error(), atoms::invalid_triple()).encode(env)),
+    };
 
-    for item in iter {
-        let tuple = rustler::types::tuple::get_tuple(item)
-            .map_err(|_| rustler::Error::Term(Box::new("expected tuple")))?;
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
 
-        if tuple.is_empty() {
-            return Ok((atoms::error(), atoms::invalid_operation()).encode(env));
-        }
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    };
 
-        let op_atom: rustler::Atom = tuple[0]
-            .decode()
-            .map_err(|_| rustler::Error::Term(Box::new("expected atom for operation")))?;
+    let cf_handle = match db.cf_handle(SPOG_CF) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), SPOG_CF)).encode(env)),
+    };
 
-        if op_atom == atoms::put() {
-            // {:put, cf, key, value}
-            if tuple.len() != 4 {
-                return Ok((atoms::error(), atoms::invalid_operation()).encode(env));
-            }
+    let prefix = triple_key(&triple, &[0, 1, 2]);
+    let read_opts = db_ref.prefix_read_options(SPOG_CF, &prefix)?;
+    let mut iterator = db.raw_iterator_cf_opt(&cf_handle, read_opts);
+    iterator.seek(&prefix);
 
-            let cf_atom: rustler::Atom = tuple[1]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected atom for cf")))?;
-            let key: Binary = tuple[2]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected binary for key")))?;
-            let value: Binary = tuple[3]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected binary for value")))?;
+    let mut graphs = Vec::new();
+    while let Some(key) = iterator.key().filter(|key| key.starts_with(&prefix)) {
+        if let Some(graph) = key_component(key, 3) {
+            graphs.push(graph);
+        }
+        iterator.next();
+    }
 
-            let cf_name = match cf_atom_to_name(cf_atom) {
-                Some(name) => name,
-                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_atom)).encode(env)),
-            };
+    match iterator.status() {
+        Ok(()) => Ok((atoms::ok(), graphs).encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env)),
+    }
+}
 
-            let cf_handle = match db.cf_handle(cf_name) {
-                Some(cf) => cf,
-                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_atom)).encode(env)),
-            };
+// ============================================================================
+// Change Data Capture
+// ============================================================================
 
-            batch.put_cf(&cf_handle, key.as_slice(), value.as_slice());
-        } else if op_atom == atoms::delete() {
-            // {:delete, cf, key}
-            if tuple.len() != 3 {
-                return Ok((atoms::error(), atoms::invalid_operation()).encode(env));
-            }
+/// Size of a serialized write batch header: the 8-byte sequence number
+/// followed by the 4-byte record count.
+const WRITE_BATCH_HEADER_SIZE: usize = 12;
+
+/// Kind of change recorded in a write batch.
+#[derive(Clone, Copy, PartialEq)]
+enum WalOp {
+    Put,
+    Delete,
+    Merge,
+    DeleteRange,
+}
 
-            let cf_atom: rustler::Atom = tuple[1]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected atom for cf")))?;
-            let key: Binary = tuple[2]
-                .decode()
-                .map_err(|_| rustler::Error::Term(Box::new("expected binary for key")))?;
+/// One change decoded from a serialized write batch.
+struct WalRecord<'a> {
+    op: WalOp,
+    cf_id: u32,
+    key: &'a [u8],
+    /// The value, merge operand or range end; `None` for deletes
+    value: Option<&'a [u8]>,
+}
 
-            let cf_name = match cf_atom_to_name(cf_atom) {
-                Some(name) => name,
-                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_atom)).encode(env)),
-            };
+/// Cursor over the varints and length-prefixed slices of a write batch.
+struct WalReader<'a> {
+    data: &'a [u8],
+}
 
-            let cf_handle = match db.cf_handle(cf_name) {
-                Some(cf) => cf,
-                None => return Ok((atoms::error(), (atoms::invalid_cf(), cf_atom)).encode(env)),
-            };
+impl<'a> WalReader<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.data.split_first()?;
+        self.data = rest;
+        Some(byte)
+    }
 
-            batch.delete_cf(&cf_handle, key.as_slice());
-        } else {
-            return Ok((atoms::error(), (atoms::invalid_operation(), op_atom)).encode(env));
+    fn varint32(&mut self) -> Result<u32, &'static str> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte().ok_or("truncated varint")?;
+            value |= u32::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
         }
+        Err("malformed varint")
     }
 
-    match db.write(batch) {
-        Ok(()) => Ok(atoms::ok().encode(env)),
-        Err(e) => Ok((atoms::error(), (atoms::batch_failed(), e.to_string())).encode(env)),
+    fn slice(&mut self) -> Result<&'a [u8], &'static str> {
+        let len = self.varint32()? as usize;
+        if self.data.len() < len {
+            return Err("truncated record");
+        }
+        let (slice, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(slice)
     }
 }
 
-// ============================================================================
-// Iterator Operations
-// ============================================================================
+/// Decodes the records of a serialized write batch.
+///
+/// The rocksdb crate's `WriteBatchIterator` only reports records of the
+/// default column family, so the batch representation is parsed directly
+/// (see RocksDB's `db/write_batch.cc`). Transaction markers, no-ops and log
+/// data carry no changes and are skipped.
+fn decode_write_batch(data: &[u8]) -> Result<Vec<WalRecord<'_>>, &'static str> {
+    let mut reader = WalReader {
+        data: data.get(WRITE_BATCH_HEADER_SIZE..).ok_or("truncated header")?,
+    };
+    let mut records = Vec::new();
+
+    while let Some(tag) = reader.byte() {
+        let (op, has_cf) = match tag {
+            // Deletion, single deletion and their column family variants
+            0x0 | 0x7 => (WalOp::Delete, false),
+            0x4 | 0x8 => (WalOp::Delete, true),
+            0x1 => (WalOp::Put, false),
+            0x5 => (WalOp::Put, true),
+            0x2 => (WalOp::Merge, false),
+            0x6 => (WalOp::Merge, true),
+            0xF => (WalOp::DeleteRange, false),
+            0xE => (WalOp::DeleteRange, true),
+            // Log data, and the end, commit and rollback markers with their XID
+            0x3 | 0xA | 0xB | 0xC => {
+                reader.slice()?;
+                continue;
+            }
+            // No-op and begin-prepare markers
+            0x9 | 0xD | 0x12 | 0x13 => continue,
+            _ => return Err("unsupported record type"),
+        };
 
-/// Creates a prefix iterator for a column family.
+        let cf_id = if has_cf { reader.varint32()? } else { 0 };
+        let key = reader.slice()?;
+        let value = if op == WalOp::Delete { None } else { Some(reader.slice()?) };
+        records.push(WalRecord { op, cf_id, key, value });
+    }
+
+    Ok(records)
+}
+
+/// Returns the RocksDB ID of a column family.
 ///
-/// The iterator returns all key-value pairs where the key starts with the given prefix.
-/// The iterator must be closed with `iterator_close` when done.
+/// The C API does not expose column family IDs, so a probe record is
+/// written into a batch and its ID read back.
+fn cf_id(cf: &ColumnFamily) -> Option<u32> {
+    let mut batch = WriteBatch::default();
+    batch.put_cf(cf, b"", b"");
+    let records = decode_write_batch(batch.data()).ok()?;
+    records.first().map(|record| record.cf_id)
+}
+
+/// Maps the IDs of the column families on disk to their names.
+fn wal_cf_names(db: &Database, path: &str) -> HashMap<u32, String> {
+    DB::list_cf(&Options::default(), path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|name| Some((db.cf_handle(&name).and_then(cf_id)?, name)))
+        .collect()
+}
+
+/// Encodes a decoded write batch as `{seq, [{op, cf, key, value}]}`.
+///
+/// Column families unknown to `cf_names` are identified by their integer ID.
+fn encode_wal_batch<'a>(env: Env<'a>, seq: u64, records: &[WalRecord], cf_names: &HashMap<u32, String>) -> Term<'a> {
+    let binary = |bytes: &[u8]| {
+        let mut binary = NewBinary::new(env, bytes.len());
+        binary.as_mut_slice().copy_from_slice(bytes);
+        Binary::from(binary)
+    };
+
+    let ops: Vec<Term> = records
+        .iter()
+        .map(|record| {
+            let op = match record.op {
+                WalOp::Put => atoms::put(),
+                WalOp::Delete => atoms::delete(),
+                WalOp::Merge => atoms::merge(),
+                WalOp::DeleteRange => atoms::delete_range(),
+            };
+            let cf = match cf_names.get(&record.cf_id) {
+                Some(name) => encode_cf_name(env, name),
+                None => record.cf_id.encode(env),
+            };
+            (op, cf, binary(record.key), record.value.map(binary)).encode(env)
+        })
+        .collect();
+
+    (seq, ops).encode(env)
+}
+
+/// Returns the sequence number of the last committed write.
 ///
 /// # Arguments
 /// * `db_ref` - The database reference
-/// * `cf` - The column family atom
-/// * `prefix` - The prefix to iterate over
 ///
 /// # Returns
-/// * `{:ok, iterator_ref}` on success
+/// * `{:ok, seq}` on success
 /// * `{:error, :already_closed}` if database is closed
-/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
-#[rustler::nif(schedule = "DirtyCpu")]
-fn prefix_iterator<'a>(
-    env: Env<'a>,
-    db_ref: ResourceArc<DbRef>,
-    cf: rustler::Atom,
-    prefix: Binary<'a>,
-) -> NifResult<Term<'a>> {
-    let cf_name = match cf_atom_to_name(cf) {
-        Some(name) => name,
-        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
-    };
+#[rustler::nif]
+fn latest_sequence_number(env: Env, db_ref: ResourceArc<DbRef>) -> NifResult<Term> {
+    let db_guard = db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    match db_guard.as_ref() {
+        Some(db) => Ok((atoms::ok(), db.latest_sequence_number()).encode(env)),
+        None => Ok((atoms::error(), atoms::already_closed()).encode(env)),
+    }
+}
 
+/// Opens an iterator over the write batches committed after `seq`.
+///
+/// The iterator reads the WAL, so it covers the batches still in the live or
+/// archived WAL files; writes made with `disable_wal: true` are not included.
+/// Keep WAL files around with the `wal_ttl_seconds` and `wal_size_limit_mb`
+/// open options to resume from an older sequence number after a restart.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `seq` - Sequence number of the last batch already processed
+///
+/// # Returns
+/// * `{:ok, wal_iterator_ref}` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:wal_iterator_failed, reason}}` if `seq` is not yet written
+///   or no longer in the WAL
+#[rustler::nif(schedule = "DirtyIo")]
+fn updates_since(env: Env, db_ref: ResourceArc<DbRef>, seq: u64) -> NifResult<Term> {
     let db_guard = db_ref
         .db
         .read()
@@ -760,107 +7759,155 @@ fn prefix_iterator<'a>(
         None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let cf_handle = match db.cf_handle(cf_name) {
-        Some(cf) => cf,
-        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
-    };
-
-    let prefix_bytes = prefix.as_slice().to_vec();
-
-    // Create the iterator with prefix mode
-    // Safety: We use unsafe to extend the lifetime because we're storing
-    // the db_ref Arc which keeps the database alive
-    let iterator = db.iterator_cf(&cf_handle, IteratorMode::From(&prefix_bytes, rocksdb::Direction::Forward));
-
-    // SAFETY: We keep the DbRef alive via Arc, so the iterator remains valid
-    let static_iterator: DBIteratorWithThreadMode<'static, DB> = unsafe {
-        std::mem::transmute(iterator)
+    let iterator = match db.get_updates_since(seq) {
+        Ok(iterator) => iterator,
+        Err(e) => return Ok((atoms::error(), (atoms::wal_iterator_failed(), e.to_string())).encode(env)),
     };
 
-    let iter_ref = ResourceArc::new(IteratorRef {
-        iterator: Mutex::new(Some(static_iterator)),
-        _db_ref: Arc::new(db_ref.clone()),
-        prefix: prefix_bytes,
-        cf_name: cf_name.to_string(),
+    let wal_ref = ResourceArc::new(WalIteratorRef {
+        iterator: Mutex::new(Some(WalIterator(iterator))),
+        cf_names: wal_cf_names(db, &db_ref.path),
+        db_ref: Arc::new(db_ref.clone()),
     });
 
-    Ok((atoms::ok(), iter_ref).encode(env))
+    Ok((atoms::ok(), wal_ref).encode(env))
 }
 
-/// Gets the next key-value pair from the iterator.
+/// Gets up to `n` write batches from a WAL iterator.
+///
+/// Each batch is returned as `{seq, ops}` where `seq` is the sequence number
+/// of its first write and `ops` lists its changes in write order as
+/// `{:put, cf, key, value}`, `{:delete, cf, key, nil}`,
+/// `{:merge, cf, key, operand}` or `{:delete_range, cf, start_key, end_key}`.
+/// Column families created after the iterator was opened are reported by
+/// their integer RocksDB ID.
+///
+/// Once the iterator returns `:end` it does not see later writes; open a new
+/// one from the last returned `seq` to continue.
 ///
 /// # Arguments
-/// * `iter_ref` - The iterator reference
+/// * `wal_ref` - The WAL iterator reference
+/// * `n` - Maximum number of batches to return
 ///
 /// # Returns
-/// * `{:ok, key, value}` if there's a next item with matching prefix
-/// * `:end` if the iterator is exhausted or prefix no longer matches
-/// * `{:error, :iterator_closed}` if iterator was closed
-/// * `{:error, {:iterator_failed, reason}}` on error
-#[rustler::nif(schedule = "DirtyCpu")]
-fn iterator_next<'a>(env: Env<'a>, iter_ref: ResourceArc<IteratorRef>) -> NifResult<Term<'a>> {
-    let mut iter_guard = iter_ref
+/// * `{:ok, batches, :more}` if `n` batches were read and more may follow
+/// * `{:ok, batches, :end}` if the iterator reached the end of the WAL
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :iterator_closed}` if the iterator was closed
+/// * `{:error, {:wal_iterator_failed, reason}}` on read errors
+#[rustler::nif(schedule = "DirtyIo")]
+fn updates_next<'a>(env: Env<'a>, wal_ref: ResourceArc<WalIteratorRef>, n: usize) -> NifResult<Term<'a>> {
+    let db_guard = wal_ref
+        .db_ref
+        .db
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+
+    if db_guard.is_none() {
+        return Ok((atoms::error(), atoms::already_closed()).encode(env));
+    }
+
+    let mut iterator_guard = wal_ref
         .iterator
         .lock()
         .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
 
-    let iterator = match iter_guard.as_mut() {
-        Some(iter) => iter,
+    let iterator = match iterator_guard.as_mut() {
+        Some(WalIterator(iterator)) => iterator,
         None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
     };
 
-    match iterator.next() {
-        Some(Ok((key, value))) => {
-            // Check if key still has the prefix
-            if !key.starts_with(&iter_ref.prefix) {
-                return Ok(atoms::iterator_end().encode(env));
+    let mut batches = Vec::with_capacity(n.min(4096));
+    while batches.len() < n {
+        let (seq, batch) = match iterator.next() {
+            Some(Ok(entry)) => entry,
+            Some(Err(e)) => return Ok((atoms::error(), (atoms::wal_iterator_failed(), e.to_string())).encode(env)),
+            None => {
+                return match iterator.status() {
+                    Ok(()) => Ok((atoms::ok(), batches, atoms::end()).encode(env)),
+                    Err(e) => Ok((atoms::error(), (atoms::wal_iterator_failed(), e.to_string())).encode(env)),
+                };
             }
+        };
 
-            let mut key_binary = NewBinary::new(env, key.len());
-            key_binary.as_mut_slice().copy_from_slice(&key);
-
-            let mut value_binary = NewBinary::new(env, value.len());
-            value_binary.as_mut_slice().copy_from_slice(&value);
-
-            Ok((atoms::ok(), Binary::from(key_binary), Binary::from(value_binary)).encode(env))
-        }
-        Some(Err(e)) => {
-            Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env))
+        match decode_write_batch(batch.data()) {
+            Ok(records) => batches.push(encode_wal_batch(env, seq, &records, &wal_ref.cf_names)),
+            Err(reason) => return Ok((atoms::error(), (atoms::wal_iterator_failed(), reason)).encode(env)),
         }
-        None => Ok(atoms::iterator_end().encode(env)),
     }
+
+    Ok((atoms::ok(), batches, atoms::more()).encode(env))
 }
 
-/// Seeks the iterator to a specific key.
-///
-/// After seeking, the iterator will return keys >= target that match the prefix.
-/// This is essential for Leapfrog Triejoin in Phase 3.
+/// Closes a WAL iterator and releases it.
 ///
 /// # Arguments
-/// * `iter_ref` - The iterator reference
-/// * `target` - The key to seek to
+/// * `wal_ref` - The WAL iterator reference
 ///
 /// # Returns
 /// * `:ok` on success
-/// * `{:error, :iterator_closed}` if iterator was closed
-#[rustler::nif(schedule = "DirtyCpu")]
-fn iterator_seek<'a>(
-    env: Env<'a>,
-    iter_ref: ResourceArc<IteratorRef>,
-    target: Binary<'a>,
-) -> NifResult<Term<'a>> {
-    let mut iter_guard = iter_ref
+/// * `{:error, :iterator_closed}` if already closed
+#[rustler::nif]
+fn updates_close(env: Env, wal_ref: ResourceArc<WalIteratorRef>) -> NifResult<Term> {
+    let mut iterator_guard = wal_ref
         .iterator
         .lock()
         .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
 
-    let iterator = match iter_guard.as_mut() {
-        Some(iter) => iter,
-        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    if iterator_guard.is_none() {
+        return Ok((atoms::error(), atoms::iterator_closed()).encode(env));
+    }
+
+    *iterator_guard = None;
+
+    Ok(atoms::ok().encode(env))
+}
+
+// ============================================================================
+// Properties and Statistics
+// ============================================================================
+
+/// Summary of one RocksDB histogram, encoded as an Elixir map.
+#[derive(rustler::NifMap)]
+struct HistogramStats {
+    count: u64,
+    sum: u64,
+    min: f64,
+    max: f64,
+    average: f64,
+    median: f64,
+    p95: f64,
+    p99: f64,
+    std_dev: f64,
+}
+
+/// Statistics collected since the database was opened, encoded as an Elixir map.
+#[derive(rustler::NifMap)]
+struct DbStatistics {
+    tickers: HashMap<String, u64>,
+    histograms: HashMap<String, HistogramStats>,
+}
+
+/// Reads a property of a column family with `read`.
+///
+/// `cf` may be `nil` for database-wide properties, which are read through
+/// the default column family.
+fn read_property<'a, T: Encoder>(
+    env: Env<'a>,
+    db_ref: &DbRef,
+    cf: Term<'a>,
+    name: &str,
+    read: impl Fn(&Database, &ColumnFamily, &str) -> Result<Option<T>, Error>,
+) -> NifResult<Term<'a>> {
+    let cf_name = if rustler::types::atom::nil() == cf {
+        DEFAULT_COLUMN_FAMILY_NAME.to_string()
+    } else {
+        match cf_term_to_name(cf) {
+            Some(name) => name,
+            None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+        }
     };
 
-    // Get the database reference to create a new iterator at the seek position
-    let db_ref = &iter_ref._db_ref;
     let db_guard = db_ref
         .db
         .read()
@@ -871,185 +7918,588 @@ fn iterator_seek<'a>(
         None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let cf_handle = match db.cf_handle(&iter_ref.cf_name) {
+    let cf_handle = match db.cf_handle(&cf_name) {
         Some(cf) => cf,
-        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
-    };
-
-    // Create new iterator at the seek position
-    let target_bytes = target.as_slice();
-    let new_iterator = db.iterator_cf(&cf_handle, IteratorMode::From(target_bytes, rocksdb::Direction::Forward));
-
-    // SAFETY: We keep the DbRef alive via Arc, so the iterator remains valid
-    let static_iterator: DBIteratorWithThreadMode<'static, DB> = unsafe {
-        std::mem::transmute(new_iterator)
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
     };
 
-    // Replace the old iterator
-    *iterator = static_iterator;
-
-    Ok(atoms::ok().encode(env))
+    match read(db, cf_handle, name) {
+        Ok(Some(value)) => Ok((atoms::ok(), value).encode(env)),
+        Ok(None) => Ok(atoms::not_found().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::property_failed(), e.to_string())).encode(env)),
+    }
 }
 
-/// Closes the iterator and releases resources.
+/// Gets a RocksDB property as a string.
 ///
 /// # Arguments
-/// * `iter_ref` - The iterator reference
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary, or `nil` for
+///   database-wide properties
+/// * `name` - The property name, e.g. `rocksdb.stats`
 ///
 /// # Returns
-/// * `:ok` on success
-/// * `{:error, :iterator_closed}` if already closed
-#[rustler::nif]
-fn iterator_close<'a>(env: Env<'a>, iter_ref: ResourceArc<IteratorRef>) -> NifResult<Term<'a>> {
-    let mut iter_guard = iter_ref
-        .iterator
-        .lock()
-        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
-
-    if iter_guard.is_none() {
-        return Ok((atoms::error(), atoms::iterator_closed()).encode(env));
-    }
-
-    // Drop the iterator
-    *iter_guard = None;
+/// * `{:ok, value}` on success
+/// * `:not_found` if the property is unknown
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:property_failed, reason}}` if the name is malformed
+#[rustler::nif(schedule = "DirtyCpu")]
+fn get_property<'a>(env: Env<'a>, db_ref: ResourceArc<DbRef>, cf: Term<'a>, name: String) -> NifResult<Term<'a>> {
+    read_property(env, &db_ref, cf, &name, |db, cf, name| db.property_value_cf(cf, name))
+}
 
-    Ok(atoms::ok().encode(env))
+/// Gets a numeric RocksDB property.
+///
+/// # Arguments
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary, or `nil` for
+///   database-wide properties
+/// * `name` - The property name, e.g. `rocksdb.estimate-num-keys`
+///
+/// # Returns
+/// * `{:ok, value}` on success
+/// * `:not_found` if the property is unknown
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:property_failed, reason}}` if the name is malformed or the value is not numeric
+#[rustler::nif(schedule = "DirtyCpu")]
+fn get_int_property<'a>(env: Env<'a>, db_ref: ResourceArc<DbRef>, cf: Term<'a>, name: String) -> NifResult<Term<'a>> {
+    read_property(env, &db_ref, cf, &name, |db, cf, name| db.property_int_value_cf(cf, name))
 }
 
-/// Collects all remaining key-value pairs from an iterator into a list.
+/// Returns the statistics collected since the database was opened.
 ///
-/// This is a convenience function that consumes the iterator and returns
-/// all matching entries. Useful for small result sets where streaming isn't needed.
+/// Tickers are cumulative counters and histograms summarize the recorded
+/// values, both keyed by their RocksDB name such as `rocksdb.block.cache.hit`
+/// or `rocksdb.db.get.micros`.
 ///
 /// # Arguments
-/// * `iter_ref` - The iterator reference
+/// * `db_ref` - The database reference, opened with `enable_statistics: true`
 ///
 /// # Returns
-/// * `{:ok, [{key, value}, ...]}` with all remaining entries
-/// * `{:error, :iterator_closed}` if iterator was closed
-/// * `{:error, {:iterator_failed, reason}}` on error
+/// * `{:ok, %{tickers: map, histograms: map}}` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :statistics_disabled}` if the database was opened without statistics
 #[rustler::nif(schedule = "DirtyCpu")]
-fn iterator_collect<'a>(env: Env<'a>, iter_ref: ResourceArc<IteratorRef>) -> NifResult<Term<'a>> {
-    let mut iter_guard = iter_ref
-        .iterator
-        .lock()
+fn statistics(env: Env, db_ref: ResourceArc<DbRef>) -> NifResult<Term> {
+    let db_guard = db_ref
+        .db
+        .read()
         .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
 
-    let iterator = match iter_guard.as_mut() {
-        Some(iter) => iter,
-        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    if db_guard.is_none() {
+        return Ok((atoms::error(), atoms::already_closed()).encode(env));
+    }
+
+    let opts = match &db_ref.statistics {
+        Some(opts) => opts,
+        None => return Ok((atoms::error(), atoms::statistics_disabled()).encode(env)),
     };
 
-    let mut results: Vec<Term<'a>> = Vec::new();
+    let tickers = Ticker::iter()
+        .map(|&ticker| (ticker.name().to_string(), opts.get_ticker_count(ticker)))
+        .collect();
 
-    for result in iterator.by_ref() {
-        match result {
-            Ok((key, value)) => {
-                // Check if key still has the prefix
-                if !key.starts_with(&iter_ref.prefix) {
-                    break;
-                }
+    let histograms = Histogram::iter()
+        .map(|&histogram| {
+            let data = opts.get_histogram_data(histogram);
+            let stats = HistogramStats {
+                count: data.count(),
+                sum: data.sum(),
+                min: data.min(),
+                max: data.max(),
+                average: data.average(),
+                median: data.median(),
+                p95: data.p95(),
+                p99: data.p99(),
+                std_dev: data.std_dev(),
+            };
+            (histogram.name().to_string(), stats)
+        })
+        .collect();
 
-                let mut key_binary = NewBinary::new(env, key.len());
-                key_binary.as_mut_slice().copy_from_slice(&key);
+    Ok((atoms::ok(), DbStatistics { tickers, histograms }).encode(env))
+}
 
-                let mut value_binary = NewBinary::new(env, value.len());
-                value_binary.as_mut_slice().copy_from_slice(&value);
+// ============================================================================
+// Event Listener
+// ============================================================================
+//
+// The `event_listener` open option installs a RocksDB `EventListener`,
+// implemented in `event_listener.cc` because the rocksdb crate does not
+// expose one. RocksDB calls it from its background threads, and for write
+// stalls also from the thread that changed the condition, which may be a BEAM
+// scheduler where `OwnedEnv::send_and_clear` cannot be used. The callbacks
+// below therefore only queue each event for a dedicated thread that sends
+// them on to the listener process in order.
+
+/// Details of a finished flush, filled in by `event_listener.cc`.
+#[repr(C)]
+struct RawFlushInfo {
+    cf_name: *const u8,
+    cf_name_len: usize,
+    file_path: *const u8,
+    file_path_len: usize,
+    job_id: c_int,
+    num_entries: u64,
+    num_deletions: u64,
+    smallest_seqno: u64,
+    largest_seqno: u64,
+    triggered_writes_slowdown: bool,
+    triggered_writes_stop: bool,
+}
 
-                results.push((Binary::from(key_binary), Binary::from(value_binary)).encode(env));
-            }
-            Err(e) => {
-                return Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env));
-            }
+/// Details of a finished compaction, filled in by `event_listener.cc`.
+#[repr(C)]
+struct RawCompactionInfo {
+    cf_name: *const u8,
+    cf_name_len: usize,
+    /// Empty when the compaction succeeded
+    status: *const u8,
+    status_len: usize,
+    job_id: c_int,
+    base_input_level: c_int,
+    output_level: c_int,
+    input_files: usize,
+    output_files: usize,
+    input_bytes: u64,
+    output_bytes: u64,
+    input_records: u64,
+    output_records: u64,
+    elapsed_micros: u64,
+    manual: bool,
+    trivial_move: bool,
+}
+
+/// A changed write stall condition, filled in by `event_listener.cc`.
+#[repr(C)]
+struct RawStallInfo {
+    cf_name: *const u8,
+    cf_name_len: usize,
+    previous: c_int,
+    current: c_int,
+}
+
+/// Callbacks of the C++ listener, each passed the state given to
+/// `triple_store_add_event_listener`.
+#[repr(C)]
+struct EventCallbacks {
+    flush_completed: unsafe extern "C" fn(*mut c_void, *const RawFlushInfo),
+    compaction_completed: unsafe extern "C" fn(*mut c_void, *const RawCompactionInfo),
+    stall_conditions_changed: unsafe extern "C" fn(*mut c_void, *const RawStallInfo),
+    /// Called once the database and every copy of its options are gone
+    destroy: unsafe extern "C" fn(*mut c_void),
+}
+
+extern "C" {
+    /// Appends a listener with `state` and `callbacks` to `options.listeners`.
+    fn triple_store_add_event_listener(options: *mut c_void, state: *mut c_void, callbacks: *const EventCallbacks);
+}
+
+static EVENT_CALLBACKS: EventCallbacks = EventCallbacks {
+    flush_completed: on_flush_completed,
+    compaction_completed: on_compaction_completed,
+    stall_conditions_changed: on_stall_conditions_changed,
+    destroy: drop_event_sender,
+};
+
+/// Same fields as `rocksdb::Options`, up to the pointee of each `Arc`, which
+/// keeps its `rocksdb_options_t` pointer private. Both structs get the same
+/// layout, and the assertion below catches a rocksdb release that changes
+/// the fields.
+struct OptionsLayout {
+    inner: *mut c_void,
+    _outlive: OptionsOutliveLayout,
+}
+
+struct OptionsOutliveLayout {
+    _env: Option<Arc<()>>,
+    _row_cache: Option<Arc<()>>,
+    _block_based: Option<BlockBasedOutliveLayout>,
+    _write_buffer_manager: Option<Arc<()>>,
+}
+
+struct BlockBasedOutliveLayout {
+    _block_cache: Option<Arc<()>>,
+}
+
+const _: () = assert!(std::mem::size_of::<Options>() == std::mem::size_of::<OptionsLayout>());
+
+/// Returns the `rocksdb_options_t` behind `opts`.
+fn raw_options(opts: &mut Options) -> *mut c_void {
+    // Safety: `OptionsLayout` has the layout of `Options`, see above
+    unsafe { (*(opts as *mut Options).cast::<OptionsLayout>()).inner }
+}
+
+/// Database-wide write stall condition, as in RocksDB's `WriteStallCondition`.
+#[derive(Clone, Copy)]
+enum WriteStall {
+    Normal,
+    Delayed,
+    Stopped,
+}
+
+impl WriteStall {
+    /// Decodes the condition numbering of `event_listener.cc`.
+    fn from_raw(condition: c_int) -> Self {
+        match condition {
+            1 => WriteStall::Delayed,
+            2 => WriteStall::Stopped,
+            _ => WriteStall::Normal,
         }
     }
 
-    Ok((atoms::ok(), results).encode(env))
+    fn atom(self) -> rustler::Atom {
+        match self {
+            WriteStall::Normal => atoms::normal(),
+            WriteStall::Delayed => atoms::delayed(),
+            WriteStall::Stopped => atoms::stopped(),
+        }
+    }
 }
 
-// ============================================================================
-// Snapshot Operations
-// ============================================================================
+/// An event sent to the `event_listener` process.
+enum DbEvent {
+    FlushCompleted {
+        cf: String,
+        file_path: String,
+        job_id: i32,
+        num_entries: u64,
+        num_deletions: u64,
+        smallest_seqno: u64,
+        largest_seqno: u64,
+        triggered_writes_slowdown: bool,
+        triggered_writes_stop: bool,
+    },
+    CompactionCompleted {
+        cf: String,
+        status: Result<(), String>,
+        job_id: i32,
+        base_input_level: i32,
+        output_level: i32,
+        input_files: usize,
+        output_files: usize,
+        input_bytes: u64,
+        output_bytes: u64,
+        input_records: u64,
+        output_records: u64,
+        elapsed_micros: u64,
+        manual: bool,
+        trivial_move: bool,
+    },
+    StallConditionsChanged {
+        cf: String,
+        previous: WriteStall,
+        current: WriteStall,
+    },
+}
 
-/// Creates a snapshot of the database for consistent point-in-time reads.
+impl DbEvent {
+    /// Encodes the event as `{:rocksdb_event, kind, info}`.
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let (kind, info): (rustler::Atom, Vec<(rustler::Atom, Term<'a>)>) = match self {
+            DbEvent::FlushCompleted {
+                cf,
+                file_path,
+                job_id,
+                num_entries,
+                num_deletions,
+                smallest_seqno,
+                largest_seqno,
+                triggered_writes_slowdown,
+                triggered_writes_stop,
+            } => (
+                atoms::flush_completed(),
+                vec![
+                    (atoms::cf(), encode_cf_name(env, cf)),
+                    (atoms::file_path(), file_path.encode(env)),
+                    (atoms::job_id(), job_id.encode(env)),
+                    (atoms::num_entries(), num_entries.encode(env)),
+                    (atoms::num_deletions(), num_deletions.encode(env)),
+                    (atoms::smallest_seqno(), smallest_seqno.encode(env)),
+                    (atoms::largest_seqno(), largest_seqno.encode(env)),
+                    (atoms::triggered_writes_slowdown(), triggered_writes_slowdown.encode(env)),
+                    (atoms::triggered_writes_stop(), triggered_writes_stop.encode(env)),
+                ],
+            ),
+            DbEvent::CompactionCompleted {
+                cf,
+                status,
+                job_id,
+                base_input_level,
+                output_level,
+                input_files,
+                output_files,
+                input_bytes,
+                output_bytes,
+                input_records,
+                output_records,
+                elapsed_micros,
+                manual,
+                trivial_move,
+            } => {
+                let status = match status {
+                    Ok(()) => atoms::ok().encode(env),
+                    Err(reason) => (atoms::error(), reason).encode(env),
+                };
+                (
+                    atoms::compaction_completed(),
+                    vec![
+                        (atoms::cf(), encode_cf_name(env, cf)),
+                        (atoms::status(), status),
+                        (atoms::job_id(), job_id.encode(env)),
+                        (atoms::base_input_level(), base_input_level.encode(env)),
+                        (atoms::output_level(), output_level.encode(env)),
+                        (atoms::input_files(), input_files.encode(env)),
+                        (atoms::output_files(), output_files.encode(env)),
+                        (atoms::input_bytes(), input_bytes.encode(env)),
+                        (atoms::output_bytes(), output_bytes.encode(env)),
+                        (atoms::input_records(), input_records.encode(env)),
+                        (atoms::output_records(), output_records.encode(env)),
+                        (atoms::elapsed_micros(), elapsed_micros.encode(env)),
+                        (atoms::manual(), manual.encode(env)),
+                        (atoms::trivial_move(), trivial_move.encode(env)),
+                    ],
+                )
+            }
+            DbEvent::StallConditionsChanged { cf, previous, current } => (
+                atoms::stall_conditions_changed(),
+                vec![
+                    (atoms::cf(), encode_cf_name(env, cf)),
+                    (atoms::previous(), previous.atom().encode(env)),
+                    (atoms::current(), current.atom().encode(env)),
+                ],
+            ),
+        };
+
+        let info = Term::map_from_pairs(env, &info).unwrap_or_else(|_| Term::map_new(env));
+        (atoms::rocksdb_event(), kind, info).encode(env)
+    }
+}
+
+/// Installs an event listener on `opts` that reports to `listener`.
 ///
-/// A snapshot provides a consistent view of the database at the time of creation.
-/// All reads using the snapshot will see the same data, regardless of subsequent
-/// writes to the database.
+/// Starts the thread that sends the events. It stops once the database and
+/// every copy of `opts` are dropped, or when the listener process has exited.
+fn add_event_listener(opts: &mut Options, listener: rustler::LocalPid) -> std::io::Result<()> {
+    let (sender, receiver) = mpsc::channel::<DbEvent>();
+
+    std::thread::Builder::new().name("rocksdb-events".to_string()).spawn(move || {
+        let mut owned_env = rustler::OwnedEnv::new();
+        for event in receiver {
+            if owned_env.send_and_clear(&listener, |env| event.encode(env)).is_err() {
+                return;
+            }
+        }
+    })?;
+
+    let state = Box::into_raw(Box::new(sender)).cast::<c_void>();
+    // Safety: `state` stays valid until the listener calls `drop_event_sender`
+    unsafe { triple_store_add_event_listener(raw_options(opts), state, &EVENT_CALLBACKS) };
+    Ok(())
+}
+
+/// Copies a string passed by `event_listener.cc`.
 ///
-/// # Arguments
-/// * `db_ref` - The database reference
+/// # Safety
+/// `ptr` must point to `len` readable bytes.
+unsafe fn raw_string(ptr: *const u8, len: usize) -> String {
+    if len == 0 {
+        return String::new();
+    }
+    String::from_utf8_lossy(std::slice::from_raw_parts(ptr, len)).into_owned()
+}
+
+/// Queues an event for the sending thread. Fails only once that thread has
+/// stopped, when there is nobody left to report to.
 ///
-/// # Returns
-/// * `{:ok, snapshot_ref}` on success
-/// * `{:error, :already_closed}` if database is closed
-#[rustler::nif(schedule = "DirtyCpu")]
-fn snapshot<'a>(env: Env<'a>, db_ref: ResourceArc<DbRef>) -> NifResult<Term<'a>> {
-    let db_guard = db_ref
-        .db
-        .read()
-        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+/// # Safety
+/// `state` must be the sender passed to `triple_store_add_event_listener`.
+unsafe fn queue_event(state: *mut c_void, event: DbEvent) {
+    let sender = &*state.cast::<mpsc::Sender<DbEvent>>();
+    let _ = sender.send(event);
+}
 
-    let db = match db_guard.as_ref() {
-        Some(db) => db,
-        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
+unsafe extern "C" fn on_flush_completed(state: *mut c_void, info: *const RawFlushInfo) {
+    let info = &*info;
+    let event = DbEvent::FlushCompleted {
+        cf: raw_string(info.cf_name, info.cf_name_len),
+        file_path: raw_string(info.file_path, info.file_path_len),
+        job_id: info.job_id,
+        num_entries: info.num_entries,
+        num_deletions: info.num_deletions,
+        smallest_seqno: info.smallest_seqno,
+        largest_seqno: info.largest_seqno,
+        triggered_writes_slowdown: info.triggered_writes_slowdown,
+        triggered_writes_stop: info.triggered_writes_stop,
     };
+    queue_event(state, event);
+}
 
-    let snap = db.snapshot();
+unsafe extern "C" fn on_compaction_completed(state: *mut c_void, info: *const RawCompactionInfo) {
+    let info = &*info;
+    let status = match raw_string(info.status, info.status_len) {
+        reason if reason.is_empty() => Ok(()),
+        reason => Err(reason),
+    };
+    let event = DbEvent::CompactionCompleted {
+        cf: raw_string(info.cf_name, info.cf_name_len),
+        status,
+        job_id: info.job_id,
+        base_input_level: info.base_input_level,
+        output_level: info.output_level,
+        input_files: info.input_files,
+        output_files: info.output_files,
+        input_bytes: info.input_bytes,
+        output_bytes: info.output_bytes,
+        input_records: info.input_records,
+        output_records: info.output_records,
+        elapsed_micros: info.elapsed_micros,
+        manual: info.manual,
+        trivial_move: info.trivial_move,
+    };
+    queue_event(state, event);
+}
 
-    // SAFETY: We keep the DbRef alive via Arc, so the snapshot remains valid
-    let static_snapshot: SnapshotWithThreadMode<'static, DB> = unsafe {
-        std::mem::transmute(snap)
+unsafe extern "C" fn on_stall_conditions_changed(state: *mut c_void, info: *const RawStallInfo) {
+    let info = &*info;
+    let event = DbEvent::StallConditionsChanged {
+        cf: raw_string(info.cf_name, info.cf_name_len),
+        previous: WriteStall::from_raw(info.previous),
+        current: WriteStall::from_raw(info.current),
     };
+    queue_event(state, event);
+}
 
-    let snap_ref = ResourceArc::new(SnapshotRef {
-        snapshot: Mutex::new(Some(static_snapshot)),
-        db_ref: Arc::new(db_ref.clone()),
-    });
+unsafe extern "C" fn drop_event_sender(state: *mut c_void) {
+    drop(Box::from_raw(state.cast::<mpsc::Sender<DbEvent>>()));
+}
 
-    Ok((atoms::ok(), snap_ref).encode(env))
+// ============================================================================
+// Maintenance Operations
+// ============================================================================
+
+/// Decodes the options of `compact_range` into `CompactOptions`.
+///
+/// On failure returns `{:invalid_option, key}` for an unknown key or a value
+/// of the wrong type.
+fn decode_compact_options<'a>(env: Env<'a>, options: Term<'a>) -> Result<CompactOptions, Term<'a>> {
+    let pairs = option_pairs(options).map_err(|term| (atoms::invalid_option(), term).encode(env))?;
+    let mut compact_opts = CompactOptions::default();
+
+    for (key, value) in pairs {
+        let valid = if key == atoms::exclusive() {
+            value.decode().map(|flag| compact_opts.set_exclusive_manual_compaction(flag)).is_ok()
+        } else if key == atoms::change_level() {
+            value.decode().map(|flag| compact_opts.set_change_level(flag)).is_ok()
+        } else if key == atoms::target_level() {
+            value.decode().map(|level| compact_opts.set_target_level(level)).is_ok()
+        } else if key == atoms::bottommost_level_compaction() {
+            decode_bottommost_level_compaction(value)
+                .map(|mode| compact_opts.set_bottommost_level_compaction(mode))
+                .is_some()
+        } else {
+            false
+        };
+        if !valid {
+            return Err((atoms::invalid_option(), key).encode(env));
+        }
+    }
+
+    Ok(compact_opts)
 }
 
-/// Gets a value from a column family using a snapshot.
+/// Decodes a `bottommost_level_compaction` atom.
+fn decode_bottommost_level_compaction(value: Term) -> Option<BottommostLevelCompaction> {
+    let mode: rustler::Atom = value.decode().ok()?;
+    if mode == atoms::skip() {
+        Some(BottommostLevelCompaction::Skip)
+    } else if mode == atoms::if_have_compaction_filter() {
+        Some(BottommostLevelCompaction::IfHaveCompactionFilter)
+    } else if mode == atoms::force() {
+        Some(BottommostLevelCompaction::Force)
+    } else if mode == atoms::force_optimized() {
+        Some(BottommostLevelCompaction::ForceOptimized)
+    } else {
+        None
+    }
+}
+
+/// Converts a `set_options` key or value to the string form RocksDB parses.
+fn option_string(term: Term) -> Option<String> {
+    if let Ok(flag) = term.decode::<bool>() {
+        Some(flag.to_string())
+    } else if let Ok(number) = term.decode::<i64>() {
+        Some(number.to_string())
+    } else if let Ok(number) = term.decode::<f64>() {
+        Some(number.to_string())
+    } else if term.is_atom() {
+        term.atom_to_string().ok()
+    } else {
+        let binary: Binary = term.decode().ok()?;
+        String::from_utf8(binary.as_slice().to_vec()).ok()
+    }
+}
+
+/// Decodes the `{key, value}` list or map of `set_options` into strings.
+/// On failure returns the offending term.
+fn decode_set_options(options: Term) -> Result<Vec<(String, String)>, Term> {
+    let pairs: Vec<(Term, Term)> = if options.is_map() {
+        let iter: rustler::types::map::MapIterator = options.decode().map_err(|_| options)?;
+        iter.collect()
+    } else {
+        options.decode().map_err(|_| options)?
+    };
+
+    pairs
+        .into_iter()
+        .map(|(key, value)| Ok((option_string(key).ok_or(key)?, option_string(value).ok_or(key)?)))
+        .collect()
+}
+
+/// Compacts the keys in `[start, end]` of a column family.
 ///
-/// This provides point-in-time consistent reads - the value returned
-/// is what existed at the time the snapshot was created.
+/// Rewrites the overlapping SST files down to the bottommost level, dropping
+/// deleted and overwritten entries along the way. Memtables overlapping the
+/// range are flushed first. Blocks until the compaction has finished; the
+/// database lock is held meanwhile, so a concurrent `close` waits for it.
 ///
 /// # Arguments
-/// * `snapshot_ref` - The snapshot reference
-/// * `cf` - The column family atom
-/// * `key` - The key as a binary
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `start` - Inclusive start key, or `nil` for unbounded
+/// * `end` - Inclusive end key, or `nil` for unbounded
+/// * `options` - Keyword list or map: `exclusive`, `change_level`,
+///   `target_level` and `bottommost_level_compaction` (`:skip`,
+///   `:if_have_compaction_filter`, `:force` or `:force_optimized`)
 ///
 /// # Returns
-/// * `{:ok, value}` if found
-/// * `:not_found` if key doesn't exist
-/// * `{:error, :snapshot_released}` if snapshot was released
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
 /// * `{:error, {:invalid_cf, cf}}` if column family is invalid
-/// * `{:error, {:get_failed, reason}}` on other errors
-#[rustler::nif(schedule = "DirtyCpu")]
-fn snapshot_get<'a>(
+/// * `{:error, {:invalid_option, key}}` if an option is unknown or malformed
+#[rustler::nif(schedule = "DirtyIo")]
+fn compact_range<'a>(
     env: Env<'a>,
-    snapshot_ref: ResourceArc<SnapshotRef>,
-    cf: rustler::Atom,
-    key: Binary<'a>,
+    db_ref: ResourceArc<DbRef>,
+    cf: Term<'a>,
+    start: Option<Binary<'a>>,
+    end: Option<Binary<'a>>,
+    options: Term<'a>,
 ) -> NifResult<Term<'a>> {
-    let cf_name = match cf_atom_to_name(cf) {
-        Some(name) => name,
-        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    let compact_opts = match decode_compact_options(env, options) {
+        Ok(compact_opts) => compact_opts,
+        Err(reason) => return Ok((atoms::error(), reason).encode(env)),
     };
 
-    let snap_guard = snapshot_ref
-        .snapshot
-        .lock()
-        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
-
-    let snapshot = match snap_guard.as_ref() {
-        Some(snap) => snap,
-        None => return Ok((atoms::error(), atoms::snapshot_released()).encode(env)),
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
     };
 
-    // Get the database to access column family handles
-    let db_guard = snapshot_ref.db_ref
+    let db_guard = db_ref
         .db
         .read()
         .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
@@ -1059,64 +8509,43 @@ fn snapshot_get<'a>(
         None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let cf_handle = match db.cf_handle(cf_name) {
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let cf_handle = match db.cf_handle(&cf_name) {
         Some(cf) => cf,
         None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
     };
 
-    // Use ReadOptions with snapshot
-    let mut read_opts = ReadOptions::default();
-    read_opts.set_snapshot(snapshot);
+    let start = start.as_ref().map(|key| key.as_slice());
+    let end = end.as_ref().map(|key| key.as_slice());
+    db.compact_range_cf_opt(cf_handle, start, end, &compact_opts);
 
-    match db.get_cf_opt(&cf_handle, key.as_slice(), &read_opts) {
-        Ok(Some(value)) => {
-            let mut binary = NewBinary::new(env, value.len());
-            binary.as_mut_slice().copy_from_slice(&value);
-            Ok((atoms::ok(), Binary::from(binary)).encode(env))
-        }
-        Ok(None) => Ok(atoms::not_found().encode(env)),
-        Err(e) => Ok((atoms::error(), (atoms::get_failed(), e.to_string())).encode(env)),
-    }
+    Ok(atoms::ok().encode(env))
 }
 
-/// Creates a prefix iterator over a snapshot.
-///
-/// The iterator returns all key-value pairs where the key starts with the given prefix,
-/// using the consistent view from the snapshot.
+/// Flushes the memtables of a column family to SST files.
 ///
 /// # Arguments
-/// * `snapshot_ref` - The snapshot reference
-/// * `cf` - The column family atom
-/// * `prefix` - The prefix to iterate over
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `wait` - Whether to block until the flush has finished
 ///
 /// # Returns
-/// * `{:ok, iterator_ref}` on success
-/// * `{:error, :snapshot_released}` if snapshot was released
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
 /// * `{:error, {:invalid_cf, cf}}` if column family is invalid
-#[rustler::nif(schedule = "DirtyCpu")]
-fn snapshot_prefix_iterator<'a>(
-    env: Env<'a>,
-    snapshot_ref: ResourceArc<SnapshotRef>,
-    cf: rustler::Atom,
-    prefix: Binary<'a>,
-) -> NifResult<Term<'a>> {
-    let cf_name = match cf_atom_to_name(cf) {
+/// * `{:error, {:flush_failed, reason}}` on failure
+#[rustler::nif(schedule = "DirtyIo")]
+fn flush<'a>(env: Env<'a>, db_ref: ResourceArc<DbRef>, cf: Term<'a>, wait: bool) -> NifResult<Term<'a>> {
+    let cf_name = match cf_term_to_name(cf) {
         Some(name) => name,
         None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
     };
 
-    let snap_guard = snapshot_ref
-        .snapshot
-        .lock()
-        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
-
-    let snapshot = match snap_guard.as_ref() {
-        Some(snap) => snap,
-        None => return Ok((atoms::error(), atoms::snapshot_released()).encode(env)),
-    };
-
-    // Get the database to access column family handles
-    let db_guard = snapshot_ref.db_ref
+    let db_guard = db_ref
         .db
         .read()
         .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
@@ -1126,193 +8555,115 @@ fn snapshot_prefix_iterator<'a>(
         None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let cf_handle = match db.cf_handle(cf_name) {
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
+
+    let cf_handle = match db.cf_handle(&cf_name) {
         Some(cf) => cf,
         None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
     };
 
-    let prefix_bytes = prefix.as_slice().to_vec();
-
-    // Create read options with snapshot
-    let mut read_opts = ReadOptions::default();
-    read_opts.set_snapshot(snapshot);
-
-    // Create the iterator with snapshot
-    let iterator = db.iterator_cf_opt(
-        &cf_handle,
-        read_opts,
-        IteratorMode::From(&prefix_bytes, rocksdb::Direction::Forward),
-    );
-
-    // SAFETY: We keep the SnapshotRef alive via Arc, so the iterator remains valid
-    let static_iterator: DBIteratorWithThreadMode<'static, DB> = unsafe {
-        std::mem::transmute(iterator)
-    };
-
-    let iter_ref = ResourceArc::new(SnapshotIteratorRef {
-        iterator: Mutex::new(Some(static_iterator)),
-        _snapshot_ref: Arc::new(snapshot_ref.clone()),
-        prefix: prefix_bytes,
-        cf_name: cf_name.to_string(),
-    });
+    let mut flush_opts = FlushOptions::default();
+    flush_opts.set_wait(wait);
 
-    Ok((atoms::ok(), iter_ref).encode(env))
+    match db.flush_cf_opt(cf_handle, &flush_opts) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::flush_failed(), e.to_string())).encode(env)),
+    }
 }
 
-/// Gets the next key-value pair from a snapshot iterator.
+/// Writes the buffered WAL entries to the WAL file.
+///
+/// With `sync` the WAL file is also fsynced, making every earlier write that
+/// was not made with `sync: true` durable at once.
 ///
 /// # Arguments
-/// * `iter_ref` - The snapshot iterator reference
+/// * `db_ref` - The database reference
+/// * `sync` - Whether to also fsync the WAL file
 ///
 /// # Returns
-/// * `{:ok, key, value}` if there's a next item with matching prefix
-/// * `:iterator_end` if the iterator is exhausted or prefix no longer matches
-/// * `{:error, :iterator_closed}` if iterator was closed
-/// * `{:error, {:iterator_failed, reason}}` on error
-#[rustler::nif(schedule = "DirtyCpu")]
-fn snapshot_iterator_next<'a>(
-    env: Env<'a>,
-    iter_ref: ResourceArc<SnapshotIteratorRef>,
-) -> NifResult<Term<'a>> {
-    let mut iter_guard = iter_ref
-        .iterator
-        .lock()
+/// * `:ok` on success
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:flush_failed, reason}}` on failure
+#[rustler::nif(schedule = "DirtyIo")]
+fn flush_wal(env: Env, db_ref: ResourceArc<DbRef>, sync: bool) -> NifResult<Term> {
+    let db_guard = db_ref
+        .db
+        .read()
         .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
 
-    let iterator = match iter_guard.as_mut() {
-        Some(iter) => iter,
-        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    match iterator.next() {
-        Some(Ok((key, value))) => {
-            // Check if key still has the prefix
-            if !key.starts_with(&iter_ref.prefix) {
-                return Ok(atoms::iterator_end().encode(env));
-            }
-
-            let mut key_binary = NewBinary::new(env, key.len());
-            key_binary.as_mut_slice().copy_from_slice(&key);
-
-            let mut value_binary = NewBinary::new(env, value.len());
-            value_binary.as_mut_slice().copy_from_slice(&value);
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
+    }
 
-            Ok((atoms::ok(), Binary::from(key_binary), Binary::from(value_binary)).encode(env))
-        }
-        Some(Err(e)) => {
-            Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env))
-        }
-        None => Ok(atoms::iterator_end().encode(env)),
+    match db.flush_wal(sync) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::flush_failed(), e.to_string())).encode(env)),
     }
 }
 
-/// Closes a snapshot iterator and releases resources.
+/// Changes mutable options of a column family on the running database.
+///
+/// Keys are RocksDB option names such as `disable_auto_compactions` or
+/// `write_buffer_size`; values may be booleans, numbers, atoms or strings
+/// and are passed to RocksDB in string form. The change lasts until the
+/// database is closed; a reopened database applies the `open` options.
 ///
 /// # Arguments
-/// * `iter_ref` - The snapshot iterator reference
+/// * `db_ref` - The database reference
+/// * `cf` - The column family name as an atom or binary
+/// * `options` - List or map of `{key, value}` pairs
 ///
 /// # Returns
 /// * `:ok` on success
-/// * `{:error, :iterator_closed}` if already closed
-#[rustler::nif]
-fn snapshot_iterator_close<'a>(
-    env: Env<'a>,
-    iter_ref: ResourceArc<SnapshotIteratorRef>,
-) -> NifResult<Term<'a>> {
-    let mut iter_guard = iter_ref
-        .iterator
-        .lock()
-        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
-
-    if iter_guard.is_none() {
-        return Ok((atoms::error(), atoms::iterator_closed()).encode(env));
-    }
-
-    // Drop the iterator
-    *iter_guard = None;
+/// * `{:error, :already_closed}` if database is closed
+/// * `{:error, :read_only}` if the database was opened read-only or as a secondary
+/// * `{:error, {:invalid_cf, cf}}` if column family is invalid
+/// * `{:error, {:invalid_option, term}}` if a key or value cannot be converted
+/// * `{:error, {:set_options_failed, reason}}` if RocksDB rejects an option
+#[rustler::nif(schedule = "DirtyIo")]
+fn set_options<'a>(env: Env<'a>, db_ref: ResourceArc<DbRef>, cf: Term<'a>, options: Term<'a>) -> NifResult<Term<'a>> {
+    let options = match decode_set_options(options) {
+        Ok(options) => options,
+        Err(term) => return Ok((atoms::error(), (atoms::invalid_option(), term)).encode(env)),
+    };
 
-    Ok(atoms::ok().encode(env))
-}
+    let cf_name = match cf_term_to_name(cf) {
+        Some(name) => name,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
 
-/// Collects all remaining key-value pairs from a snapshot iterator into a list.
-///
-/// # Arguments
-/// * `iter_ref` - The snapshot iterator reference
-///
-/// # Returns
-/// * `{:ok, [{key, value}, ...]}` with all remaining entries
-/// * `{:error, :iterator_closed}` if iterator was closed
-/// * `{:error, {:iterator_failed, reason}}` on error
-#[rustler::nif(schedule = "DirtyCpu")]
-fn snapshot_iterator_collect<'a>(
-    env: Env<'a>,
-    iter_ref: ResourceArc<SnapshotIteratorRef>,
-) -> NifResult<Term<'a>> {
-    let mut iter_guard = iter_ref
-        .iterator
-        .lock()
+    let db_guard = db_ref
+        .db
+        .read()
         .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
 
-    let iterator = match iter_guard.as_mut() {
-        Some(iter) => iter,
-        None => return Ok((atoms::error(), atoms::iterator_closed()).encode(env)),
+    let db = match db_guard.as_ref() {
+        Some(db) => db,
+        None => return Ok((atoms::error(), atoms::already_closed()).encode(env)),
     };
 
-    let mut results: Vec<Term<'a>> = Vec::new();
-
-    for result in iterator.by_ref() {
-        match result {
-            Ok((key, value)) => {
-                // Check if key still has the prefix
-                if !key.starts_with(&iter_ref.prefix) {
-                    break;
-                }
-
-                let mut key_binary = NewBinary::new(env, key.len());
-                key_binary.as_mut_slice().copy_from_slice(&key);
-
-                let mut value_binary = NewBinary::new(env, value.len());
-                value_binary.as_mut_slice().copy_from_slice(&value);
-
-                results.push((Binary::from(key_binary), Binary::from(value_binary)).encode(env));
-            }
-            Err(e) => {
-                return Ok((atoms::error(), (atoms::iterator_failed(), e.to_string())).encode(env));
-            }
-        }
+    if db_ref.is_read_only() {
+        return Ok((atoms::error(), atoms::read_only()).encode(env));
     }
 
-    Ok((atoms::ok(), results).encode(env))
-}
-
-/// Releases a snapshot and frees resources.
-///
-/// After calling release, the snapshot handle is no longer valid.
-///
-/// # Arguments
-/// * `snapshot_ref` - The snapshot reference
-///
-/// # Returns
-/// * `:ok` on success
-/// * `{:error, :snapshot_released}` if already released
-#[rustler::nif]
-fn release_snapshot<'a>(
-    env: Env<'a>,
-    snapshot_ref: ResourceArc<SnapshotRef>,
-) -> NifResult<Term<'a>> {
-    let mut snap_guard = snapshot_ref
-        .snapshot
-        .lock()
-        .map_err(|_| rustler::Error::Term(Box::new("lock poisoned")))?;
+    let cf_handle = match db.cf_handle(&cf_name) {
+        Some(cf) => cf,
+        None => return Ok((atoms::error(), (atoms::invalid_cf(), cf)).encode(env)),
+    };
 
-    if snap_guard.is_none() {
-        return Ok((atoms::error(), atoms::snapshot_released()).encode(env));
+    let options: Vec<(&str, &str)> = options.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect();
+    match db.set_options_cf(cf_handle, &options) {
+        Ok(()) => Ok(atoms::ok().encode(env)),
+        Err(e) => Ok((atoms::error(), (atoms::set_options_failed(), e.to_string())).encode(env)),
     }
-
-    // Drop the snapshot
-    *snap_guard = None;
-
-    Ok(atoms::ok().encode(env))
 }
 
 rustler::init!("Elixir.TripleStore.Backend.RocksDB.NIF");
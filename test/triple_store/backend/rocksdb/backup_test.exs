@@ -0,0 +1,106 @@
+defmodule TripleStore.Backend.RocksDB.BackupTest do
+  @moduledoc """
+  Tests for checkpoints and BackupEngine backups.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_backup_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      for path <- Path.wildcard("#{test_path}*"), do: File.rm_rf(path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  describe "create_checkpoint/2" do
+    test "creates an openable copy of the database", %{db: db, path: path} do
+      :ok = NIF.insert_triples(db, [{1, 2, 3}])
+      :ok = NIF.put(db, :id2str, "key", "value")
+
+      assert :ok = NIF.create_checkpoint(db, "#{path}_checkpoint")
+      :ok = NIF.put(db, :id2str, "later", "value")
+
+      {:ok, copy} = NIF.open("#{path}_checkpoint")
+      assert {:ok, "value"} = NIF.get(copy, :id2str, "key")
+      assert :not_found = NIF.get(copy, :id2str, "later")
+      assert {:ok, true} = NIF.exists(copy, :spo, <<1::64, 2::64, 3::64>>)
+      NIF.close(copy)
+    end
+
+    test "fails if the directory already exists", %{db: db, path: path} do
+      File.mkdir_p!("#{path}_existing")
+      assert {:error, {:checkpoint_failed, _}} = NIF.create_checkpoint(db, "#{path}_existing")
+    end
+
+    test "returns error for closed database", %{db: db, path: path} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.create_checkpoint(db, "#{path}_checkpoint")
+    end
+  end
+
+  describe "backups" do
+    test "creates, lists and restores backups", %{db: db, path: path} do
+      backup_dir = "#{path}_backups"
+
+      :ok = NIF.put(db, :id2str, "key", "v1")
+      assert {:ok, first} = NIF.backup_create(db, backup_dir)
+      :ok = NIF.put(db, :id2str, "key", "v2")
+      assert {:ok, second} = NIF.backup_create(db, backup_dir, flush: true)
+      assert second > first
+
+      assert {:ok, [%{id: ^first} = info, %{id: ^second}]} = NIF.backup_list(backup_dir)
+      assert info.num_files > 0
+      assert info.size > 0
+      assert is_integer(info.timestamp)
+
+      assert :ok = NIF.backup_verify(backup_dir, first)
+      assert :ok = NIF.backup_restore(backup_dir, "#{path}_restored", first)
+
+      {:ok, restored} = NIF.open("#{path}_restored")
+      assert {:ok, "v1"} = NIF.get(restored, :id2str, "key")
+      NIF.close(restored)
+    end
+
+    test "purge keeps the newest backups", %{db: db, path: path} do
+      backup_dir = "#{path}_backups"
+
+      ids =
+        for i <- 1..3 do
+          :ok = NIF.put(db, :id2str, "key", "v#{i}")
+          {:ok, id} = NIF.backup_create(db, backup_dir)
+          id
+        end
+
+      assert :ok = NIF.backup_purge(backup_dir, 1)
+      assert {:ok, [%{id: id}]} = NIF.backup_list(backup_dir)
+      assert id == List.last(ids)
+      assert {:error, {:backup_failed, _}} = NIF.backup_verify(backup_dir, hd(ids))
+    end
+
+    test "restoring an unknown backup fails", %{db: db, path: path} do
+      backup_dir = "#{path}_backups"
+      {:ok, _} = NIF.backup_create(db, backup_dir)
+
+      assert {:error, {:restore_failed, _}} =
+               NIF.backup_restore(backup_dir, "#{path}_restored", 99)
+    end
+
+    test "rejects unknown options", %{db: db, path: path} do
+      assert {:error, {:invalid_option, :bogus}} =
+               NIF.backup_create(db, "#{path}_backups", bogus: true)
+    end
+
+    test "returns error for closed database", %{db: db, path: path} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.backup_create(db, "#{path}_backups")
+    end
+  end
+end
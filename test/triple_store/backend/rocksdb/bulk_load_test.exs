@@ -0,0 +1,219 @@
+defmodule TripleStore.Backend.RocksDB.BulkLoadTest do
+  @moduledoc """
+  Tests for the native N-Triples bulk loader.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+  alias TripleStore.Dictionary
+  alias TripleStore.Dictionary.StringToId
+
+  @test_db_base "/tmp/triple_store_bulk_load_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      for path <- Path.wildcard("#{test_path}*"), do: File.rm_rf(path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  defp write_nt(path, lines) do
+    file = "#{path}.nt"
+    File.write!(file, Enum.join(lines, "\n"))
+    file
+  end
+
+  defp lookup!(db, term) do
+    {:ok, id} = StringToId.lookup_id(db, term)
+    id
+  end
+
+  describe "bulk_load_ntriples/3" do
+    test "loads triples into the dictionary and all indices", %{db: db, path: path} do
+      file =
+        write_nt(path, [
+          "# a comment",
+          "<http://example.org/s> <http://example.org/p> <http://example.org/o> .",
+          "",
+          "<http://example.org/s> <http://example.org/name> \"Alice\"@EN .",
+          "_:b1 <http://example.org/p> \"plain \\\"quoted\\\" \\u00e9\" ."
+        ])
+
+      assert {:ok, %{triples: 3, unique_triples: 3, terms_created: 7}} =
+               NIF.bulk_load_ntriples(db, file)
+
+      s = lookup!(db, RDF.iri("http://example.org/s"))
+      p = lookup!(db, RDF.iri("http://example.org/p"))
+      o = lookup!(db, RDF.iri("http://example.org/o"))
+      alice = lookup!(db, RDF.literal("Alice", language: "en"))
+      plain = lookup!(db, RDF.literal("plain \"quoted\" é"))
+
+      assert Dictionary.term_type(s) == :uri
+      assert Dictionary.term_type(alice) == :literal
+      assert {:ok, <<1, "http://example.org/s">>} = NIF.get(db, :id2str, <<s::64-big>>)
+
+      assert {:ok, true} = NIF.exists(db, :spo, <<s::64, p::64, o::64>>)
+      assert {:ok, true} = NIF.exists(db, :pos, <<p::64, o::64, s::64>>)
+      assert {:ok, true} = NIF.exists(db, :osp, <<o::64, s::64, p::64>>)
+
+      {:ok, stream} = NIF.lookup_pattern_stream(db, {nil, p, plain})
+      assert [{_bnode, ^p, ^plain}] = Enum.to_list(stream)
+    end
+
+    test "inlines integers, decimals and datetimes", %{db: db, path: path} do
+      file =
+        write_nt(path, [
+          "<http://example.org/s> <http://example.org/int> \"+042\"^^<http://www.w3.org/2001/XMLSchema#integer> .",
+          "<http://example.org/s> <http://example.org/neg> \"-7\"^^<http://www.w3.org/2001/XMLSchema#integer> .",
+          "<http://example.org/s> <http://example.org/dec> \"3.140\"^^<http://www.w3.org/2001/XMLSchema#decimal> .",
+          "<http://example.org/s> <http://example.org/at> \"2024-01-15T12:30:00.250+02:00\"^^<http://www.w3.org/2001/XMLSchema#dateTime> ."
+        ])
+
+      assert {:ok, %{triples: 4, terms_created: 5}} = NIF.bulk_load_ntriples(db, file)
+
+      s = lookup!(db, RDF.iri("http://example.org/s"))
+      {:ok, int} = Dictionary.encode_integer(42)
+      {:ok, neg} = Dictionary.encode_integer(-7)
+      {:ok, dec} = Dictionary.encode_decimal(%Decimal{sign: 1, coef: 314, exp: -2})
+      {:ok, at} = Dictionary.encode_datetime(~U[2024-01-15 10:30:00.250Z])
+
+      {:ok, stream} = NIF.lookup_pattern_stream(db, {s, nil, nil})
+      objects = stream |> Enum.map(fn {_, _, o} -> o end) |> Enum.sort()
+      assert objects == Enum.sort([int, neg, dec, at])
+    end
+
+    test "reuses existing dictionary entries and continues the counters", %{db: db, path: path} do
+      :ok = NIF.put(db, :str2id, "__seq_counter__uri", <<5::64-big>>)
+      existing = Dictionary.encode_id(Dictionary.type_uri(), 3)
+      :ok = NIF.put(db, :str2id, <<1, "http://example.org/s">>, <<existing::64-big>>)
+
+      file = write_nt(path, ["<http://example.org/s> <http://example.org/p> <http://example.org/s> ."])
+
+      assert {:ok, %{terms_created: 1}} = NIF.bulk_load_ntriples(db, file)
+      assert lookup!(db, RDF.iri("http://example.org/s")) == existing
+      assert lookup!(db, RDF.iri("http://example.org/p")) == Dictionary.encode_id(Dictionary.type_uri(), 1006)
+      assert {:ok, <<1006::64-big>>} = NIF.get(db, :str2id, "__seq_counter__uri")
+    end
+
+    test "NFC-normalizes IRIs and literal values like StringToId", %{db: db, path: path} do
+      # "e" followed by a combining acute accent, "\u00e9" in NFC
+      decomposed = "cafe\u0301"
+      file = write_nt(path, ["<http://example.org/#{decomposed}> <http://example.org/p> \"#{decomposed}\"@fr ."])
+
+      assert {:ok, %{terms_created: 3}} = NIF.bulk_load_ntriples(db, file)
+      assert {:ok, _} = NIF.get(db, :str2id, <<1, "http://example.org/caf\u00e9">>)
+      assert :not_found = NIF.get(db, :str2id, <<1, "http://example.org/#{decomposed}">>)
+
+      s = lookup!(db, RDF.iri("http://example.org/#{decomposed}"))
+      o = lookup!(db, RDF.literal(decomposed, language: "fr"))
+      assert s == lookup!(db, RDF.iri("http://example.org/caf\u00e9"))
+      assert o == lookup!(db, RDF.literal("caf\u00e9", language: "fr"))
+    end
+
+    test "deduplicates triples across sorted runs", %{db: db, path: path} do
+      lines =
+        for i <- 1..50, j <- 1..2 do
+          "<http://example.org/s#{rem(i, 10)}> <http://example.org/p> \"#{i}\"^^<http://www.w3.org/2001/XMLSchema#integer> . # #{j}"
+        end
+
+      file = write_nt(path, lines)
+
+      assert {:ok, %{triples: 100, unique_triples: 50}} =
+               NIF.bulk_load_ntriples(db, file, chunk_size: 7, cache_size: 3)
+
+      assert {:ok, 50} = NIF.count_prefix(db, :spo, <<>>)
+      assert {:ok, 50} = NIF.count_prefix(db, :osp, <<>>)
+      assert [] = Path.wildcard("#{path}.bulk_load_*")
+    end
+
+    test "builds index files with the column family's open settings", %{db: db, path: path} do
+      NIF.close(db)
+      {:ok, db} = NIF.open(path, column_families: [spo: [bloom_filter_bits: 0]])
+      file = write_nt(path, ["<http://example.org/s> <http://example.org/p> <http://example.org/o> ."])
+
+      assert {:ok, %{unique_triples: 1}} = NIF.bulk_load_ntriples(db, file)
+
+      {:ok, spo} = NIF.get_property(db, :spo, "rocksdb.aggregated-table-properties")
+      {:ok, pos} = NIF.get_property(db, :pos, "rocksdb.aggregated-table-properties")
+      assert spo =~ ~r/filter block size=0\b/
+      refute pos =~ ~r/filter block size=0\b/
+
+      NIF.close(db)
+    end
+
+    test "reports progress to the caller", %{db: db, path: path} do
+      lines = for i <- 1..5, do: "<http://example.org/s> <http://example.org/p> _:b#{i} ."
+      file = write_nt(path, lines)
+
+      assert {:ok, _} = NIF.bulk_load_ntriples(db, file, progress: self(), chunk_size: 2)
+
+      assert_received {:bulk_load_progress, :parsing, 2}
+      assert_received {:bulk_load_progress, :parsing, 4}
+      assert_received {:bulk_load_progress, :parsing, 5}
+      assert_received {:bulk_load_progress, :spo, 5}
+      assert_received {:bulk_load_progress, :pos, 5}
+      assert_received {:bulk_load_progress, :osp, 5}
+    end
+
+    test "reports the line of an invalid statement", %{db: db, path: path} do
+      file =
+        write_nt(path, [
+          "<http://example.org/s> <http://example.org/p> <http://example.org/o> .",
+          "<http://example.org/s> \"not a predicate\" <http://example.org/o> ."
+        ])
+
+      assert {:error, {:parse_error, 2}} = NIF.bulk_load_ntriples(db, file)
+      assert {:ok, 0} = NIF.count_prefix(db, :spo, <<>>)
+    end
+
+    test "writes nothing when a later statement is invalid", %{db: db, path: path} do
+      lines = for i <- 1..5, do: "<http://example.org/s#{i}> <http://example.org/p> _:b#{i} ."
+      file = write_nt(path, lines ++ ["<http://example.org/s> <http://example.org/p> ."])
+
+      assert {:error, {:parse_error, 6}} =
+               NIF.bulk_load_ntriples(db, file, chunk_size: 1, cache_size: 1, progress: self())
+
+      refute_received {:bulk_load_progress, _, _}
+      assert {:ok, 0} = NIF.count_prefix(db, :id2str, <<>>)
+      assert {:ok, 0} = NIF.count_prefix(db, :str2id, <<>>)
+      assert {:ok, 0} = NIF.count_prefix(db, :spo, <<>>)
+      assert [] = Path.wildcard("#{path}.bulk_load_*")
+    end
+
+    test "refuses to restart a malformed sequence counter", %{db: db, path: path} do
+      :ok = NIF.put(db, :str2id, "__seq_counter__literal", <<1, 2, 3>>)
+      file = write_nt(path, ["<http://example.org/s> <http://example.org/p> \"o\" ."])
+
+      assert {:error, {:bulk_load_failed, "malformed sequence counter"}} =
+               NIF.bulk_load_ntriples(db, file)
+
+      assert {:ok, 0} = NIF.count_prefix(db, :id2str, <<>>)
+      assert {:ok, 0} = NIF.count_prefix(db, :spo, <<>>)
+    end
+
+    test "rejects invalid terms", %{db: db, path: path} do
+      file = write_nt(path, ["<http://example.org/\\u0000> <http://example.org/p> \"o\" ."])
+      assert {:error, {:null_byte_in_uri, 1}} = NIF.bulk_load_ntriples(db, file)
+
+      long = String.duplicate("x", 16_385)
+      file = write_nt(path, ["<http://example.org/s> <http://example.org/p> \"#{long}\" ."])
+      assert {:error, {:term_too_large, 1}} = NIF.bulk_load_ntriples(db, file)
+    end
+
+    test "returns errors for bad input", %{db: db, path: path} do
+      assert {:error, {:bulk_load_failed, _}} = NIF.bulk_load_ntriples(db, "#{path}_missing.nt")
+      assert {:error, {:invalid_option, :bogus}} = NIF.bulk_load_ntriples(db, "x.nt", bogus: 1)
+      assert {:error, {:invalid_option, :chunk_size}} = NIF.bulk_load_ntriples(db, "x.nt", chunk_size: 0)
+    end
+
+    test "returns error for closed database", %{db: db, path: path} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.bulk_load_ntriples(db, "#{path}.nt")
+    end
+  end
+end
@@ -0,0 +1,160 @@
+defmodule TripleStore.Backend.RocksDB.ChangeFeedTest do
+  @moduledoc """
+  Tests for sequence numbers and the WAL change feed.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_change_feed_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      File.rm_rf(test_path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  describe "latest_sequence_number/1" do
+    test "advances by one per write", %{db: db} do
+      {:ok, seq} = NIF.latest_sequence_number(db)
+      :ok = NIF.put(db, :id2str, "a", "1")
+      :ok = NIF.write_batch(db, [{:id2str, "b", "2"}, {:id2str, "c", "3"}])
+
+      assert {:ok, latest} = NIF.latest_sequence_number(db)
+      assert latest == seq + 3
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.latest_sequence_number(db)
+    end
+  end
+
+  describe "updates_since/2" do
+    test "yields the decoded write batches after a sequence number", %{db: db} do
+      {:ok, seq} = NIF.latest_sequence_number(db)
+
+      :ok = NIF.put(db, :id2str, "a", "1")
+
+      :ok =
+        NIF.mixed_batch(db, [
+          {:put, :spo, <<1::64, 2::64, 3::64>>, ""},
+          {:delete, :id2str, "a"},
+          {:merge, :counters, "count", 1}
+        ])
+
+      :ok = NIF.delete_range(db, :spo, <<1::64>>, <<2::64>>)
+
+      {:ok, wal} = NIF.updates_since(db, seq)
+
+      assert {:ok, batches, :end} = NIF.updates_next(wal, 10)
+
+      first = seq + 1
+      second = seq + 2
+      third = seq + 5
+
+      assert [
+               {^first, [{:put, :id2str, "a", "1"}]},
+               {^second,
+                [
+                  {:put, :spo, <<1::64, 2::64, 3::64>>, ""},
+                  {:delete, :id2str, "a", nil},
+                  {:merge, :counters, "count", <<1::64-big>>}
+                ]},
+               {^third, [{:delete_range, :spo, <<1::64>>, <<2::64>>}]}
+             ] = batches
+
+      assert :ok = NIF.updates_close(wal)
+    end
+
+    test "resumes after the last processed batch", %{db: db} do
+      {:ok, seq} = NIF.latest_sequence_number(db)
+      for i <- 1..5, do: :ok = NIF.put(db, :id2str, "k#{i}", "v")
+
+      {:ok, wal} = NIF.updates_since(db, seq)
+      assert {:ok, [_, {last, _}], :more} = NIF.updates_next(wal, 2)
+      NIF.updates_close(wal)
+
+      {:ok, wal} = NIF.updates_since(db, last)
+      assert {:ok, batches, :end} = NIF.updates_next(wal, 10)
+      assert Enum.map(batches, fn {_, [{:put, :id2str, key, _}]} -> key end) == ["k3", "k4", "k5"]
+    end
+
+    test "reports column families created at runtime", %{db: db} do
+      :ok = NIF.create_column_family(db, "custom")
+      {:ok, seq} = NIF.latest_sequence_number(db)
+      :ok = NIF.put(db, "custom", "key", "value")
+
+      {:ok, stream} = NIF.updates_stream(db, seq)
+      assert [{_, [{:put, :custom, "key", "value"}]}] = Enum.to_list(stream)
+    end
+
+    test "leaves out writes that skip the WAL", %{db: db} do
+      {:ok, seq} = NIF.latest_sequence_number(db)
+      :ok = NIF.put(db, :id2str, "a", "1", disable_wal: true)
+      :ok = NIF.put(db, :id2str, "b", "2")
+
+      {:ok, stream} = NIF.updates_stream(db, seq)
+      assert [{_, [{:put, :id2str, "b", "2"}]}] = Enum.to_list(stream)
+    end
+
+    test "resumes after a restart when the WAL is retained", %{db: db, path: path} do
+      NIF.close(db)
+      {:ok, db} = NIF.open(path, wal_ttl_seconds: 3600)
+
+      {:ok, seq} = NIF.latest_sequence_number(db)
+      :ok = NIF.put(db, :id2str, "a", "1")
+      NIF.close(db)
+
+      {:ok, db} = NIF.open(path, wal_ttl_seconds: 3600)
+      :ok = NIF.put(db, :id2str, "b", "2")
+
+      {:ok, stream} = NIF.updates_stream(db, seq)
+
+      assert [{_, [{:put, :id2str, "a", "1"}]}, {_, [{:put, :id2str, "b", "2"}]}] =
+               Enum.to_list(stream)
+
+      NIF.close(db)
+    end
+
+    test "an iterator at the end does not see later writes", %{db: db} do
+      {:ok, seq} = NIF.latest_sequence_number(db)
+      :ok = NIF.put(db, :id2str, "a", "1")
+
+      {:ok, wal} = NIF.updates_since(db, seq)
+      assert {:ok, [_], :end} = NIF.updates_next(wal, 10)
+
+      :ok = NIF.put(db, :id2str, "b", "2")
+      assert {:ok, [], :end} = NIF.updates_next(wal, 10)
+    end
+
+    test "fails for a sequence number not yet written", %{db: db} do
+      {:ok, seq} = NIF.latest_sequence_number(db)
+      assert {:error, {:wal_iterator_failed, _}} = NIF.updates_since(db, seq + 100)
+    end
+
+    test "a closed iterator returns an error", %{db: db} do
+      {:ok, seq} = NIF.latest_sequence_number(db)
+      {:ok, wal} = NIF.updates_since(db, seq)
+
+      assert :ok = NIF.updates_close(wal)
+      assert {:error, :iterator_closed} = NIF.updates_next(wal, 10)
+      assert {:error, :iterator_closed} = NIF.updates_close(wal)
+    end
+
+    test "returns error for closed database", %{db: db} do
+      {:ok, seq} = NIF.latest_sequence_number(db)
+      {:ok, wal} = NIF.updates_since(db, seq)
+      NIF.close(db)
+
+      assert {:error, :already_closed} = NIF.updates_since(db, seq)
+      assert {:error, :already_closed} = NIF.updates_next(wal, 10)
+    end
+  end
+end
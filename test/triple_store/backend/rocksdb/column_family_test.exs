@@ -0,0 +1,128 @@
+defmodule TripleStore.Backend.RocksDB.ColumnFamilyTest do
+  @moduledoc """
+  Tests for creating and dropping column families at runtime.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_column_family_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      File.rm_rf(test_path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  describe "create_column_family/3" do
+    test "creates a column family usable by the data functions", %{db: db} do
+      assert :ok = NIF.create_column_family(db, :graph_1)
+
+      assert :ok = NIF.put(db, :graph_1, "key", "value")
+      assert {:ok, "value"} = NIF.get(db, "graph_1", "key")
+      assert :ok = NIF.write_batch(db, [{"graph_1", "other", "v"}])
+
+      {:ok, stream} = NIF.prefix_stream(db, :graph_1, "")
+      assert [{"key", "value"}, {"other", "v"}] = Enum.to_list(stream)
+
+      assert :not_found = NIF.get(db, :spo, "key")
+      assert {:ok, cfs} = NIF.list_column_families(db)
+      assert :graph_1 in cfs
+    end
+
+    test "applies column family options", %{db: db} do
+      assert :ok = NIF.create_column_family(db, "tenant", prefix_length: 8, compression: :lz4)
+
+      :ok = NIF.put(db, "tenant", <<1::64, 2::64>>, "a")
+      :ok = NIF.put(db, "tenant", <<2::64, 1::64>>, "b")
+
+      assert {:ok, 1} = NIF.count_prefix(db, "tenant", <<1::64>>)
+      assert {:ok, 2} = NIF.count_prefix(db, "tenant", <<>>)
+    end
+
+    test "column families survive a reopen", %{db: db, path: path} do
+      :ok = NIF.create_column_family(db, :graph_1)
+      :ok = NIF.put(db, :graph_1, "key", "value")
+      NIF.close(db)
+
+      {:ok, db} = NIF.open(path, column_families: [graph_1: [compression: :none]])
+      assert {:ok, "value"} = NIF.get(db, :graph_1, "key")
+      assert {:ok, cfs} = NIF.list_column_families(db)
+      assert length(cfs) == 12
+      NIF.close(db)
+    end
+
+    test "returns errors for bad input", %{db: db} do
+      :ok = NIF.create_column_family(db, :graph_1)
+
+      assert {:error, {:cf_exists, :graph_1}} = NIF.create_column_family(db, :graph_1)
+      assert {:error, {:invalid_cf, :spo}} = NIF.create_column_family(db, :spo)
+      assert {:error, {:invalid_cf, "counters"}} = NIF.create_column_family(db, "counters")
+      assert {:error, {:invalid_cf, :default}} = NIF.create_column_family(db, :default)
+      assert {:error, {:invalid_cf, ""}} = NIF.create_column_family(db, "")
+      assert {:error, {:invalid_cf, "default"}} = NIF.create_column_family(db, "default")
+      assert {:error, {:invalid_cf, 42}} = NIF.create_column_family(db, 42)
+
+      assert {:error, {:invalid_option, :bogus}} =
+               NIF.create_column_family(db, :graph_2, bogus: 1)
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.create_column_family(db, :graph_1)
+    end
+  end
+
+  describe "drop_column_family/2" do
+    test "removes the column family and its data", %{db: db, path: path} do
+      :ok = NIF.create_column_family(db, :graph_1)
+      :ok = NIF.put(db, :graph_1, "key", "value")
+
+      assert :ok = NIF.drop_column_family(db, "graph_1")
+      assert {:error, {:invalid_cf, :graph_1}} = NIF.get(db, :graph_1, "key")
+      assert {:ok, cfs} = NIF.list_column_families(db)
+      refute :graph_1 in cfs
+      NIF.close(db)
+
+      {:ok, db} = NIF.open(path)
+      assert {:error, {:invalid_cf, :graph_1}} = NIF.get(db, :graph_1, "key")
+      NIF.close(db)
+    end
+
+    test "can recreate a dropped column family empty", %{db: db} do
+      :ok = NIF.create_column_family(db, :graph_1)
+      :ok = NIF.put(db, :graph_1, "key", "value")
+      :ok = NIF.drop_column_family(db, :graph_1)
+
+      assert :ok = NIF.create_column_family(db, :graph_1)
+      assert :not_found = NIF.get(db, :graph_1, "key")
+    end
+
+    test "returns errors for bad input", %{db: db} do
+      assert {:error, {:invalid_cf, :spo}} = NIF.drop_column_family(db, :spo)
+      assert {:error, {:invalid_cf, "counters"}} = NIF.drop_column_family(db, "counters")
+      assert {:error, {:invalid_cf, :missing}} = NIF.drop_column_family(db, :missing)
+      assert {:error, {:invalid_cf, "default"}} = NIF.drop_column_family(db, "default")
+      assert {:error, {:invalid_cf, :default}} = NIF.drop_column_family(db, :default)
+
+      assert {:ok, cfs} = NIF.list_column_families(db)
+      assert :spo in cfs and :counters in cfs
+    end
+
+    test "data functions do not reach the default column family", %{db: db} do
+      assert {:error, {:invalid_cf, :default}} = NIF.put(db, :default, "key", "value")
+      assert {:error, {:invalid_cf, "default"}} = NIF.get(db, "default", "key")
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.drop_column_family(db, :graph_1)
+    end
+  end
+end
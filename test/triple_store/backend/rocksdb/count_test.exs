@@ -0,0 +1,122 @@
+defmodule TripleStore.Backend.RocksDB.CountTest do
+  @moduledoc """
+  Tests for the count_prefix and approximate_count NIFs.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_count_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      File.rm_rf(test_path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  describe "count_prefix/3" do
+    test "counts the keys with a prefix", %{db: db} do
+      put_keys(db, :spo, 1..4, 25)
+
+      assert {:ok, 25} = NIF.count_prefix(db, :spo, <<2::64-big>>)
+      assert {:ok, 1} = NIF.count_prefix(db, :spo, <<2::64-big, 7::64-big>>)
+      assert {:ok, 100} = NIF.count_prefix(db, :spo, <<>>)
+      assert {:ok, 0} = NIF.count_prefix(db, :spo, <<9::64-big>>)
+    end
+
+    test "matches the number of entries in a prefix stream", %{db: db} do
+      put_keys(db, :pos, 1..3, 10)
+      {:ok, stream} = NIF.prefix_stream(db, :pos, <<3::64-big>>)
+
+      assert {:ok, Enum.count(stream)} == NIF.count_prefix(db, :pos, <<3::64-big>>)
+    end
+
+    test "counts prefixes shorter than the prefix extractor", %{db: db} do
+      put_keys(db, :osp, 1..3, 10)
+      assert {:ok, 30} = NIF.count_prefix(db, :osp, <<0, 0, 0>>)
+    end
+
+    test "does not count deleted keys", %{db: db} do
+      put_keys(db, :spo, 1..1, 10)
+      :ok = NIF.delete(db, :spo, <<1::64-big, 3::64-big>>)
+
+      assert {:ok, 9} = NIF.count_prefix(db, :spo, <<1::64-big>>)
+    end
+
+    test "works on data recovered after reopening", %{db: db, path: path} do
+      put_keys(db, :spo, 1..3, 50)
+      NIF.close(db)
+
+      {:ok, db} = NIF.open(path)
+      assert {:ok, 50} = NIF.count_prefix(db, :spo, <<2::64-big>>)
+      NIF.close(db)
+    end
+
+    test "returns error for invalid column family", %{db: db} do
+      assert {:error, {:invalid_cf, :bogus}} = NIF.count_prefix(db, :bogus, <<>>)
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.count_prefix(db, :spo, <<>>)
+    end
+  end
+
+  describe "approximate_count/4" do
+    test "estimates the whole column family without bounds", %{db: db} do
+      put_keys(db, :spo, 1..10, 100)
+
+      assert {:ok, estimate} = NIF.approximate_count(db, :spo, nil, nil)
+      assert estimate > 0
+    end
+
+    test "scales the estimate to a range of flushed keys", %{db: db, path: path} do
+      put_keys(db, :spo, 1..10, 100)
+      NIF.close(db)
+
+      # Reopening flushes the recovered memtable to an SST file
+      {:ok, db} = NIF.open(path)
+
+      assert {:ok, total} = NIF.approximate_count(db, :spo, nil, nil)
+      assert total in 800..1200
+
+      assert {:ok, range} = NIF.approximate_count(db, :spo, <<3::64-big>>, <<5::64-big>>)
+      assert range in 100..300
+
+      assert {:ok, tail} = NIF.approximate_count(db, :spo, <<8::64-big>>, nil)
+      assert tail in 200..400
+
+      assert {:ok, 0} = NIF.approximate_count(db, :spo, <<20::64-big>>, nil)
+      NIF.close(db)
+    end
+
+    test "returns zero for an empty column family", %{db: db} do
+      assert {:ok, 0} = NIF.approximate_count(db, :derived, nil, nil)
+      assert {:ok, 0} = NIF.approximate_count(db, :derived, <<1>>, <<2>>)
+    end
+
+    test "returns error for invalid column family", %{db: db} do
+      assert {:error, {:invalid_cf, :bogus}} = NIF.approximate_count(db, :bogus, nil, nil)
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.approximate_count(db, :spo, nil, nil)
+    end
+  end
+
+  defp put_keys(db, cf, subjects, per_subject) do
+    operations =
+      for s <- subjects, o <- 1..per_subject do
+        {cf, <<s::64-big, o::64-big, 0::64-big>>, ""}
+      end
+
+    :ok = NIF.write_batch(db, operations)
+  end
+end
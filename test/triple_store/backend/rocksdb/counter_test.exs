@@ -0,0 +1,118 @@
+defmodule TripleStore.Backend.RocksDB.CounterTest do
+  @moduledoc """
+  Tests for merge operator-backed counters.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_counter_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      File.rm_rf(test_path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  describe "merge/5" do
+    test "adds deltas to a counter", %{db: db} do
+      assert :ok = NIF.merge(db, :counters, "count", 10)
+      assert :ok = NIF.merge(db, :counters, "count", 5, sync: true)
+      assert :ok = NIF.merge(db, :counters, "count", -3)
+
+      assert {:ok, <<12::64-big>>} = NIF.get(db, :counters, "count")
+    end
+
+    test "adds to a value written with put", %{db: db} do
+      :ok = NIF.put(db, :counters, "count", <<100::64-big>>)
+      :ok = NIF.merge(db, :counters, "count", 1)
+
+      assert {:ok, <<101::64-big>>} = NIF.get(db, :counters, "count")
+    end
+
+    test "wraps around at 2^64", %{db: db} do
+      :ok = NIF.merge(db, :counters, "count", -1)
+      assert {:ok, <<0xFFFFFFFFFFFFFFFF::64-big>>} = NIF.get(db, :counters, "count")
+    end
+
+    test "keeps its value across reopen", %{db: db, path: path} do
+      for _ <- 1..100, do: :ok = NIF.merge(db, :counters, "count", 2)
+      NIF.close(db)
+
+      {:ok, db} = NIF.open(path)
+      assert {:ok, <<200::64-big>>} = NIF.get(db, :counters, "count")
+      NIF.close(db)
+    end
+
+    test "updates counters atomically with mixed_batch", %{db: db} do
+      operations = [
+        {:put, :spo, <<1::64, 2::64, 3::64>>, ""},
+        {:merge, :counters, "predicate:2", 1},
+        {:merge, :counters, "predicate:2", 1}
+      ]
+
+      assert :ok = NIF.mixed_batch(db, operations)
+      assert {:ok, <<2::64-big>>} = NIF.get(db, :counters, "predicate:2")
+
+      assert {:error, {:invalid_cf, :spo}} =
+               NIF.mixed_batch(db, [{:put, :spo, "k", ""}, {:merge, :spo, "k", 1}])
+
+      assert :not_found = NIF.get(db, :spo, "k")
+    end
+
+    test "returns errors for bad input", %{db: db} do
+      assert {:error, {:invalid_cf, :spo}} = NIF.merge(db, :spo, "count", 1)
+      assert {:error, {:invalid_cf, :bogus}} = NIF.merge(db, :bogus, "count", 1)
+      assert {:error, {:invalid_option, :bogus}} = NIF.merge(db, :counters, "count", 1, bogus: 1)
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.merge(db, :counters, "count", 1)
+    end
+  end
+
+  describe "increment_and_get/5" do
+    test "returns the value after the increment", %{db: db} do
+      assert {:ok, 1} = NIF.increment_and_get(db, :counters, "seq", 1)
+      assert {:ok, 11} = NIF.increment_and_get(db, :counters, "seq", 10)
+      assert {:ok, 6} = NIF.increment_and_get(db, :counters, "seq", -5, disable_wal: true)
+    end
+
+    test "hands out distinct values to concurrent callers", %{db: db} do
+      values =
+        1..8
+        |> Task.async_stream(
+          fn _ -> for _ <- 1..50, do: elem(NIF.increment_and_get(db, :counters, "seq", 1), 1) end,
+          max_concurrency: 8
+        )
+        |> Enum.flat_map(fn {:ok, values} -> values end)
+
+      assert Enum.sort(values) == Enum.to_list(1..400)
+    end
+
+    test "works on a transactional database", %{path: path} do
+      {:ok, db} = NIF.open("#{path}_txn", transactional: true)
+
+      assert {:ok, 3} = NIF.increment_and_get(db, :counters, "seq", 3)
+      assert :ok = NIF.merge(db, :counters, "seq", 2)
+      assert {:ok, <<5::64-big>>} = NIF.get(db, :counters, "seq")
+
+      NIF.close(db)
+      File.rm_rf("#{path}_txn")
+    end
+
+    test "returns errors for bad input and closed databases", %{db: db} do
+      assert {:error, {:invalid_cf, :str2id}} = NIF.increment_and_get(db, :str2id, "seq", 1)
+
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.increment_and_get(db, :counters, "seq", 1)
+    end
+  end
+end
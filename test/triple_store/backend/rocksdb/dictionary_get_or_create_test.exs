@@ -0,0 +1,148 @@
+defmodule TripleStore.Backend.RocksDB.DictionaryGetOrCreateTest do
+  @moduledoc """
+  Tests for the native atomic dictionary get-or-create operation.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+  alias TripleStore.Dictionary
+  alias TripleStore.Dictionary.IdToString
+  alias TripleStore.Dictionary.StringToId
+
+  @test_db_base "/tmp/triple_store_dictionary_nif_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      for path <- Path.wildcard("#{test_path}*"), do: File.rm_rf(path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  defp key(term) do
+    {:ok, key} = StringToId.encode_term(term)
+    key
+  end
+
+  describe "dictionary_get_or_create/4" do
+    test "creates IDs of the requested type in input order", %{db: db} do
+      terms = [
+        key(RDF.iri("http://example.org/s")),
+        key(RDF.bnode("b1")),
+        key(RDF.literal("hello", language: "en"))
+      ]
+
+      assert {:ok, [uri, bnode, literal]} =
+               NIF.dictionary_get_or_create(db, terms, [:uri, :bnode, :literal])
+
+      assert Dictionary.term_type(uri) == :uri
+      assert Dictionary.term_type(bnode) == :bnode
+      assert Dictionary.term_type(literal) == :literal
+      assert {_, 1001} = Dictionary.decode_id(uri)
+
+      assert {:ok, ^uri} = StringToId.lookup_id(db, RDF.iri("http://example.org/s"))
+      assert {:ok, RDF.bnode("b1")} == IdToString.lookup_term(db, bnode)
+    end
+
+    test "returns existing IDs and reuses IDs for repeated terms", %{db: db} do
+      s = key(RDF.iri("http://example.org/s"))
+      o = key(RDF.iri("http://example.org/o"))
+
+      {:ok, [first]} = NIF.dictionary_get_or_create(db, [s], [:uri])
+
+      assert {:ok, [^first, new, ^new, ^first]} =
+               NIF.dictionary_get_or_create(db, [s, o, o, s], [:uri, :uri, :uri, :uri])
+
+      assert new == first + 1
+    end
+
+    test "persists the counters with the new entries", %{db: db, path: path} do
+      {:ok, _} = NIF.dictionary_get_or_create(db, [key(RDF.iri("http://example.org/a"))], [:uri])
+      assert {:ok, <<1001::64-big>>} = NIF.get(db, :str2id, "__seq_counter__uri")
+      NIF.close(db)
+
+      {:ok, db} = NIF.open(path)
+      {:ok, [id]} = NIF.dictionary_get_or_create(db, [key(RDF.iri("http://example.org/b"))], [:uri])
+      assert {_, 2002} = Dictionary.decode_id(id)
+      NIF.close(db)
+    end
+
+    test "continues after IDs allocated by the bulk loader", %{db: db, path: path} do
+      File.write!("#{path}.nt", "<http://example.org/s> <http://example.org/p> _:b .\n")
+      {:ok, _} = NIF.bulk_load_ntriples(db, "#{path}.nt")
+
+      {:ok, [id]} = NIF.dictionary_get_or_create(db, [key(RDF.iri("http://example.org/o"))], [:uri])
+      assert {_, 1003} = Dictionary.decode_id(id)
+    end
+
+    test "runs alongside a bulk load without duplicating terms", %{db: db, path: path} do
+      lines = for i <- 1..2000, do: "<http://example.org/s#{i}> <http://example.org/p> _:b#{i} ."
+      File.write!("#{path}.nt", Enum.join(lines, "\n"))
+
+      load = Task.async(fn -> NIF.bulk_load_ntriples(db, "#{path}.nt", chunk_size: 10) end)
+
+      ids =
+        for i <- 1..200 do
+          {:ok, [id]} =
+            NIF.dictionary_get_or_create(db, [key(RDF.iri("http://example.org/s#{i}"))], [:uri])
+
+          id
+        end
+
+      assert {:ok, %{triples: 2000}} = Task.await(load, 60_000)
+
+      assert {:ok, 4001} = NIF.count_prefix(db, :id2str, <<>>)
+      assert {:ok, 4004} = NIF.count_prefix(db, :str2id, <<>>)
+
+      for {id, i} <- Enum.with_index(ids, 1) do
+        assert {:ok, ^id} = StringToId.lookup_id(db, RDF.iri("http://example.org/s#{i}"))
+      end
+    end
+
+    test "concurrent callers get one ID per term", %{db: db} do
+      terms = for i <- 1..50, do: key(RDF.iri("http://example.org/#{i}"))
+      types = List.duplicate(:uri, 50)
+
+      results =
+        1..8
+        |> Task.async_stream(fn _ -> NIF.dictionary_get_or_create(db, Enum.shuffle(terms), types) end)
+        |> Enum.map(fn {:ok, {:ok, ids}} -> ids end)
+
+      ids = for i <- 1..50, do: elem(StringToId.lookup_id(db, RDF.iri("http://example.org/#{i}")), 1)
+      assert length(Enum.uniq(ids)) == 50
+
+      for ids_in_call <- results do
+        assert Enum.sort(ids_in_call) == Enum.sort(ids)
+      end
+    end
+
+    test "accepts empty input and write options", %{db: db} do
+      assert {:ok, []} = NIF.dictionary_get_or_create(db, [], [])
+      assert {:ok, [_]} = NIF.dictionary_get_or_create(db, ["x"], [:literal], sync: true)
+    end
+
+    test "returns errors for bad input", %{db: db} do
+      assert {:error, :length_mismatch} = NIF.dictionary_get_or_create(db, ["a"], [])
+      assert {:error, {:invalid_type, :integer}} = NIF.dictionary_get_or_create(db, ["a"], [:integer])
+      assert {:error, {:invalid_option, :bogus}} = NIF.dictionary_get_or_create(db, [], [], bogus: 1)
+    end
+
+    test "refuses to restart a malformed sequence counter", %{db: db} do
+      :ok = NIF.put(db, :str2id, "__seq_counter__uri", "bad")
+
+      assert {:error, {:get_failed, "malformed sequence counter"}} =
+               NIF.dictionary_get_or_create(db, ["a"], [:uri])
+
+      assert :not_found = NIF.get(db, :str2id, "a")
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.dictionary_get_or_create(db, ["a"], [:uri])
+    end
+  end
+end
@@ -0,0 +1,161 @@
+defmodule TripleStore.Backend.RocksDB.EventListenerTest do
+  @moduledoc """
+  Tests for the flush, compaction and write stall events of `event_listener`.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_event_listener_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+
+    on_exit(fn ->
+      for path <- Path.wildcard("#{test_path}*"), do: File.rm_rf(path)
+    end)
+
+    {:ok, path: test_path}
+  end
+
+  test "reports flushes", %{path: path} do
+    {:ok, db} = NIF.open(path, event_listener: self())
+
+    :ok = NIF.put(db, :id2str, "key", "value")
+    :ok = NIF.delete(db, :id2str, "other")
+    :ok = NIF.flush(db, :id2str, true)
+
+    assert_receive {:rocksdb_event, :flush_completed, %{cf: :id2str} = info}, 5000
+    assert %{num_entries: 2, num_deletions: 1} = info
+    assert String.ends_with?(info.file_path, ".sst")
+    assert info.smallest_seqno <= info.largest_seqno
+    assert info.triggered_writes_stop == false
+    refute_received {:rocksdb_event, :flush_completed, %{cf: :spo}}
+
+    NIF.close(db)
+  end
+
+  test "reports compactions", %{path: path} do
+    {:ok, db} =
+      NIF.open(path,
+        event_listener: self(),
+        column_families: [id2str: [level_zero_file_num_compaction_trigger: 2]]
+      )
+
+    # Overlapping files are merged rather than moved
+    for i <- 1..2 do
+      :ok = NIF.put(db, :id2str, "key", "value#{i}")
+      :ok = NIF.flush(db, :id2str, true)
+    end
+
+    assert_receive {:rocksdb_event, :compaction_completed, %{cf: :id2str} = info}, 5000
+    assert %{status: :ok, input_files: 2, output_files: 1} = info
+    assert %{input_records: 2, output_records: 1, manual: false, trivial_move: false} = info
+    assert info.input_bytes > 0
+    assert info.output_bytes > 0
+    assert info.base_input_level == 0
+    assert info.output_level > 0
+
+    NIF.close(db)
+  end
+
+  test "reports trivial moves as compactions", %{path: path} do
+    {:ok, db} = NIF.open(path, event_listener: self())
+
+    :ok = NIF.put(db, :id2str, "key", "value")
+    :ok = NIF.flush(db, :id2str, true)
+    assert_receive {:rocksdb_event, :flush_completed, %{cf: :id2str}}, 5000
+
+    # A lone L0 file overlapping nothing is moved down without being rewritten
+    :ok = NIF.compact_range(db, :id2str, nil, nil)
+
+    assert_receive {:rocksdb_event, :compaction_completed, %{cf: :id2str} = info}, 5000
+    assert %{status: :ok, input_files: 1, output_files: 1} = info
+    assert %{manual: true, trivial_move: true} = info
+    assert info.output_level > 0
+
+    NIF.close(db)
+  end
+
+  test "does not report ingested files", %{path: path} do
+    {:ok, db} = NIF.open(path, event_listener: self())
+
+    {:ok, writer} = NIF.sst_writer_open("#{path}.sst")
+    :ok = NIF.sst_writer_put_sorted(writer, [{"a", "1"}, {"b", "2"}])
+    {:ok, _} = NIF.sst_writer_finish(writer)
+    :ok = NIF.ingest_external_files(db, :id2str, ["#{path}.sst"], move_files: true)
+
+    file = "#{path}.nt"
+    File.write!(file, "<http://example.org/s> <http://example.org/p> <http://example.org/o> .\n")
+    {:ok, _} = NIF.bulk_load_ntriples(db, file)
+
+    refute_receive {:rocksdb_event, _, _}, 200
+
+    NIF.close(db)
+  end
+
+  test "reports write stall changes", %{path: path} do
+    {:ok, db} = NIF.open(path, event_listener: self())
+
+    # Slow writes down at one L0 file and stop them at two, which are never compacted
+    :ok =
+      NIF.set_options(db, :id2str,
+        level0_file_num_compaction_trigger: 100,
+        level0_slowdown_writes_trigger: 1,
+        level0_stop_writes_trigger: 2
+      )
+
+    for i <- 1..2 do
+      :ok = NIF.put(db, :id2str, "key#{i}", "value")
+      :ok = NIF.flush(db, :id2str, true)
+    end
+
+    assert_receive {:rocksdb_event, :stall_conditions_changed,
+                    %{cf: :id2str, previous: :normal, current: :delayed}},
+                   5000
+
+    assert_receive {:rocksdb_event, :stall_conditions_changed,
+                    %{cf: :id2str, previous: :delayed, current: :stopped}},
+                   5000
+
+    assert {:error, :write_stall} = NIF.put(db, :id2str, "key3", "value", no_slowdown: true)
+
+    :ok =
+      NIF.set_options(db, :id2str,
+        level0_slowdown_writes_trigger: 20,
+        level0_stop_writes_trigger: 36
+      )
+
+    assert_receive {:rocksdb_event, :stall_conditions_changed,
+                    %{cf: :id2str, previous: :stopped, current: :normal}},
+                   5000
+
+    NIF.close(db)
+  end
+
+  test "does not report files present at open", %{path: path} do
+    {:ok, db} = NIF.open(path)
+    :ok = NIF.put(db, :id2str, "key", "value")
+    :ok = NIF.flush(db, :id2str, true)
+    NIF.close(db)
+
+    {:ok, db} = NIF.open(path, event_listener: self())
+    refute_receive {:rocksdb_event, _, _}, 200
+    NIF.close(db)
+  end
+
+  test "stops reporting once the database is closed", %{path: path} do
+    {:ok, db} = NIF.open(path, event_listener: self())
+    :ok = NIF.put(db, :id2str, "key", "value")
+    NIF.close(db)
+
+    {:ok, db} = NIF.open(path)
+    :ok = NIF.flush(db, :id2str, true)
+    refute_receive {:rocksdb_event, _, _}, 200
+    NIF.close(db)
+  end
+
+  test "rejects invalid options", %{path: path} do
+    assert {:error, {:invalid_option, :event_listener}} = NIF.open(path, event_listener: :nobody)
+  end
+end
@@ -113,6 +113,48 @@ defmodule TripleStore.Backend.RocksDB.IteratorTest do
     end
   end
 
+  describe "iterator_next_n/2" do
+    test "returns chunks until the prefix ends", %{db: db} do
+      for k <- ["p_a", "p_b", "p_c", "p_d", "p_e", "q_a"], do: NIF.put(db, :spo, k, k)
+
+      {:ok, iter} = NIF.prefix_iterator(db, :spo, "p_")
+
+      assert {:ok, [{"p_a", "p_a"}, {"p_b", "p_b"}], :more} = NIF.iterator_next_n(iter, 2)
+      assert {:ok, [{"p_c", "p_c"}, {"p_d", "p_d"}], :more} = NIF.iterator_next_n(iter, 2)
+      assert {:ok, [{"p_e", "p_e"}], :end} = NIF.iterator_next_n(iter, 2)
+
+      NIF.iterator_close(iter)
+    end
+
+    test "reports :end when the iterator is exhausted", %{db: db} do
+      NIF.put(db, :spo, "a", "1")
+
+      {:ok, iter} = NIF.prefix_iterator(db, :spo, "")
+      assert {:ok, [{"a", "1"}], :end} = NIF.iterator_next_n(iter, 10)
+      assert {:ok, [], :end} = NIF.iterator_next_n(iter, 10)
+
+      NIF.iterator_close(iter)
+    end
+
+    test "works with range iterators", %{db: db} do
+      for i <- 1..5, do: NIF.put(db, :pos, <<i::64-big>>, "")
+
+      {:ok, iter} = NIF.range_iterator(db, :pos, nil, <<4::64-big>>, :reverse)
+
+      assert {:ok, [{<<3::64-big>>, ""}, {<<2::64-big>>, ""}, {<<1::64-big>>, ""}], :end} =
+               NIF.iterator_next_n(iter, 3)
+
+      NIF.iterator_close(iter)
+    end
+
+    test "returns error for closed iterator", %{db: db} do
+      {:ok, iter} = NIF.prefix_iterator(db, :spo, "")
+      NIF.iterator_close(iter)
+
+      assert {:error, :iterator_closed} = NIF.iterator_next_n(iter, 10)
+    end
+  end
+
   describe "iterator_seek/2" do
     test "seeks to a specific key", %{db: db} do
       NIF.put(db, :spo, "a", "1")
@@ -164,14 +206,63 @@ defmodule TripleStore.Backend.RocksDB.IteratorTest do
 
       {:ok, iter} = NIF.prefix_iterator(db, :spo, "prefix_")
 
-      # Seek to something that exists but outside prefix
-      assert :ok = NIF.iterator_seek(iter, "other_c")
-      # Should be :iterator_end because "other_c" doesn't match "prefix_" prefix
+      # Seek to something that exists but sorts after the prefix
+      NIF.put(db, :spo, "zzz", "4")
+      assert :ok = NIF.iterator_seek(iter, "zzz")
       assert :iterator_end = NIF.iterator_next(iter)
 
       NIF.iterator_close(iter)
     end
 
+    test "seek below prefix is clamped to the prefix", %{db: db} do
+      NIF.put(db, :spo, "prefix_a", "1")
+      NIF.put(db, :spo, "prefix_b", "2")
+      NIF.put(db, :spo, "other_c", "3")
+
+      {:ok, iter} = NIF.prefix_iterator(db, :spo, "prefix_")
+
+      assert :ok = NIF.iterator_seek(iter, "other_c")
+      assert {:ok, "prefix_a", "1"} = NIF.iterator_next(iter)
+
+      NIF.iterator_close(iter)
+    end
+
+    test "repositions in place after iterating", %{db: db} do
+      for k <- ["s1a", "s1b", "s1c", "s2a"], do: NIF.put(db, :spo, k, "")
+
+      {:ok, iter} = NIF.prefix_iterator(db, :spo, "s1")
+      assert {:ok, [_, _, _], :end} = NIF.iterator_next_n(iter, 10)
+
+      assert :ok = NIF.iterator_seek(iter, "s1b")
+      assert {:ok, [{"s1b", ""}, {"s1c", ""}], :end} = NIF.iterator_next_n(iter, 10)
+
+      NIF.iterator_close(iter)
+    end
+
+    test "keeps range iterator bounds", %{db: db} do
+      for i <- 1..10, do: NIF.put(db, :osp, <<i::64-big>>, "")
+
+      {:ok, iter} = NIF.range_iterator(db, :osp, <<3::64-big>>, <<6::64-big>>, :forward)
+
+      assert :ok = NIF.iterator_seek(iter, <<1::64-big>>)
+      assert {:ok, pairs, :end} = NIF.iterator_next_n(iter, 10)
+      assert Enum.map(pairs, &elem(&1, 0)) == [<<3::64-big>>, <<4::64-big>>, <<5::64-big>>]
+
+      NIF.iterator_close(iter)
+    end
+
+    test "keeps prefix semantics with 24-byte index keys", %{db: db} do
+      for s <- 1..3, p <- 1..3, do: NIF.put(db, :spo, <<s::64-big, p::64-big, 1::64-big>>, "")
+
+      {:ok, iter} = NIF.prefix_iterator(db, :spo, <<2::64-big>>)
+
+      assert :ok = NIF.iterator_seek(iter, <<2::64-big, 2::64-big>>)
+      assert {:ok, pairs, :end} = NIF.iterator_next_n(iter, 10)
+      assert length(pairs) == 2
+
+      NIF.iterator_close(iter)
+    end
+
     test "returns error for closed iterator", %{db: db} do
       {:ok, iter} = NIF.prefix_iterator(db, :spo, "")
       NIF.iterator_close(iter)
@@ -180,6 +271,61 @@ defmodule TripleStore.Backend.RocksDB.IteratorTest do
     end
   end
 
+  describe "iterator_seek_for_prev/2" do
+    test "positions at the last key <= target and iterates backwards", %{db: db} do
+      for k <- ["a", "c", "e"], do: NIF.put(db, :spo, k, k)
+
+      {:ok, iter} = NIF.prefix_iterator(db, :spo, "")
+
+      assert :ok = NIF.iterator_seek_for_prev(iter, "d")
+      assert {:ok, "c", "c"} = NIF.iterator_next(iter)
+      assert {:ok, "a", "a"} = NIF.iterator_next(iter)
+      assert :iterator_end = NIF.iterator_next(iter)
+
+      NIF.iterator_close(iter)
+    end
+
+    test "target above the prefix is clamped to the last prefixed key", %{db: db} do
+      for k <- ["p_a", "p_b", "q_a"], do: NIF.put(db, :spo, k, "")
+
+      {:ok, iter} = NIF.prefix_iterator(db, :spo, "p_")
+
+      assert :ok = NIF.iterator_seek_for_prev(iter, "q_z")
+      assert {:ok, [{"p_b", ""}, {"p_a", ""}], :end} = NIF.iterator_next_n(iter, 10)
+
+      NIF.iterator_close(iter)
+    end
+
+    test "target above the prefix is clamped with 24-byte index keys", %{db: db} do
+      for s <- 1..3, p <- 1..3, do: NIF.put(db, :pos, <<s::64-big, p::64-big, 1::64-big>>, "")
+
+      {:ok, iter} = NIF.prefix_iterator(db, :pos, <<2::64-big>>)
+
+      assert :ok = NIF.iterator_seek_for_prev(iter, <<3::64-big>>)
+      assert {:ok, <<2::64-big, 3::64-big, 1::64-big>>, ""} = NIF.iterator_next(iter)
+
+      NIF.iterator_close(iter)
+    end
+
+    test "target below the prefix ends the iterator", %{db: db} do
+      for k <- ["a", "p_a"], do: NIF.put(db, :spo, k, "")
+
+      {:ok, iter} = NIF.prefix_iterator(db, :spo, "p_")
+
+      assert :ok = NIF.iterator_seek_for_prev(iter, "b")
+      assert :iterator_end = NIF.iterator_next(iter)
+
+      NIF.iterator_close(iter)
+    end
+
+    test "returns error for closed iterator", %{db: db} do
+      {:ok, iter} = NIF.prefix_iterator(db, :spo, "")
+      NIF.iterator_close(iter)
+
+      assert {:error, :iterator_closed} = NIF.iterator_seek_for_prev(iter, "a")
+    end
+  end
+
   describe "iterator_close/1" do
     test "closes an open iterator", %{db: db} do
       {:ok, iter} = NIF.prefix_iterator(db, :spo, "")
@@ -252,6 +398,70 @@ defmodule TripleStore.Backend.RocksDB.IteratorTest do
     end
   end
 
+  describe "range_iterator/5" do
+    setup %{db: db} do
+      for i <- 1..10, do: NIF.put(db, :osp, <<i::64-big>>, "v#{i}")
+      :ok
+    end
+
+    test "iterates forward within bounds", %{db: db} do
+      assert range_keys(db, <<3::64-big>>, <<7::64-big>>, :forward) == [3, 4, 5, 6]
+    end
+
+    test "iterates in reverse within bounds", %{db: db} do
+      assert range_keys(db, <<3::64-big>>, <<7::64-big>>, :reverse) == [6, 5, 4, 3]
+    end
+
+    test "treats nil bounds as unbounded", %{db: db} do
+      assert range_keys(db, nil, <<4::64-big>>, :forward) == [1, 2, 3]
+      assert range_keys(db, <<8::64-big>>, nil, :reverse) == [10, 9, 8]
+      assert range_keys(db, nil, nil, :forward) == Enum.to_list(1..10)
+    end
+
+    test "returns nothing for an empty range", %{db: db} do
+      assert range_keys(db, <<5::64-big>>, <<5::64-big>>, :forward) == []
+      assert range_keys(db, <<20::64-big>>, nil, :reverse) == []
+    end
+
+    test "respects bounds across prefix boundaries with short keys", %{db: db} do
+      NIF.put(db, :spo, "a1", "")
+      NIF.put(db, :spo, "b1", "")
+      NIF.put(db, :spo, "b2", "")
+      NIF.put(db, :spo, "c1", "")
+
+      {:ok, iter} = NIF.range_iterator(db, :spo, "a2", "c", :reverse)
+      assert {:ok, [{"b2", ""}, {"b1", ""}]} = NIF.iterator_collect(iter)
+      NIF.iterator_close(iter)
+    end
+
+    test "returns error for invalid direction", %{db: db} do
+      assert {:error, {:invalid_direction, :sideways}} =
+               NIF.range_iterator(db, :osp, nil, nil, :sideways)
+    end
+
+    test "returns error for invalid column family", %{db: db} do
+      assert {:error, {:invalid_cf, :nonexistent}} =
+               NIF.range_iterator(db, :nonexistent, nil, nil, :forward)
+    end
+  end
+
+  describe "range_stream/5" do
+    test "streams a descending range", %{db: db} do
+      for i <- 1..5, do: NIF.put(db, :pos, <<i::64-big>>, "")
+
+      assert {:ok, stream} = NIF.range_stream(db, :pos, <<2::64-big>>, nil, :reverse)
+      assert [<<5::64-big>>, <<4::64-big>>] = stream |> Enum.take(2) |> Enum.map(&elem(&1, 0))
+    end
+
+    test "defaults to forward iteration", %{db: db} do
+      NIF.put(db, :pos, "k1", "")
+      NIF.put(db, :pos, "k2", "")
+
+      assert {:ok, stream} = NIF.range_stream(db, :pos, "k", "l")
+      assert [{"k1", ""}, {"k2", ""}] = Enum.to_list(stream)
+    end
+  end
+
   describe "prefix_stream/3" do
     test "creates a stream from an iterator", %{db: db} do
       NIF.put(db, :spo, "s1p1o1", "")
@@ -278,6 +488,17 @@ defmodule TripleStore.Backend.RocksDB.IteratorTest do
       assert length(results) == 5
     end
 
+    test "streams results spanning several chunks", %{db: db} do
+      for i <- 1..2500, do: NIF.put(db, :spo, <<1::64-big, i::64-big>>, "")
+      NIF.put(db, :spo, <<2::64-big, 1::64-big>>, "")
+
+      {:ok, stream} = NIF.prefix_stream(db, :spo, <<1::64-big>>)
+      keys = Enum.map(stream, &elem(&1, 0))
+
+      assert length(keys) == 2500
+      assert keys == Enum.sort(keys)
+    end
+
     test "stream handles empty result", %{db: db} do
       {:ok, stream} = NIF.prefix_stream(db, :spo, "nonexistent")
       assert [] = Enum.to_list(stream)
@@ -417,4 +638,11 @@ defmodule TripleStore.Backend.RocksDB.IteratorTest do
       NIF.iterator_close(iter)
     end
   end
+
+  defp range_keys(db, lower, upper, direction) do
+    {:ok, iter} = NIF.range_iterator(db, :osp, lower, upper, direction)
+    {:ok, results} = NIF.iterator_collect(iter)
+    NIF.iterator_close(iter)
+    Enum.map(results, fn {<<i::64-big>>, _value} -> i end)
+  end
 end
@@ -0,0 +1,197 @@
+defmodule TripleStore.Backend.RocksDB.LeapfrogJoinTest do
+  @moduledoc """
+  Tests for the native Leapfrog Triejoin executor.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+  alias TripleStore.Index
+
+  @test_db_base "/tmp/triple_store_leapfrog_join_test"
+
+  # Predicates
+  @knows 100
+  @name 101
+  @age 102
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+
+    :ok =
+      Index.insert_triples(db, [
+        {1, @knows, 2},
+        {1, @knows, 3},
+        {2, @knows, 3},
+        {3, @knows, 1},
+        {1, @name, 10},
+        {2, @name, 20},
+        {3, @name, 30},
+        {1, @age, 40},
+        {3, @age, 40}
+      ])
+
+    on_exit(fn ->
+      NIF.close(db)
+      File.rm_rf(test_path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  defp join_rows(source, patterns, order) do
+    {:ok, stream} = NIF.leapfrog_stream(source, patterns, order)
+    Enum.to_list(stream)
+  end
+
+  defp brute_force(triples, patterns, order) do
+    domain = triples |> Enum.flat_map(&Tuple.to_list/1) |> Enum.uniq() |> Enum.sort()
+
+    order
+    |> Enum.reduce([%{}], fn var, bindings ->
+      for binding <- bindings, value <- domain, do: Map.put(binding, var, value)
+    end)
+    |> Enum.filter(fn binding ->
+      Enum.all?(patterns, fn pattern ->
+        resolved = pattern |> Tuple.to_list() |> Enum.map(&resolve(&1, binding))
+        List.to_tuple(resolved) in triples
+      end)
+    end)
+    |> Enum.map(fn binding -> Enum.map(order, &Map.fetch!(binding, &1)) end)
+  end
+
+  defp resolve({:bound, id}, _binding), do: id
+  defp resolve({:var, name}, binding), do: Map.fetch!(binding, name)
+
+  describe "leapfrog_join/3" do
+    test "joins two patterns on a shared variable", %{db: db} do
+      patterns = [
+        {{:var, :x}, {:bound, @knows}, {:var, :y}},
+        {{:var, :y}, {:bound, @name}, {:var, :n}}
+      ]
+
+      assert join_rows(db, patterns, [:x, :y, :n]) == [
+               [1, 2, 20],
+               [1, 3, 30],
+               [2, 3, 30],
+               [3, 1, 10]
+             ]
+    end
+
+    test "evaluates a triangle query", %{db: db} do
+      patterns = [
+        {{:var, :a}, {:bound, @knows}, {:var, :b}},
+        {{:var, :b}, {:bound, @knows}, {:var, :c}},
+        {{:var, :c}, {:bound, @knows}, {:var, :a}}
+      ]
+
+      assert join_rows(db, patterns, [:a, :b, :c]) == [[1, 2, 3], [2, 3, 1], [3, 1, 2]]
+    end
+
+    test "supports variable orders no index provides directly", %{db: db} do
+      patterns = [
+        {{:var, :x}, {:bound, @age}, {:var, :a}},
+        {{:var, :x}, {:bound, @knows}, {:var, :y}}
+      ]
+
+      assert join_rows(db, patterns, [:a, :y, :x]) == [[40, 1, 3], [40, 2, 1], [40, 3, 1]]
+    end
+
+    test "handles variables repeated within a pattern", %{db: db} do
+      :ok = Index.insert_triple(db, {5, @knows, 5})
+
+      patterns = [{{:var, :x}, {:bound, @knows}, {:var, :x}}]
+      assert join_rows(db, patterns, [:x]) == [[5]]
+    end
+
+    test "fully bound patterns act as filters", %{db: db} do
+      matching = [
+        {{:bound, 1}, {:bound, @age}, {:bound, 40}},
+        {{:var, :x}, {:bound, @name}, {:var, :n}}
+      ]
+
+      assert length(join_rows(db, matching, [:x, :n])) == 3
+
+      missing = [
+        {{:bound, 2}, {:bound, @age}, {:bound, 40}},
+        {{:var, :x}, {:bound, @name}, {:var, :n}}
+      ]
+
+      assert join_rows(db, missing, [:x, :n]) == []
+    end
+
+    test "returns no rows when a pattern has no matches", %{db: db} do
+      patterns = [
+        {{:var, :x}, {:bound, 999}, {:var, :y}},
+        {{:var, :x}, {:bound, @name}, {:var, :n}}
+      ]
+
+      assert join_rows(db, patterns, [:x, :y, :n]) == []
+    end
+
+    test "matches a brute-force join on random data", %{db: db} do
+      :rand.seed(:exsss, {1, 2, 3})
+      triples = for _ <- 1..60, do: {:rand.uniform(6), :rand.uniform(3), :rand.uniform(6)}
+      :ok = Index.insert_triples(db, triples)
+      {:ok, all} = Index.lookup_all(db, {:var, :var, :var})
+
+      patterns = [
+        {{:var, :x}, {:bound, 1}, {:var, :y}},
+        {{:var, :y}, {:var, :p}, {:var, :z}},
+        {{:var, :z}, {:bound, 2}, {:var, :x}}
+      ]
+
+      for order <- [[:x, :y, :z, :p], [:p, :z, :y, :x], [:y, :p, :x, :z]] do
+        assert join_rows(db, patterns, order) == Enum.sort(brute_force(all, patterns, order))
+      end
+    end
+
+    test "reads from a snapshot", %{db: db} do
+      {:ok, snap} = NIF.snapshot(db)
+      :ok = Index.insert_triple(db, {2, @knows, 1})
+
+      patterns = [{{:var, :x}, {:bound, @knows}, {:bound, 1}}]
+
+      assert join_rows(snap, patterns, [:x]) == [[3]]
+      assert join_rows(db, patterns, [:x]) == [[2], [3]]
+
+      NIF.release_snapshot(snap)
+    end
+
+    test "returns error for unknown variables", %{db: db} do
+      pattern = {{:var, :x}, {:bound, @knows}, {:var, :y}}
+
+      assert {:error, {:invalid_pattern, ^pattern}} = NIF.leapfrog_join(db, [pattern], [:x])
+      assert {:error, {:invalid_variable, :z}} = NIF.leapfrog_join(db, [pattern], [:x, :y, :z])
+      assert {:error, {:invalid_variable, :x}} = NIF.leapfrog_join(db, [pattern], [:x, :x, :y])
+    end
+
+    test "returns error for malformed patterns", %{db: db} do
+      assert {:error, {:invalid_pattern, {:a, :b}}} = NIF.leapfrog_join(db, [{:a, :b}], [])
+      assert {:error, {:invalid_pattern, []}} = NIF.leapfrog_join(db, [], [])
+    end
+
+    test "returns error for closed database", %{path: path} do
+      {:ok, db2} = NIF.open("#{path}_closed")
+      NIF.close(db2)
+
+      patterns = [{{:var, :x}, {:bound, @knows}, {:var, :y}}]
+      assert {:error, :already_closed} = NIF.leapfrog_join(db2, patterns, [:x, :y])
+      File.rm_rf("#{path}_closed")
+    end
+  end
+
+  describe "leapfrog_join_next/2" do
+    test "returns rows in chunks", %{db: db} do
+      patterns = [{{:var, :x}, {:bound, @knows}, {:var, :y}}]
+      {:ok, join} = NIF.leapfrog_join(db, patterns, [:x, :y])
+
+      assert {:ok, [[1, 2], [1, 3]], :more} = NIF.leapfrog_join_next(join, 2)
+      assert {:ok, [[2, 3], [3, 1]], :more} = NIF.leapfrog_join_next(join, 2)
+      assert {:ok, [], :end} = NIF.leapfrog_join_next(join, 2)
+
+      assert :ok = NIF.leapfrog_join_close(join)
+      assert {:error, :iterator_closed} = NIF.leapfrog_join_next(join, 2)
+    end
+  end
+end
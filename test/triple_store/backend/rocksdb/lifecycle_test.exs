@@ -30,9 +30,9 @@ defmodule TripleStore.Backend.RocksDB.LifecycleTest do
 
     test "creates all column families", %{path: path} do
       {:ok, db} = NIF.open(path)
+      {:ok, cfs} = NIF.list_column_families(db)
       NIF.close(db)
 
-      cfs = NIF.list_column_families()
       assert :id2str in cfs
       assert :str2id in cfs
       assert :spo in cfs
@@ -86,16 +86,30 @@ defmodule TripleStore.Backend.RocksDB.LifecycleTest do
     end
   end
 
-  describe "list_column_families/0" do
-    test "returns all configured column families" do
-      cfs = NIF.list_column_families()
-      assert length(cfs) == 6
+  describe "list_column_families/1" do
+    test "returns all configured column families", %{path: path} do
+      {:ok, db} = NIF.open(path)
+      {:ok, cfs} = NIF.list_column_families(db)
+      NIF.close(db)
+
+      assert length(cfs) == 11
       assert :id2str in cfs
       assert :str2id in cfs
       assert :spo in cfs
       assert :pos in cfs
       assert :osp in cfs
       assert :derived in cfs
+      assert :counters in cfs
+      assert :gspo in cfs
+      assert :gpos in cfs
+      assert :gosp in cfs
+      assert :spog in cfs
+    end
+
+    test "returns error for closed database", %{path: path} do
+      {:ok, db} = NIF.open(path)
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.list_column_families(db)
     end
   end
 
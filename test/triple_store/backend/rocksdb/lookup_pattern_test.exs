@@ -0,0 +1,139 @@
+defmodule TripleStore.Backend.RocksDB.LookupPatternTest do
+  @moduledoc """
+  Tests for native triple pattern lookup.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_lookup_pattern_test"
+
+  @triples for s <- 1..4, p <- 10..12, o <- [1, 2, 100], rem(s + p + o, 3) != 0, do: {s, p, o}
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+    :ok = NIF.insert_triples(db, @triples)
+
+    on_exit(fn ->
+      NIF.close(db)
+      File.rm_rf(test_path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  defp lookup(db, pattern, opts \\ []) do
+    {:ok, stream} = NIF.lookup_pattern_stream(db, pattern, opts)
+    Enum.to_list(stream)
+  end
+
+  defp expected(triples, {s, p, o}) do
+    triples
+    |> Enum.filter(fn {ts, tp, to} ->
+      s in [nil, ts] and p in [nil, tp] and o in [nil, to]
+    end)
+    |> Enum.sort()
+  end
+
+  describe "lookup_pattern/3" do
+    test "matches a brute-force scan for every pattern shape", %{db: db} do
+      for s <- [nil, 1, 3, 9], p <- [nil, 11, 99], o <- [nil, 2, 100] do
+        pattern = {s, p, o}
+        assert Enum.sort(lookup(db, pattern)) == expected(@triples, pattern), inspect(pattern)
+      end
+    end
+
+    test "returns triples in the key order of the selected index", %{db: db} do
+      assert lookup(db, {1, nil, nil}) == expected(@triples, {1, nil, nil})
+
+      by_object_subject = Enum.sort_by(expected(@triples, {nil, 11, nil}), fn {s, _, o} -> {o, s} end)
+      assert lookup(db, {nil, 11, nil}) == by_object_subject
+    end
+
+    test "filters the predicate of subject-object patterns natively", %{db: db} do
+      :ok = NIF.insert_triples(db, [{7, 20, 8}, {7, 21, 8}, {7, 22, 9}])
+
+      assert lookup(db, {7, nil, 8}) == [{7, 20, 8}, {7, 21, 8}]
+      assert lookup(db, {7, 21, 8}) == [{7, 21, 8}]
+      assert lookup(db, {7, 22, 8}) == []
+    end
+
+    test "handles the full 64-bit ID range", %{db: db} do
+      max = 0xFFFFFFFFFFFFFFFF
+      :ok = NIF.insert_triples(db, [{max, 0, max}, {0, max, 0}])
+
+      assert lookup(db, {max, nil, nil}) == [{max, 0, max}]
+      assert lookup(db, {nil, max, nil}) == [{0, max, 0}]
+      assert lookup(db, {nil, nil, max}) == [{max, 0, max}]
+    end
+
+    test "reads the derived column family", %{db: db} do
+      :ok = NIF.insert_triples(db, [{1, 10, 5}, {2, 10, 5}, {2, 11, 6}], target: :derived)
+
+      assert lookup(db, {nil, nil, nil}, target: :derived) == [{1, 10, 5}, {2, 10, 5}, {2, 11, 6}]
+      assert lookup(db, {2, nil, nil}, target: :derived) == [{2, 10, 5}, {2, 11, 6}]
+      assert lookup(db, {nil, 10, 5}, target: :derived) == [{1, 10, 5}, {2, 10, 5}]
+      assert lookup(db, {1, 10, 2}, target: :derived) == []
+    end
+
+    test "returns results in chunks", %{db: db} do
+      {:ok, lookup} = NIF.lookup_pattern(db, {nil, nil, nil})
+
+      assert {:ok, first, :more} = NIF.lookup_pattern_next(lookup, 5)
+      assert length(first) == 5
+      assert {:ok, rest, :end} = NIF.lookup_pattern_next(lookup, 1000)
+      assert first ++ rest == Enum.sort(@triples)
+
+      assert {:ok, [], :end} = NIF.lookup_pattern_next(lookup, 10)
+      assert :ok = NIF.lookup_pattern_close(lookup)
+      assert {:error, :iterator_closed} = NIF.lookup_pattern_next(lookup, 10)
+      assert {:error, :iterator_closed} = NIF.lookup_pattern_close(lookup)
+    end
+
+    test "stream can be halted early", %{db: db} do
+      {:ok, stream} = NIF.lookup_pattern_stream(db, {nil, nil, nil})
+      assert Enum.take(stream, 3) == Enum.take(Enum.sort(@triples), 3)
+    end
+
+    test "rejects malformed patterns", %{db: db} do
+      assert {:error, {:invalid_pattern, {1, 2}}} = NIF.lookup_pattern(db, {1, 2})
+      assert {:error, {:invalid_pattern, {:var, 2, 3}}} = NIF.lookup_pattern(db, {:var, 2, 3})
+      assert {:error, {:invalid_pattern, {-1, nil, nil}}} = NIF.lookup_pattern(db, {-1, nil, nil})
+    end
+
+    test "rejects unknown options", %{db: db} do
+      assert {:error, {:invalid_option, :target}} =
+               NIF.lookup_pattern(db, {nil, nil, nil}, target: :id2str)
+    end
+
+    test "returns error for closed database", %{path: path} do
+      {:ok, db} = NIF.open("#{path}_closed")
+      NIF.close(db)
+
+      assert {:error, :already_closed} = NIF.lookup_pattern(db, {nil, nil, nil})
+      File.rm_rf("#{path}_closed")
+    end
+  end
+
+  describe "snapshot_lookup_pattern/3" do
+    test "sees the triples present when the snapshot was taken", %{db: db} do
+      {:ok, snap} = NIF.snapshot(db)
+      :ok = NIF.insert_triples(db, [{1, 50, 1}])
+      :ok = NIF.delete_triples(db, [{1, 10, 2}])
+
+      {:ok, stream} = NIF.snapshot_lookup_pattern_stream(snap, {1, nil, nil})
+      assert Enum.to_list(stream) == expected(@triples, {1, nil, nil})
+
+      assert {1, 50, 1} in lookup(db, {1, nil, nil})
+      NIF.release_snapshot(snap)
+    end
+
+    test "returns error for a released snapshot", %{db: db} do
+      {:ok, snap} = NIF.snapshot(db)
+      NIF.release_snapshot(snap)
+
+      assert {:error, :snapshot_released} = NIF.snapshot_lookup_pattern(snap, {nil, nil, nil})
+    end
+  end
+end
@@ -0,0 +1,156 @@
+defmodule TripleStore.Backend.RocksDB.MaintenanceTest do
+  @moduledoc """
+  Tests for manual compaction, flushing and dynamic options.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_maintenance_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      File.rm_rf(test_path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  defp level0_files(db, cf) do
+    {:ok, files} = NIF.get_int_property(db, cf, "rocksdb.num-files-at-level0")
+    files
+  end
+
+  describe "flush/3" do
+    test "moves the memtable into an SST file", %{db: db} do
+      :ok = NIF.put(db, :id2str, "key", "value")
+      assert {:ok, 1} = NIF.get_int_property(db, :id2str, "rocksdb.num-entries-active-mem-table")
+
+      assert :ok = NIF.flush(db, :id2str, true)
+
+      assert {:ok, 0} = NIF.get_int_property(db, :id2str, "rocksdb.num-entries-active-mem-table")
+      assert level0_files(db, :id2str) == 1
+      assert {:ok, "value"} = NIF.get(db, :id2str, "key")
+    end
+
+    test "returns errors for bad input and closed databases", %{db: db} do
+      assert {:error, {:invalid_cf, :bogus}} = NIF.flush(db, :bogus, true)
+
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.flush(db, :spo, true)
+    end
+  end
+
+  describe "compact_range/5" do
+    test "drops deleted entries", %{db: db} do
+      keys = for i <- 1..100, do: <<i::64, 0::64, 0::64>>
+      :ok = NIF.write_batch(db, Enum.map(keys, &{:spo, &1, ""}))
+      :ok = NIF.flush(db, :spo, true)
+      :ok = NIF.delete_batch(db, Enum.map(keys, &{:spo, &1}))
+      :ok = NIF.flush(db, :spo, true)
+
+      assert level0_files(db, :spo) == 2
+
+      assert :ok = NIF.compact_range(db, :spo, nil, nil)
+
+      assert level0_files(db, :spo) == 0
+      assert {:ok, 0} = NIF.get_int_property(db, :spo, "rocksdb.estimate-num-keys")
+      assert {:ok, 0} = NIF.count_prefix(db, :spo, <<>>)
+    end
+
+    test "compacts a key range with options", %{db: db} do
+      :ok = NIF.put(db, :derived, <<1::64>>, "a")
+      :ok = NIF.put(db, :derived, <<2::64>>, "b")
+
+      assert :ok =
+               NIF.compact_range(db, :derived, <<1::64>>, <<2::64>>,
+                 exclusive: true,
+                 bottommost_level_compaction: :force
+               )
+
+      assert {:ok, "a"} = NIF.get(db, :derived, <<1::64>>)
+      assert {:ok, "b"} = NIF.get(db, :derived, <<2::64>>)
+    end
+
+    test "returns errors for bad input", %{db: db} do
+      assert {:error, {:invalid_cf, :bogus}} = NIF.compact_range(db, :bogus, nil, nil)
+      assert {:error, {:invalid_option, :bogus}} =
+               NIF.compact_range(db, :spo, nil, nil, bogus: 1)
+
+      assert {:error, {:invalid_option, :bottommost_level_compaction}} =
+               NIF.compact_range(db, :spo, nil, nil, bottommost_level_compaction: :always)
+    end
+
+    test "returns errors for read-only and closed databases", %{db: db, path: path} do
+      {:ok, reader} = NIF.open_read_only(path)
+      assert {:error, :read_only} = NIF.compact_range(reader, :spo, nil, nil)
+      assert {:error, :read_only} = NIF.flush(reader, :spo, true)
+      assert {:error, :read_only} = NIF.flush_wal(reader, true)
+      NIF.close(reader)
+
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.compact_range(db, :spo, nil, nil)
+    end
+  end
+
+  describe "flush_wal/2" do
+    test "flushes and syncs the WAL", %{db: db} do
+      :ok = NIF.put(db, :id2str, "key", "value")
+      assert :ok = NIF.flush_wal(db, false)
+      assert :ok = NIF.flush_wal(db, true)
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.flush_wal(db, true)
+    end
+  end
+
+  describe "set_options/3" do
+    test "changes mutable column family options", %{db: db} do
+      assert :ok = NIF.set_options(db, :spo, disable_auto_compactions: true)
+      assert :ok = NIF.set_options(db, "spo", [{"write_buffer_size", 8 * 1024 * 1024}])
+      assert :ok = NIF.set_options(db, :spo, %{"level0_file_num_compaction_trigger" => 8})
+      assert :ok = NIF.set_options(db, :spo, disable_auto_compactions: false)
+    end
+
+    test "keeps automatic compactions off while disabled", %{db: db} do
+      :ok =
+        NIF.set_options(db, :id2str,
+          disable_auto_compactions: true,
+          level0_file_num_compaction_trigger: 2
+        )
+
+      for i <- 1..3 do
+        :ok = NIF.put(db, :id2str, "key#{i}", "value")
+        :ok = NIF.flush(db, :id2str, true)
+      end
+
+      assert level0_files(db, :id2str) == 3
+    end
+
+    test "returns errors for bad input", %{db: db} do
+      assert {:error, {:set_options_failed, _}} = NIF.set_options(db, :spo, bogus_option: 1)
+      assert {:error, {:invalid_option, {:bad}}} = NIF.set_options(db, :spo, [{{:bad}, 1}])
+      assert {:error, {:invalid_option, :write_buffer_size}} =
+               NIF.set_options(db, :spo, write_buffer_size: [1])
+
+      assert {:error, {:invalid_cf, :bogus}} = NIF.set_options(db, :bogus, [])
+    end
+
+    test "returns error for read-only database", %{path: path} do
+      {:ok, reader} = NIF.open_read_only(path)
+      assert {:error, :read_only} = NIF.set_options(reader, :spo, disable_auto_compactions: true)
+      NIF.close(reader)
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.set_options(db, :spo, disable_auto_compactions: true)
+    end
+  end
+end
@@ -0,0 +1,192 @@
+defmodule TripleStore.Backend.RocksDB.OpenOptionsTest do
+  @moduledoc """
+  Tests for opening a database with RocksDB tuning options.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_open_options_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    on_exit(fn -> File.rm_rf(test_path) end)
+    {:ok, path: test_path}
+  end
+
+  describe "open/2 with database options" do
+    test "accepts an empty keyword list", %{path: path} do
+      assert {:ok, db} = NIF.open(path, [])
+      NIF.close(db)
+    end
+
+    test "accepts database-wide options as a keyword list", %{path: path} do
+      opts = [
+        block_cache_size: 8 * 1024 * 1024,
+        max_open_files: 256,
+        parallelism: 2,
+        max_background_jobs: 2,
+        bytes_per_sync: 1024 * 1024
+      ]
+
+      assert {:ok, db} = NIF.open(path, opts)
+      assert :ok = NIF.put(db, :spo, "key", "value")
+      assert {:ok, "value"} = NIF.get(db, :spo, "key")
+      NIF.close(db)
+    end
+
+    test "accepts options as a map", %{path: path} do
+      assert {:ok, db} = NIF.open(path, %{write_buffer_size: 4 * 1024 * 1024})
+      NIF.close(db)
+    end
+
+    test "accepts column family defaults", %{path: path} do
+      opts = [
+        write_buffer_size: 4 * 1024 * 1024,
+        max_write_buffer_number: 3,
+        target_file_size_base: 16 * 1024 * 1024,
+        level_zero_file_num_compaction_trigger: 8,
+        compaction_style: :universal,
+        compression: :none,
+        block_size: 16 * 1024,
+        disable_auto_compactions: false
+      ]
+
+      assert {:ok, db} = NIF.open(path, opts)
+      NIF.close(db)
+    end
+
+    test "data written with options survives reopen without options", %{path: path} do
+      {:ok, db} = NIF.open(path, compression: :none, block_cache_size: 1024 * 1024)
+      :ok = NIF.put(db, :id2str, "key", "value")
+      NIF.close(db)
+
+      {:ok, db} = NIF.open(path)
+      assert {:ok, "value"} = NIF.get(db, :id2str, "key")
+      NIF.close(db)
+    end
+  end
+
+  describe "open/2 with column family overrides" do
+    test "accepts per column family options", %{path: path} do
+      opts = [
+        write_buffer_size: 4 * 1024 * 1024,
+        column_families: [
+          spo: [write_buffer_size: 8 * 1024 * 1024, compaction_style: :level],
+          id2str: [compression: :none]
+        ]
+      ]
+
+      assert {:ok, db} = NIF.open(path, opts)
+      assert :ok = NIF.put(db, :spo, "key", "")
+      NIF.close(db)
+    end
+
+    test "rejects an unknown column family", %{path: path} do
+      opts = [column_families: [nonexistent: [write_buffer_size: 1024]]]
+      assert {:error, {:invalid_cf, :nonexistent}} = NIF.open(path, opts)
+    end
+
+    test "rejects database-wide options inside a column family", %{path: path} do
+      opts = [column_families: [spo: [max_open_files: 10]]]
+      assert {:error, {:invalid_option, :max_open_files}} = NIF.open(path, opts)
+    end
+  end
+
+  describe "open/2 with prefix extractors and bloom filters" do
+    test "accepts filter options", %{path: path} do
+      opts = [
+        bloom_filter_bits: 12,
+        column_families: [
+          spo: [prefix_length: 16, whole_key_filtering: true],
+          str2id: [bloom_filter_bits: 7.5],
+          derived: [prefix_length: 0, bloom_filter_bits: 0]
+        ]
+      ]
+
+      assert {:ok, db} = NIF.open(path, opts)
+      assert :ok = NIF.put(db, :str2id, "term", "id")
+      assert {:ok, "id"} = NIF.get(db, :str2id, "term")
+      NIF.close(db)
+    end
+
+    test "prefix scans return the same keys for every prefix length", %{path: path} do
+      {:ok, db} = NIF.open(path)
+      put_index_keys(db, :spo)
+
+      assert length(scan(db, :spo, "")) == 27
+      assert length(scan(db, :spo, <<0, 0, 0>>)) == 27
+      assert scan(db, :spo, <<2::64-big>>) == for(p <- 1..3, o <- 1..3, do: index_key(2, p, o))
+      assert scan(db, :spo, <<2::64-big, 3::64-big>>) == for(o <- 1..3, do: index_key(2, 3, o))
+      assert scan(db, :spo, index_key(3, 1, 2)) == [index_key(3, 1, 2)]
+      assert scan(db, :spo, <<4::64-big>>) == []
+      NIF.close(db)
+    end
+
+    test "prefix scans work on data recovered after reopening", %{path: path} do
+      {:ok, db} = NIF.open(path, write_buffer_size: 64 * 1024)
+      put_index_keys(db, :pos)
+      NIF.close(db)
+
+      {:ok, db} = NIF.open(path)
+      assert scan(db, :pos, <<1::64-big>>) == for(p <- 1..3, o <- 1..3, do: index_key(1, p, o))
+      assert length(scan(db, :pos, <<>>)) == 27
+      NIF.close(db)
+    end
+
+    test "prefix scans work with the prefix extractor disabled", %{path: path} do
+      {:ok, db} = NIF.open(path, column_families: [osp: [prefix_length: 0]])
+      put_index_keys(db, :osp)
+
+      assert scan(db, :osp, <<3::64-big, 2::64-big>>) == for(o <- 1..3, do: index_key(3, 2, o))
+      NIF.close(db)
+    end
+
+    test "rejects a non-numeric bloom filter size", %{path: path} do
+      assert {:error, {:invalid_option, :bloom_filter_bits}} =
+               NIF.open(path, bloom_filter_bits: :many)
+    end
+  end
+
+  describe "open/2 option validation" do
+    test "rejects unknown keys", %{path: path} do
+      assert {:error, {:invalid_option, :bogus}} = NIF.open(path, bogus: 1)
+      refute File.exists?(path)
+    end
+
+    test "rejects values of the wrong type", %{path: path} do
+      assert {:error, {:invalid_option, :write_buffer_size}} =
+               NIF.open(path, write_buffer_size: "large")
+    end
+
+    test "rejects unknown compaction styles", %{path: path} do
+      assert {:error, {:invalid_option, :compaction_style}} =
+               NIF.open(path, compaction_style: :sideways)
+    end
+
+    test "rejects unknown compression types", %{path: path} do
+      assert {:error, {:invalid_option, :compression}} =
+               NIF.open(path, compression: :rar)
+    end
+
+    test "rejects non-atom keys", %{path: path} do
+      assert {:error, {:invalid_option, "write_buffer_size"}} =
+               NIF.open(path, %{"write_buffer_size" => 1024})
+    end
+  end
+
+  defp index_key(s, p, o), do: <<s::64-big, p::64-big, o::64-big>>
+
+  defp put_index_keys(db, cf) do
+    for s <- 1..3, p <- 1..3, o <- 1..3 do
+      :ok = NIF.put(db, cf, index_key(s, p, o), "")
+    end
+  end
+
+  defp scan(db, cf, prefix) do
+    {:ok, iter} = NIF.prefix_iterator(db, cf, prefix)
+    {:ok, results} = NIF.iterator_collect(iter)
+    NIF.iterator_close(iter)
+    Enum.map(results, fn {key, _value} -> key end)
+  end
+end
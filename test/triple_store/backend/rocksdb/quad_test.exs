@@ -0,0 +1,156 @@
+defmodule TripleStore.Backend.RocksDB.QuadTest do
+  @moduledoc """
+  Tests for named-graph quad storage.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_quad_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      File.rm_rf(test_path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  defp graph_triples(db, graph, pattern) do
+    {:ok, stream} = NIF.lookup_graph_pattern_stream(db, graph, pattern)
+    Enum.to_list(stream)
+  end
+
+  describe "quad_insert/3 and quad_delete/3" do
+    test "writes 32-byte keys to all quad indices", %{db: db} do
+      assert :ok = NIF.quad_insert(db, [{10, 1, 2, 3}])
+
+      assert {:ok, true} = NIF.exists(db, :gspo, <<10::64, 1::64, 2::64, 3::64>>)
+      assert {:ok, true} = NIF.exists(db, :gpos, <<10::64, 2::64, 3::64, 1::64>>)
+      assert {:ok, true} = NIF.exists(db, :gosp, <<10::64, 3::64, 1::64, 2::64>>)
+      assert {:ok, true} = NIF.exists(db, :spog, <<1::64, 2::64, 3::64, 10::64>>)
+      assert {:ok, false} = NIF.exists(db, :spo, <<1::64, 2::64, 3::64>>)
+    end
+
+    test "accepts packed binary records", %{db: db} do
+      assert :ok = NIF.quad_insert(db, <<10::64, 1::64, 2::64, 3::64, 11::64, 4::64, 5::64, 6::64>>)
+      assert [{4, 5, 6}] = graph_triples(db, 11, {nil, nil, nil})
+    end
+
+    test "deletes quads from all indices", %{db: db} do
+      :ok = NIF.quad_insert(db, [{10, 1, 2, 3}, {10, 1, 2, 4}], sync: true)
+      assert :ok = NIF.quad_delete(db, [{10, 1, 2, 3}, {10, 9, 9, 9}])
+
+      assert [{1, 2, 4}] = graph_triples(db, 10, {nil, nil, nil})
+      assert {:ok, 1} = NIF.count_prefix(db, :spog, <<>>)
+      assert {:ok, 1} = NIF.count_prefix(db, :gosp, <<>>)
+    end
+
+    test "returns errors for bad input", %{db: db} do
+      assert {:error, :invalid_quad} = NIF.quad_insert(db, [{1, 2, 3}])
+      assert {:error, :invalid_quad} = NIF.quad_insert(db, <<1::64, 2::64, 3::64>>)
+      assert {:error, :invalid_quad} = NIF.quad_delete(db, [{-1, 2, 3, 4}])
+      assert {:error, {:invalid_option, :bogus}} = NIF.quad_insert(db, [], bogus: true)
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.quad_insert(db, [{10, 1, 2, 3}])
+    end
+  end
+
+  describe "lookup_graph_pattern/3" do
+    setup %{db: db} do
+      :ok =
+        NIF.quad_insert(db, [
+          {10, 1, 2, 3},
+          {10, 1, 5, 3},
+          {10, 4, 2, 3},
+          {11, 1, 2, 3},
+          {11, 1, 2, 6}
+        ])
+
+      :ok = NIF.insert_triples(db, [{1, 2, 7}])
+      :ok
+    end
+
+    test "returns only the triples of the graph", %{db: db} do
+      assert [{1, 2, 3}, {1, 5, 3}, {4, 2, 3}] = graph_triples(db, 10, {nil, nil, nil})
+      assert [{1, 2, 3}, {1, 2, 6}] = graph_triples(db, 11, {nil, nil, nil})
+      assert [] = graph_triples(db, 12, {nil, nil, nil})
+    end
+
+    test "uses the bound terms of the pattern", %{db: db} do
+      assert [{1, 2, 3}, {1, 5, 3}] = graph_triples(db, 10, {1, nil, nil})
+      assert [{1, 2, 3}, {4, 2, 3}] = graph_triples(db, 10, {nil, 2, nil}) |> Enum.sort()
+      assert [{1, 2, 3}, {1, 5, 3}] = graph_triples(db, 10, {1, nil, 3}) |> Enum.sort()
+      assert [{1, 2, 6}] = graph_triples(db, 11, {nil, nil, 6})
+      assert [] = graph_triples(db, 11, {1, 2, 7})
+    end
+
+    test "reads in chunks with lookup_pattern_next", %{db: db} do
+      {:ok, lookup} = NIF.lookup_graph_pattern(db, 10, {nil, nil, nil})
+
+      assert {:ok, [_, _], :more} = NIF.lookup_pattern_next(lookup, 2)
+      assert {:ok, [{4, 2, 3}], :end} = NIF.lookup_pattern_next(lookup, 2)
+      assert :ok = NIF.lookup_pattern_close(lookup)
+    end
+
+    test "returns errors for bad input", %{db: db} do
+      assert {:error, {:invalid_pattern, {1, 2}}} = NIF.lookup_graph_pattern(db, 10, {1, 2})
+
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.lookup_graph_pattern(db, 10, {nil, nil, nil})
+    end
+  end
+
+  describe "delete_graph/3" do
+    test "removes every quad of the graph", %{db: db} do
+      :ok = NIF.quad_insert(db, [{10, 1, 2, 3}, {10, 4, 5, 6}, {11, 1, 2, 3}])
+
+      assert {:ok, 2} = NIF.delete_graph(db, 10)
+
+      assert [] = graph_triples(db, 10, {nil, nil, nil})
+      assert [{1, 2, 3}] = graph_triples(db, 11, {nil, nil, nil})
+      assert {:ok, [11]} = NIF.triple_graphs(db, {1, 2, 3})
+      assert {:ok, []} = NIF.triple_graphs(db, {4, 5, 6})
+      assert {:ok, 0} = NIF.delete_graph(db, 10)
+    end
+
+    test "handles the largest graph ID", %{db: db} do
+      max = 0xFFFFFFFFFFFFFFFF
+      previous = max - 1
+      :ok = NIF.quad_insert(db, [{max, 1, 2, 3}, {previous, 1, 2, 3}])
+
+      assert {:ok, 1} = NIF.delete_graph(db, max)
+      assert {:ok, [^previous]} = NIF.triple_graphs(db, {1, 2, 3})
+    end
+
+    test "returns errors for bad input and closed databases", %{db: db} do
+      assert {:error, {:invalid_option, :bogus}} = NIF.delete_graph(db, 10, bogus: true)
+
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.delete_graph(db, 10)
+    end
+  end
+
+  describe "triple_graphs/2" do
+    test "lists the graphs containing a triple in ID order", %{db: db} do
+      :ok = NIF.quad_insert(db, [{12, 1, 2, 3}, {10, 1, 2, 3}, {11, 1, 2, 4}])
+
+      assert {:ok, [10, 12]} = NIF.triple_graphs(db, {1, 2, 3})
+      assert {:ok, []} = NIF.triple_graphs(db, {3, 2, 1})
+    end
+
+    test "returns errors for bad input and closed databases", %{db: db} do
+      assert {:error, :invalid_triple} = NIF.triple_graphs(db, {1, 2})
+
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.triple_graphs(db, {1, 2, 3})
+    end
+  end
+end
@@ -0,0 +1,128 @@
+defmodule TripleStore.Backend.RocksDB.ReadOnlyTest do
+  @moduledoc """
+  Tests for read-only and secondary database handles.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_read_only_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+    :ok = NIF.put(db, :id2str, "key", "value")
+    :ok = NIF.insert_triples(db, [{1, 2, 3}])
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      for path <- Path.wildcard("#{test_path}*"), do: File.rm_rf(path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  describe "create_if_missing option" do
+    test "refuses to create a missing database", %{path: path} do
+      assert {:error, {:open_failed, _}} = NIF.open("#{path}_missing", create_if_missing: false)
+      refute File.exists?("#{path}_missing/CURRENT")
+    end
+
+    test "opens an existing database", %{db: db, path: path} do
+      NIF.close(db)
+      assert {:ok, db} = NIF.open(path, create_if_missing: false)
+      assert {:ok, "value"} = NIF.get(db, :id2str, "key")
+      NIF.close(db)
+    end
+  end
+
+  describe "open_read_only/2" do
+    test "reads the data next to the read-write handle", %{path: path} do
+      assert {:ok, reader} = NIF.open_read_only(path)
+
+      assert {:ok, "value"} = NIF.get(reader, :id2str, "key")
+      assert {:ok, true} = NIF.exists(reader, :spo, <<1::64, 2::64, 3::64>>)
+
+      {:ok, stream} = NIF.lookup_pattern_stream(reader, {1, nil, nil})
+      assert [{1, 2, 3}] = Enum.to_list(stream)
+
+      NIF.close(reader)
+    end
+
+    test "does not see writes made after it was opened", %{db: db, path: path} do
+      {:ok, reader} = NIF.open_read_only(path)
+      :ok = NIF.put(db, :id2str, "later", "value")
+
+      assert :not_found = NIF.get(reader, :id2str, "later")
+      NIF.close(reader)
+    end
+
+    test "rejects writes", %{path: path} do
+      {:ok, reader} = NIF.open_read_only(path)
+
+      assert {:error, :read_only} = NIF.put(reader, :id2str, "key", "other")
+      assert {:error, :read_only} = NIF.delete(reader, :id2str, "key")
+      assert {:error, :read_only} = NIF.write_batch(reader, [{:id2str, "a", "b"}])
+      assert {:error, :read_only} = NIF.mixed_batch(reader, [{:delete, :id2str, "key"}])
+      assert {:error, :read_only} = NIF.delete_range(reader, :spo, <<1::64>>, <<2::64>>)
+      assert {:error, :read_only} = NIF.insert_triples(reader, [{4, 5, 6}])
+      assert {:error, :read_only} = NIF.delete_subject(reader, 1)
+      assert {:error, :read_only} = NIF.merge(reader, :counters, "count", 1)
+      assert {:error, :read_only} = NIF.increment_and_get(reader, :counters, "count", 1)
+      assert {:error, :read_only} = NIF.dictionary_get_or_create(reader, ["a"], [:uri])
+      assert {:error, :read_only} = NIF.quad_insert(reader, [{1, 2, 3, 4}])
+      assert {:error, :read_only} = NIF.create_column_family(reader, :custom)
+      assert {:error, :not_transactional} = NIF.transaction_begin(reader)
+
+      assert {:ok, "value"} = NIF.get(reader, :id2str, "key")
+      NIF.close(reader)
+    end
+
+    test "fails for a missing database without creating it", %{path: path} do
+      assert {:error, {:open_failed, _}} = NIF.open_read_only("#{path}_missing")
+      refute File.exists?("#{path}_missing/CURRENT")
+    end
+
+    test "rejects the transactional option", %{path: path} do
+      assert {:error, {:invalid_option, :transactional}} =
+               NIF.open_read_only(path, transactional: true)
+    end
+  end
+
+  describe "open_as_secondary/3" do
+    test "catches up with the primary's writes", %{db: db, path: path} do
+      assert {:ok, secondary} = NIF.open_as_secondary(path, "#{path}_secondary")
+      assert {:ok, "value"} = NIF.get(secondary, :id2str, "key")
+
+      :ok = NIF.put(db, :id2str, "later", "value")
+      assert :not_found = NIF.get(secondary, :id2str, "later")
+
+      assert :ok = NIF.try_catch_up_with_primary(secondary)
+      assert {:ok, "value"} = NIF.get(secondary, :id2str, "later")
+
+      assert {:error, :read_only} = NIF.put(secondary, :id2str, "key", "other")
+      NIF.close(secondary)
+    end
+
+    test "fails for a missing primary", %{path: path} do
+      assert {:error, {:open_failed, _}} =
+               NIF.open_as_secondary("#{path}_missing", "#{path}_secondary")
+    end
+  end
+
+  describe "try_catch_up_with_primary/1" do
+    test "requires a secondary", %{db: db, path: path} do
+      assert {:error, :not_secondary} = NIF.try_catch_up_with_primary(db)
+
+      {:ok, reader} = NIF.open_read_only(path)
+      assert {:error, :not_secondary} = NIF.try_catch_up_with_primary(reader)
+      NIF.close(reader)
+    end
+
+    test "returns error for closed database", %{path: path} do
+      {:ok, secondary} = NIF.open_as_secondary(path, "#{path}_secondary")
+      NIF.close(secondary)
+      assert {:error, :already_closed} = NIF.try_catch_up_with_primary(secondary)
+    end
+  end
+end
@@ -169,6 +169,52 @@ defmodule TripleStore.Backend.RocksDB.ReadWriteTest do
     end
   end
 
+  describe "multi_get/3" do
+    test "returns results in input order", %{db: db} do
+      NIF.put(db, :id2str, "key1", "value1")
+      NIF.put(db, :id2str, "key2", "value2")
+
+      assert {:ok, [{:ok, "value2"}, :not_found, {:ok, "value1"}]} =
+               NIF.multi_get(db, :id2str, ["key2", "missing", "key1"])
+    end
+
+    test "returns a result for each duplicate key", %{db: db} do
+      NIF.put(db, :str2id, "key1", "value1")
+
+      assert {:ok, [{:ok, "value1"}, {:ok, "value1"}]} =
+               NIF.multi_get(db, :str2id, ["key1", "key1"])
+    end
+
+    test "returns an empty list for no keys", %{db: db} do
+      assert {:ok, []} = NIF.multi_get(db, :id2str, [])
+    end
+
+    test "handles large batches", %{db: db} do
+      keys = for i <- 1..2000, do: <<i::64-big>>
+      for key <- Enum.take_every(keys, 2), do: NIF.put(db, :id2str, key, key)
+
+      assert {:ok, results} = NIF.multi_get(db, :id2str, keys)
+      assert length(results) == 2000
+
+      for {key, result} <- Enum.zip(keys, results) do
+        <<i::64-big>> = key
+        expected = if rem(i, 2) == 1, do: {:ok, key}, else: :not_found
+        assert result == expected
+      end
+    end
+
+    test "returns error for invalid column family", %{db: db} do
+      assert {:error, {:invalid_cf, :nonexistent}} = NIF.multi_get(db, :nonexistent, ["key"])
+    end
+
+    test "returns error for closed database", %{path: path} do
+      {:ok, db2} = NIF.open("#{path}_closed")
+      NIF.close(db2)
+      assert {:error, :already_closed} = NIF.multi_get(db2, :id2str, ["key"])
+      File.rm_rf("#{path}_closed")
+    end
+  end
+
   describe "data persistence" do
     test "data persists after close and reopen", %{path: path} do
       {:ok, db1} = NIF.open("#{path}_persist")
@@ -126,6 +126,43 @@ defmodule TripleStore.Backend.RocksDB.SnapshotTest do
     end
   end
 
+  describe "snapshot_multi_get/3" do
+    test "reads values at snapshot time in input order", %{db: db} do
+      NIF.put(db, :spo, "key1", "value1")
+      NIF.put(db, :spo, "key2", "value2")
+
+      {:ok, snap} = NIF.snapshot(db)
+
+      NIF.put(db, :spo, "key1", "changed")
+      NIF.delete(db, :spo, "key2")
+      NIF.put(db, :spo, "key3", "value3")
+
+      assert {:ok, [{:ok, "value2"}, :not_found, {:ok, "value1"}]} =
+               NIF.snapshot_multi_get(snap, :spo, ["key2", "key3", "key1"])
+
+      assert {:ok, [:not_found, {:ok, "value3"}, {:ok, "changed"}]} =
+               NIF.multi_get(db, :spo, ["key2", "key3", "key1"])
+
+      NIF.release_snapshot(snap)
+    end
+
+    test "returns error for invalid column family", %{db: db} do
+      {:ok, snap} = NIF.snapshot(db)
+
+      assert {:error, {:invalid_cf, :nonexistent}} =
+               NIF.snapshot_multi_get(snap, :nonexistent, ["key"])
+
+      NIF.release_snapshot(snap)
+    end
+
+    test "returns error for released snapshot", %{db: db} do
+      {:ok, snap} = NIF.snapshot(db)
+      NIF.release_snapshot(snap)
+
+      assert {:error, :snapshot_released} = NIF.snapshot_multi_get(snap, :spo, ["key"])
+    end
+  end
+
   describe "snapshot_prefix_iterator/3" do
     test "creates iterator over snapshot", %{db: db} do
       NIF.put(db, :spo, "key1", "value1")
@@ -180,6 +217,40 @@ defmodule TripleStore.Backend.RocksDB.SnapshotTest do
     end
   end
 
+  describe "snapshot_range_iterator/5" do
+    test "iterates the snapshot view within bounds", %{db: db} do
+      for i <- 1..5, do: NIF.put(db, :osp, <<i::64-big>>, "")
+      {:ok, snap} = NIF.snapshot(db)
+
+      NIF.put(db, :osp, <<3::64-big, 1>>, "")
+      NIF.delete(db, :osp, <<2::64-big>>)
+
+      {:ok, iter} = NIF.snapshot_range_iterator(snap, :osp, <<2::64-big>>, <<5::64-big>>, :reverse)
+      {:ok, results} = NIF.snapshot_iterator_collect(iter)
+      assert Enum.map(results, &elem(&1, 0)) == [<<4::64-big>>, <<3::64-big>>, <<2::64-big>>]
+
+      NIF.snapshot_iterator_close(iter)
+      NIF.release_snapshot(snap)
+    end
+
+    test "returns error for invalid direction", %{db: db} do
+      {:ok, snap} = NIF.snapshot(db)
+
+      assert {:error, {:invalid_direction, :up}} =
+               NIF.snapshot_range_iterator(snap, :osp, nil, nil, :up)
+
+      NIF.release_snapshot(snap)
+    end
+
+    test "returns error for released snapshot", %{db: db} do
+      {:ok, snap} = NIF.snapshot(db)
+      NIF.release_snapshot(snap)
+
+      assert {:error, :snapshot_released} =
+               NIF.snapshot_range_iterator(snap, :osp, nil, nil, :forward)
+    end
+  end
+
   describe "snapshot_iterator_next/1" do
     test "returns key-value pairs in order", %{db: db} do
       NIF.put(db, :spo, "a", "1")
@@ -225,6 +296,61 @@ defmodule TripleStore.Backend.RocksDB.SnapshotTest do
     end
   end
 
+  describe "snapshot_iterator_next_n/2" do
+    test "returns chunks from the snapshot view", %{db: db} do
+      NIF.put(db, :spo, "s1a", "1")
+      NIF.put(db, :spo, "s1b", "2")
+      {:ok, snap} = NIF.snapshot(db)
+      NIF.put(db, :spo, "s1c", "3")
+
+      {:ok, iter} = NIF.snapshot_prefix_iterator(snap, :spo, "s1")
+      assert {:ok, [{"s1a", "1"}], :more} = NIF.snapshot_iterator_next_n(iter, 1)
+      assert {:ok, [{"s1b", "2"}], :end} = NIF.snapshot_iterator_next_n(iter, 5)
+
+      NIF.snapshot_iterator_close(iter)
+      NIF.release_snapshot(snap)
+    end
+
+    test "returns error for closed iterator", %{db: db} do
+      {:ok, snap} = NIF.snapshot(db)
+      {:ok, iter} = NIF.snapshot_prefix_iterator(snap, :spo, "")
+      NIF.snapshot_iterator_close(iter)
+
+      assert {:error, :iterator_closed} = NIF.snapshot_iterator_next_n(iter, 5)
+      NIF.release_snapshot(snap)
+    end
+  end
+
+  describe "snapshot_iterator_seek/2" do
+    test "seeks within the snapshot view", %{db: db} do
+      for k <- ["s1a", "s1b", "s1c"], do: NIF.put(db, :spo, k, "old")
+      {:ok, snap} = NIF.snapshot(db)
+      NIF.put(db, :spo, "s1b", "new")
+
+      {:ok, iter} = NIF.snapshot_prefix_iterator(snap, :spo, "s1")
+
+      assert :ok = NIF.snapshot_iterator_seek(iter, "s1b")
+      assert {:ok, "s1b", "old"} = NIF.snapshot_iterator_next(iter)
+
+      assert :ok = NIF.snapshot_iterator_seek_for_prev(iter, "s1bz")
+      assert {:ok, "s1b", "old"} = NIF.snapshot_iterator_next(iter)
+      assert {:ok, "s1a", "old"} = NIF.snapshot_iterator_next(iter)
+
+      NIF.snapshot_iterator_close(iter)
+      NIF.release_snapshot(snap)
+    end
+
+    test "returns error for closed iterator", %{db: db} do
+      {:ok, snap} = NIF.snapshot(db)
+      {:ok, iter} = NIF.snapshot_prefix_iterator(snap, :spo, "")
+      NIF.snapshot_iterator_close(iter)
+
+      assert {:error, :iterator_closed} = NIF.snapshot_iterator_seek(iter, "a")
+      assert {:error, :iterator_closed} = NIF.snapshot_iterator_seek_for_prev(iter, "a")
+      NIF.release_snapshot(snap)
+    end
+  end
+
   describe "snapshot_iterator_close/1" do
     test "closes an open iterator", %{db: db} do
       {:ok, snap} = NIF.snapshot(db)
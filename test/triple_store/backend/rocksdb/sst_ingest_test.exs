@@ -0,0 +1,135 @@
+defmodule TripleStore.Backend.RocksDB.SstIngestTest do
+  @moduledoc """
+  Tests for SST file writing and external file ingestion.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_sst_ingest_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      for path <- Path.wildcard("#{test_path}*"), do: File.rm_rf(path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  defp write_sst(path, entries, opts \\ []) do
+    {:ok, writer} = NIF.sst_writer_open(path, opts)
+    :ok = NIF.sst_writer_put_sorted(writer, entries)
+    {:ok, info} = NIF.sst_writer_finish(writer)
+    info
+  end
+
+  describe "sst_writer_*" do
+    test "writes a file and reports its size", %{path: path} do
+      entries = for i <- 1..100, do: {<<i::64>>, "value#{i}"}
+      info = write_sst("#{path}.sst", entries, cf: :spo, compression: :lz4)
+
+      assert info.path == "#{path}.sst"
+      assert info.entries == 100
+      assert info.file_size > 0
+      assert File.exists?("#{path}.sst")
+    end
+
+    test "accepts entries over several calls", %{path: path} do
+      {:ok, writer} = NIF.sst_writer_open("#{path}.sst")
+      :ok = NIF.sst_writer_put_sorted(writer, [{"a", "1"}, {"b", "2"}])
+      :ok = NIF.sst_writer_put_sorted(writer, [{"c", "3"}])
+
+      assert {:ok, %{entries: 3}} = NIF.sst_writer_finish(writer)
+    end
+
+    test "rejects keys out of order", %{path: path} do
+      {:ok, writer} = NIF.sst_writer_open("#{path}.sst")
+      :ok = NIF.sst_writer_put_sorted(writer, [{"b", ""}])
+
+      assert {:error, {:unsorted_key, "a"}} = NIF.sst_writer_put_sorted(writer, [{"a", ""}])
+      assert {:error, {:unsorted_key, "b"}} = NIF.sst_writer_put_sorted(writer, [{"b", ""}])
+      assert {:ok, %{entries: 1}} = NIF.sst_writer_finish(writer)
+    end
+
+    test "a finished writer is closed", %{path: path} do
+      {:ok, writer} = NIF.sst_writer_open("#{path}.sst")
+      :ok = NIF.sst_writer_put_sorted(writer, [{"a", ""}])
+      {:ok, _} = NIF.sst_writer_finish(writer)
+
+      assert {:error, :writer_closed} = NIF.sst_writer_put_sorted(writer, [{"b", ""}])
+      assert {:error, :writer_closed} = NIF.sst_writer_finish(writer)
+    end
+
+    test "finishing an empty file fails", %{path: path} do
+      {:ok, writer} = NIF.sst_writer_open("#{path}.sst")
+      assert {:error, {:sst_write_failed, _}} = NIF.sst_writer_finish(writer)
+    end
+
+    test "rejects unknown options", %{path: path} do
+      assert {:error, {:invalid_option, :bogus}} = NIF.sst_writer_open("#{path}.sst", bogus: 1)
+      assert {:error, {:invalid_option, :cf}} = NIF.sst_writer_open("#{path}.sst", cf: 1)
+      assert {:error, {:invalid_option, :cf}} = NIF.sst_writer_open("#{path}.sst", cf: :default)
+    end
+  end
+
+  describe "ingest_external_files/4" do
+    test "makes the file contents readable", %{db: db, path: path} do
+      keys = for s <- 1..5, o <- 1..20, do: <<s::64, 7::64, o::64>>
+      %{path: sst} = write_sst("#{path}.sst", Enum.map(keys, &{&1, ""}), cf: :spo)
+
+      assert :ok = NIF.ingest_external_files(db, :spo, [sst])
+
+      {:ok, stream} = NIF.lookup_pattern_stream(db, {3, nil, nil})
+      assert length(Enum.to_list(stream)) == 20
+      assert {:ok, 100} = NIF.count_prefix(db, :spo, <<>>)
+    end
+
+    test "writes files for created column families", %{db: db, path: path} do
+      :ok = NIF.create_column_family(db, :custom)
+      %{path: sst} = write_sst("#{path}.sst", [{"a", "1"}, {"b", "2"}], cf: :custom)
+
+      assert :ok = NIF.ingest_external_files(db, :custom, [sst])
+      assert {:ok, "2"} = NIF.get(db, :custom, "b")
+
+      %{path: sst} = write_sst("#{path}_named.sst", [{"c", "3"}], cf: "custom")
+      assert :ok = NIF.ingest_external_files(db, "custom", [sst])
+      assert {:ok, "3"} = NIF.get(db, :custom, "c")
+    end
+
+    test "ingests several non-overlapping files at once", %{db: db, path: path} do
+      %{path: first} = write_sst("#{path}_1.sst", [{"a", "1"}, {"b", "2"}])
+      %{path: second} = write_sst("#{path}_2.sst", [{"c", "3"}])
+
+      assert :ok = NIF.ingest_external_files(db, :id2str, [first, second], move_files: true)
+      assert {:ok, "1"} = NIF.get(db, :id2str, "a")
+      assert {:ok, "3"} = NIF.get(db, :id2str, "c")
+    end
+
+    test "ingested keys override existing values", %{db: db, path: path} do
+      :ok = NIF.put(db, :id2str, "a", "old")
+      %{path: sst} = write_sst("#{path}.sst", [{"a", "new"}])
+
+      assert :ok = NIF.ingest_external_files(db, :id2str, [sst])
+      assert {:ok, "new"} = NIF.get(db, :id2str, "a")
+    end
+
+    test "returns errors for bad input", %{db: db, path: path} do
+      assert {:error, {:ingest_failed, _}} =
+               NIF.ingest_external_files(db, :spo, ["#{path}_missing.sst"])
+
+      assert {:error, {:invalid_cf, :bogus}} = NIF.ingest_external_files(db, :bogus, [])
+
+      assert {:error, {:invalid_option, :bogus}} =
+               NIF.ingest_external_files(db, :spo, [], bogus: true)
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.ingest_external_files(db, :spo, [])
+    end
+  end
+end
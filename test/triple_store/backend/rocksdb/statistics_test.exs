@@ -0,0 +1,86 @@
+defmodule TripleStore.Backend.RocksDB.StatisticsTest do
+  @moduledoc """
+  Tests for RocksDB properties and statistics.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_statistics_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path, enable_statistics: true)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      for path <- Path.wildcard("#{test_path}*"), do: File.rm_rf(path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  describe "get_property/3 and get_int_property/3" do
+    test "read column family properties", %{db: db} do
+      :ok = NIF.insert_triples(db, for(i <- 1..10, do: {i, 1, 1}))
+
+      assert {:ok, 10} = NIF.get_int_property(db, :spo, "rocksdb.estimate-num-keys")
+      assert {:ok, size} = NIF.get_int_property(db, :spo, "rocksdb.cur-size-all-mem-tables")
+      assert size > 0
+      assert {:ok, _} = NIF.get_int_property(db, "spo", "rocksdb.estimate-pending-compaction-bytes")
+
+      assert {:ok, stats} = NIF.get_property(db, :spo, "rocksdb.stats")
+      assert stats =~ "Compaction Stats"
+    end
+
+    test "read database-wide properties with a nil column family", %{db: db} do
+      assert {:ok, 0} = NIF.get_int_property(db, nil, "rocksdb.num-running-compactions")
+      assert {:ok, "0"} = NIF.get_property(db, nil, "rocksdb.background-errors")
+    end
+
+    test "report unknown properties as not found", %{db: db} do
+      assert :not_found = NIF.get_property(db, :spo, "rocksdb.bogus")
+      assert :not_found = NIF.get_int_property(db, :spo, "rocksdb.bogus")
+    end
+
+    test "return errors for bad input", %{db: db} do
+      assert {:error, {:invalid_cf, :bogus}} =
+               NIF.get_int_property(db, :bogus, "rocksdb.estimate-num-keys")
+
+      assert {:error, {:property_failed, _}} = NIF.get_property(db, :spo, "rocksdb.stats\0")
+      assert {:error, {:property_failed, _}} = NIF.get_int_property(db, :spo, "rocksdb.stats")
+    end
+
+    test "return error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.get_property(db, :spo, "rocksdb.stats")
+      assert {:error, :already_closed} = NIF.get_int_property(db, :spo, "rocksdb.estimate-num-keys")
+    end
+  end
+
+  describe "statistics/1" do
+    test "returns tickers and histograms", %{db: db} do
+      :ok = NIF.put(db, :id2str, "key", "value")
+      {:ok, "value"} = NIF.get(db, :id2str, "key")
+
+      assert {:ok, %{tickers: tickers, histograms: histograms}} = NIF.statistics(db)
+
+      assert tickers["rocksdb.number.keys.written"] == 1
+      assert tickers["rocksdb.number.keys.read"] == 1
+
+      assert %{count: 1, median: median, p99: _, std_dev: _} = histograms["rocksdb.db.get.micros"]
+      assert is_float(median)
+    end
+
+    test "requires statistics to be enabled at open", %{path: path} do
+      {:ok, db} = NIF.open("#{path}_plain")
+      assert {:error, :statistics_disabled} = NIF.statistics(db)
+      NIF.close(db)
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.statistics(db)
+    end
+  end
+end
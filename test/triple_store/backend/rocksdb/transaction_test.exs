@@ -0,0 +1,193 @@
+defmodule TripleStore.Backend.RocksDB.TransactionTest do
+  @moduledoc """
+  Tests for optimistic transactions.
+  """
+  use ExUnit.Case, async: false
+
+  alias TripleStore.Backend.RocksDB.NIF
+
+  @test_db_base "/tmp/triple_store_transaction_test"
+
+  setup do
+    test_path = "#{@test_db_base}_#{:erlang.unique_integer([:positive])}"
+    {:ok, db} = NIF.open(test_path, transactional: true)
+
+    on_exit(fn ->
+      if NIF.is_open(db), do: NIF.close(db)
+      File.rm_rf(test_path)
+    end)
+
+    {:ok, db: db, path: test_path}
+  end
+
+  describe "transactional database" do
+    test "supports the regular read and write operations", %{db: db} do
+      assert :ok = NIF.put(db, :id2str, "k1", "v1")
+      assert :ok = NIF.write_batch(db, [{:id2str, "k2", "v2"}])
+      assert {:ok, "v1"} = NIF.get(db, :id2str, "k1")
+      assert {:ok, [{"k1", "v1"}, {"k2", "v2"}]} = NIF.prefix_iterator(db, :id2str, "k") |> collect()
+
+      :ok = NIF.insert_triples(db, [{1, 2, 3}])
+      {:ok, stream} = NIF.lookup_pattern_stream(db, {1, nil, nil})
+      assert Enum.to_list(stream) == [{1, 2, 3}]
+    end
+
+    test "plain databases reject transactions", %{path: path} do
+      {:ok, db} = NIF.open("#{path}_plain")
+      assert {:error, :not_transactional} = NIF.transaction_begin(db)
+      NIF.close(db)
+      File.rm_rf("#{path}_plain")
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.transaction_begin(db)
+    end
+  end
+
+  describe "commit and rollback" do
+    test "writes are invisible until commit", %{db: db} do
+      :ok = NIF.put(db, :id2str, "gone", "x")
+      {:ok, txn} = NIF.transaction_begin(db)
+
+      assert :ok = NIF.txn_put(txn, :id2str, "key", "value")
+      assert :ok = NIF.txn_delete(txn, :id2str, "gone")
+      assert {:ok, "value"} = NIF.txn_get(txn, :id2str, "key")
+      assert :not_found = NIF.txn_get(txn, :id2str, "gone")
+
+      assert :not_found = NIF.get(db, :id2str, "key")
+      assert {:ok, "x"} = NIF.get(db, :id2str, "gone")
+
+      assert :ok = NIF.txn_commit(txn)
+      assert {:ok, "value"} = NIF.get(db, :id2str, "key")
+      assert :not_found = NIF.get(db, :id2str, "gone")
+    end
+
+    test "rollback discards writes", %{db: db} do
+      {:ok, txn} = NIF.transaction_begin(db)
+      :ok = NIF.txn_put(txn, :id2str, "key", "value")
+
+      assert :ok = NIF.txn_rollback(txn)
+      assert :not_found = NIF.get(db, :id2str, "key")
+    end
+
+    test "an ended transaction rejects further calls", %{db: db} do
+      {:ok, txn} = NIF.transaction_begin(db)
+      :ok = NIF.txn_commit(txn)
+
+      assert {:error, :transaction_closed} = NIF.txn_put(txn, :id2str, "k", "v")
+      assert {:error, :transaction_closed} = NIF.txn_get(txn, :id2str, "k")
+      assert {:error, :transaction_closed} = NIF.txn_delete(txn, :id2str, "k")
+      assert {:error, :transaction_closed} = NIF.txn_prefix_iterator(txn, :id2str, "k")
+      assert {:error, :transaction_closed} = NIF.txn_commit(txn)
+      assert {:error, :transaction_closed} = NIF.txn_rollback(txn)
+    end
+
+    test "returns error for invalid column family or option", %{db: db} do
+      {:ok, txn} = NIF.transaction_begin(db)
+
+      assert {:error, {:invalid_cf, :bogus}} = NIF.txn_put(txn, :bogus, "k", "v")
+      assert {:error, {:invalid_cf, :bogus}} = NIF.txn_get(txn, :bogus, "k")
+      assert {:error, {:invalid_option, :bogus}} = NIF.txn_get(txn, :id2str, "k", bogus: true)
+      assert {:error, {:invalid_option, :for_update}} = NIF.txn_get(txn, :id2str, "k", for_update: 1)
+
+      NIF.txn_rollback(txn)
+    end
+
+    test "applies write options to the commit", %{db: db} do
+      {:ok, seq} = NIF.latest_sequence_number(db)
+
+      {:ok, txn} = NIF.transaction_begin(db, disable_wal: true)
+      :ok = NIF.txn_put(txn, :id2str, "unlogged", "v")
+      assert :ok = NIF.txn_commit(txn)
+
+      {:ok, txn} = NIF.transaction_begin(db, sync: true, no_slowdown: true, low_pri: true)
+      :ok = NIF.txn_put(txn, :id2str, "logged", "v")
+      assert :ok = NIF.txn_commit(txn)
+
+      assert {:ok, "v"} = NIF.get(db, :id2str, "unlogged")
+      {:ok, stream} = NIF.updates_stream(db, seq)
+      assert [{_, [{:put, :id2str, "logged", "v"}]}] = Enum.to_list(stream)
+    end
+
+    test "rejects invalid write options", %{db: db} do
+      assert {:error, {:invalid_option, :bogus}} = NIF.transaction_begin(db, bogus: true)
+      assert {:error, {:invalid_option, :sync}} = NIF.transaction_begin(db, sync: 1)
+    end
+  end
+
+  describe "conflict detection" do
+    test "commit fails when a key read for update changed", %{db: db} do
+      :ok = NIF.put(db, :str2id, "counter", <<1::64>>)
+      {:ok, txn} = NIF.transaction_begin(db)
+
+      assert {:ok, <<1::64>>} = NIF.txn_get(txn, :str2id, "counter", for_update: true)
+      :ok = NIF.put(db, :str2id, "counter", <<5::64>>)
+      :ok = NIF.txn_put(txn, :str2id, "counter", <<2::64>>)
+
+      assert {:error, :conflict} = NIF.txn_commit(txn)
+      assert {:ok, <<5::64>>} = NIF.get(db, :str2id, "counter")
+      assert {:error, :transaction_closed} = NIF.txn_commit(txn)
+    end
+
+    test "commit fails when two transactions write the same key", %{db: db} do
+      {:ok, txn1} = NIF.transaction_begin(db)
+      {:ok, txn2} = NIF.transaction_begin(db)
+
+      :ok = NIF.txn_put(txn1, :str2id, "term", <<1::64>>)
+      :ok = NIF.txn_put(txn2, :str2id, "term", <<2::64>>)
+
+      assert :ok = NIF.txn_commit(txn1)
+      assert {:error, :conflict} = NIF.txn_commit(txn2)
+      assert {:ok, <<1::64>>} = NIF.get(db, :str2id, "term")
+    end
+
+    test "plain reads do not cause conflicts", %{db: db} do
+      :ok = NIF.put(db, :str2id, "other", "a")
+      {:ok, txn} = NIF.transaction_begin(db)
+
+      assert {:ok, "a"} = NIF.txn_get(txn, :str2id, "other")
+      :ok = NIF.put(db, :str2id, "other", "b")
+      :ok = NIF.txn_put(txn, :str2id, "mine", "c")
+
+      assert :ok = NIF.txn_commit(txn)
+    end
+
+    test "disjoint transactions both commit", %{db: db} do
+      {:ok, txn1} = NIF.transaction_begin(db)
+      {:ok, txn2} = NIF.transaction_begin(db)
+
+      :ok = NIF.txn_put(txn1, :str2id, "a", "1")
+      :ok = NIF.txn_put(txn2, :str2id, "b", "2")
+
+      assert :ok = NIF.txn_commit(txn1)
+      assert :ok = NIF.txn_commit(txn2)
+    end
+  end
+
+  describe "txn_prefix_iterator/3" do
+    test "merges committed data with uncommitted writes", %{db: db} do
+      :ok = NIF.write_batch(db, [{:spo, "s1a", "1"}, {:spo, "s1b", "2"}, {:spo, "s2a", "3"}])
+      {:ok, txn} = NIF.transaction_begin(db)
+      :ok = NIF.txn_put(txn, :spo, "s1c", "4")
+      :ok = NIF.txn_delete(txn, :spo, "s1a")
+
+      assert {:ok, [{"s1b", "2"}, {"s1c", "4"}]} =
+               NIF.txn_prefix_iterator(txn, :spo, "s1") |> collect()
+
+      NIF.txn_rollback(txn)
+    end
+
+    test "is closed when the transaction ends", %{db: db} do
+      :ok = NIF.put(db, :spo, "s1a", "1")
+      {:ok, txn} = NIF.transaction_begin(db)
+      {:ok, iter} = NIF.txn_prefix_iterator(txn, :spo, "s1")
+
+      :ok = NIF.txn_commit(txn)
+      assert {:error, :iterator_closed} = NIF.iterator_next(iter)
+      assert {:error, :iterator_closed} = NIF.iterator_close(iter)
+    end
+  end
+
+  defp collect({:ok, iter}), do: NIF.iterator_collect(iter)
+end
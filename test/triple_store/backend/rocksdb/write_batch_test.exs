@@ -307,6 +307,221 @@ defmodule TripleStore.Backend.RocksDB.WriteBatchTest do
     end
   end
 
+  describe "insert_triples/3 and delete_triples/3" do
+    test "writes every index permutation of each triple", %{db: db} do
+      assert :ok = NIF.insert_triples(db, [{1, 2, 3}, {4, 5, 6}])
+
+      assert {:ok, ""} = NIF.get(db, :spo, <<1::64, 2::64, 3::64>>)
+      assert {:ok, ""} = NIF.get(db, :pos, <<2::64, 3::64, 1::64>>)
+      assert {:ok, ""} = NIF.get(db, :osp, <<3::64, 1::64, 2::64>>)
+      assert {:ok, true} = NIF.exists(db, :osp, <<6::64, 4::64, 5::64>>)
+      assert {:ok, false} = NIF.exists(db, :derived, <<1::64, 2::64, 3::64>>)
+    end
+
+    test "accepts packed 24-byte records", %{db: db} do
+      packed = <<1::64, 2::64, 3::64, 0xFFFFFFFFFFFFFFFF::64, 0::64, 7::64>>
+
+      assert :ok = NIF.insert_triples(db, packed)
+      assert {:ok, true} = NIF.exists(db, :pos, <<2::64, 3::64, 1::64>>)
+      assert {:ok, true} = NIF.exists(db, :osp, <<7::64, 0xFFFFFFFFFFFFFFFF::64, 0::64>>)
+
+      assert :ok = NIF.delete_triples(db, packed)
+      assert {:ok, false} = NIF.exists(db, :spo, <<1::64, 2::64, 3::64>>)
+      assert {:ok, false} = NIF.exists(db, :pos, <<0::64, 7::64, 0xFFFFFFFFFFFFFFFF::64>>)
+    end
+
+    test "deletes every index permutation and ignores missing triples", %{db: db} do
+      :ok = NIF.insert_triples(db, [{1, 2, 3}, {4, 5, 6}])
+
+      assert :ok = NIF.delete_triples(db, [{1, 2, 3}, {7, 8, 9}])
+      assert {:ok, false} = NIF.exists(db, :spo, <<1::64, 2::64, 3::64>>)
+      assert {:ok, false} = NIF.exists(db, :pos, <<2::64, 3::64, 1::64>>)
+      assert {:ok, false} = NIF.exists(db, :osp, <<3::64, 1::64, 2::64>>)
+      assert {:ok, true} = NIF.exists(db, :spo, <<4::64, 5::64, 6::64>>)
+    end
+
+    test "targets the derived column family", %{db: db} do
+      assert :ok = NIF.insert_triples(db, [{1, 2, 3}], target: :derived)
+
+      assert {:ok, ""} = NIF.get(db, :derived, <<1::64, 2::64, 3::64>>)
+      assert {:ok, false} = NIF.exists(db, :spo, <<1::64, 2::64, 3::64>>)
+
+      assert :ok = NIF.delete_triples(db, [{1, 2, 3}], target: :derived)
+      assert {:ok, false} = NIF.exists(db, :derived, <<1::64, 2::64, 3::64>>)
+    end
+
+    test "accepts an empty list", %{db: db} do
+      assert :ok = NIF.insert_triples(db, [])
+      assert :ok = NIF.delete_triples(db, <<>>)
+    end
+
+    test "rejects malformed triples without writing any", %{db: db} do
+      assert {:error, :invalid_triple} = NIF.insert_triples(db, [{1, 2, 3}, {4, 5}])
+      assert {:error, :invalid_triple} = NIF.insert_triples(db, [{1, 2, -3}])
+      assert {:error, :invalid_triple} = NIF.insert_triples(db, [{1, 2, 0x10000000000000000}])
+      assert {:error, :invalid_triple} = NIF.delete_triples(db, <<1::64, 2::64>>)
+      assert {:ok, false} = NIF.exists(db, :spo, <<1::64, 2::64, 3::64>>)
+    end
+
+    test "rejects unknown options", %{db: db} do
+      assert {:error, {:invalid_option, :target}} =
+               NIF.insert_triples(db, [{1, 2, 3}], target: :spo)
+
+      assert {:error, {:invalid_option, :bogus}} =
+               NIF.delete_triples(db, [{1, 2, 3}], bogus: true)
+    end
+
+    test "returns error for closed database", %{path: path} do
+      {:ok, db} = NIF.open("#{path}_closed")
+      NIF.close(db)
+
+      assert {:error, :already_closed} = NIF.insert_triples(db, [{1, 2, 3}])
+      File.rm_rf("#{path}_closed")
+    end
+  end
+
+  describe "delete_range/5" do
+    test "deletes keys in the half-open range", %{db: db} do
+      :ok = NIF.write_batch(db, for(k <- ["a", "b", "c", "d"], do: {:id2str, k, k}))
+
+      assert :ok = NIF.delete_range(db, :id2str, "b", "d")
+      assert {:ok, "a"} = NIF.get(db, :id2str, "a")
+      assert :not_found = NIF.get(db, :id2str, "b")
+      assert :not_found = NIF.get(db, :id2str, "c")
+      assert {:ok, "d"} = NIF.get(db, :id2str, "d")
+    end
+
+    test "is visible to iterators", %{db: db} do
+      :ok = NIF.insert_triples(db, for(o <- 1..100, do: {1, 2, o}) ++ [{2, 2, 2}])
+
+      assert :ok = NIF.delete_range(db, :spo, <<1::64>>, <<2::64>>, sync: true)
+      assert {:ok, 0} = NIF.count_prefix(db, :spo, <<1::64>>)
+      assert {:ok, 1} = NIF.count_prefix(db, :spo, <<>>)
+    end
+
+    test "returns errors for bad input", %{db: db} do
+      assert {:error, :invalid_range} = NIF.delete_range(db, :spo, "b", "a")
+      assert {:error, {:invalid_cf, :bogus}} = NIF.delete_range(db, :bogus, "a", "b")
+      assert {:error, {:invalid_option, :bogus}} = NIF.delete_range(db, :spo, "a", "b", bogus: 1)
+    end
+
+    test "returns error for closed database", %{db: db} do
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.delete_range(db, :spo, "a", "b")
+    end
+  end
+
+  describe "delete_subject/3 and delete_predicate/3" do
+    test "delete_subject removes the subject from every index", %{db: db} do
+      :ok = NIF.insert_triples(db, [{1, 2, 3}, {1, 4, 5}, {6, 2, 3}, {3, 1, 1}])
+
+      assert {:ok, 2} = NIF.delete_subject(db, 1)
+      assert {:ok, 0} = NIF.count_prefix(db, :spo, <<1::64>>)
+      assert {:ok, false} = NIF.exists(db, :pos, <<2::64, 3::64, 1::64>>)
+      assert {:ok, false} = NIF.exists(db, :osp, <<5::64, 1::64, 4::64>>)
+
+      assert {:ok, true} = NIF.exists(db, :pos, <<2::64, 3::64, 6::64>>)
+      assert {:ok, true} = NIF.exists(db, :osp, <<1::64, 3::64, 1::64>>)
+      assert {:ok, 2} = NIF.count_prefix(db, :spo, <<>>)
+    end
+
+    test "delete_predicate removes the predicate from every index", %{db: db} do
+      :ok = NIF.insert_triples(db, [{1, 2, 3}, {1, 4, 5}, {6, 2, 3}])
+
+      assert {:ok, 2} = NIF.delete_predicate(db, 2, disable_wal: true)
+      assert {:ok, 0} = NIF.count_prefix(db, :pos, <<2::64>>)
+      assert {:ok, false} = NIF.exists(db, :spo, <<6::64, 2::64, 3::64>>)
+      assert {:ok, false} = NIF.exists(db, :osp, <<3::64, 1::64, 2::64>>)
+      assert {:ok, true} = NIF.exists(db, :osp, <<5::64, 1::64, 4::64>>)
+    end
+
+    test "handles the largest term ID", %{db: db} do
+      max = 0xFFFFFFFFFFFFFFFF
+      :ok = NIF.insert_triples(db, [{max, 1, 2}, {max, max, max}, {1, max, 2}])
+
+      assert {:ok, 2} = NIF.delete_subject(db, max)
+      assert {:ok, 1} = NIF.count_prefix(db, :spo, <<>>)
+      assert {:ok, 1} = NIF.delete_predicate(db, max)
+      assert {:ok, 0} = NIF.count_prefix(db, :osp, <<>>)
+    end
+
+    test "returns zero for unknown terms", %{db: db} do
+      assert {:ok, 0} = NIF.delete_subject(db, 42)
+      assert {:ok, 0} = NIF.delete_predicate(db, 42)
+    end
+
+    test "works on a transactional database", %{path: path} do
+      {:ok, db} = NIF.open("#{path}_txn", transactional: true)
+      :ok = NIF.insert_triples(db, [{1, 2, 3}, {4, 2, 5}])
+
+      assert {:ok, 1} = NIF.delete_subject(db, 1)
+      assert {:ok, 1} = NIF.delete_predicate(db, 2)
+      assert {:ok, 0} = NIF.count_prefix(db, :pos, <<>>)
+
+      NIF.close(db)
+      File.rm_rf("#{path}_txn")
+    end
+
+    test "returns errors for bad options and closed databases", %{db: db} do
+      assert {:error, {:invalid_option, :bogus}} = NIF.delete_subject(db, 1, bogus: true)
+
+      NIF.close(db)
+      assert {:error, :already_closed} = NIF.delete_subject(db, 1)
+      assert {:error, :already_closed} = NIF.delete_predicate(db, 1)
+    end
+  end
+
+  describe "write options" do
+    test "every write function accepts write options", %{db: db} do
+      assert :ok = NIF.put(db, :id2str, "k1", "v1", sync: true)
+      assert :ok = NIF.write_batch(db, [{:id2str, "k2", "v2"}], disable_wal: true)
+      assert :ok = NIF.mixed_batch(db, [{:put, :id2str, "k3", "v3"}], low_pri: true)
+      assert :ok = NIF.insert_triples(db, [{1, 2, 3}], sync: true, target: :derived)
+
+      assert {:ok, "v1"} = NIF.get(db, :id2str, "k1")
+      assert {:ok, "v2"} = NIF.get(db, :id2str, "k2")
+      assert {:ok, "v3"} = NIF.get(db, :id2str, "k3")
+      assert {:ok, true} = NIF.exists(db, :derived, <<1::64, 2::64, 3::64>>)
+
+      assert :ok = NIF.delete(db, :id2str, "k1", no_slowdown: true)
+      assert :ok = NIF.delete_batch(db, [{:id2str, "k2"}], %{sync: true, low_pri: false})
+      assert :ok = NIF.delete_triples(db, [{1, 2, 3}], disable_wal: true, target: :derived)
+
+      assert :not_found = NIF.get(db, :id2str, "k1")
+      assert :not_found = NIF.get(db, :id2str, "k2")
+      assert {:ok, false} = NIF.exists(db, :derived, <<1::64, 2::64, 3::64>>)
+    end
+
+    test "no_slowdown writes succeed when nothing is stalled", %{db: db} do
+      operations = for i <- 1..1000, do: {:spo, <<i::64>>, ""}
+      assert :ok = NIF.write_batch(db, operations, no_slowdown: true)
+    end
+
+    test "synced writes survive reopening", %{path: path} do
+      {:ok, db} = NIF.open("#{path}_sync")
+      :ok = NIF.write_batch(db, [{:id2str, "key", "value"}], sync: true)
+      NIF.close(db)
+
+      {:ok, db} = NIF.open("#{path}_sync")
+      assert {:ok, "value"} = NIF.get(db, :id2str, "key")
+      NIF.close(db)
+      File.rm_rf("#{path}_sync")
+    end
+
+    test "rejects unknown or malformed options", %{db: db} do
+      assert {:error, {:invalid_option, :bogus}} = NIF.put(db, :id2str, "k", "v", bogus: true)
+      assert {:error, {:invalid_option, :sync}} = NIF.delete(db, :id2str, "k", sync: 1)
+
+      assert {:error, {:invalid_option, :disable_wal}} =
+               NIF.write_batch(db, [{:id2str, "k", "v"}], disable_wal: :yes)
+
+      assert {:error, {:invalid_option, :low_pri}} =
+               NIF.insert_triples(db, [{1, 2, 3}], low_pri: nil)
+
+      assert :not_found = NIF.get(db, :id2str, "k")
+    end
+  end
+
   describe "atomicity" do
     test "write_batch is atomic - all or nothing", %{db: db} do
       # First write some data